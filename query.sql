@@ -63,7 +63,8 @@ CREATE TABLE services (
 CREATE TABLE patients (
     id_patient SERIAL PRIMARY KEY,
     id_user INT NULL REFERENCES users(id_user),
-    identity_number VARCHAR(20) UNIQUE NOT NULL,   -- cédula
+    identity_number VARCHAR(20) UNIQUE NOT NULL,   -- cédula (001-010190-0001A) o partida de nacimiento
+    identity_type VARCHAR(20) NOT NULL DEFAULT 'cedula' CHECK (identity_type IN ('cedula','partida_nacimiento')),
     first_name VARCHAR(50) NOT NULL,
     second_name VARCHAR(50),
     first_lastname VARCHAR(50) NOT NULL,
@@ -80,10 +81,15 @@ CREATE TABLE patients (
     current_medications TEXT,
     medical_background TEXT, -- info general (aparte de tabla medical_history)
     priority INT DEFAULT 0,
-    status VARCHAR(20) DEFAULT 'active',
+    -- Ciclo de vida: los cambios pasan por POST /patients/{id}/status
+    status VARCHAR(20) DEFAULT 'active'
+        CHECK (status IN ('pending_verification','active','inactive','transferred_out','deceased')),
+    status_date DATE,                 -- fecha efectiva del estado (ej. fecha de defunción o de traslado)
+    status_reason TEXT,
     created_at TIMESTAMP DEFAULT NOW(),
     updated_at TIMESTAMP,
-    deleted_at TIMESTAMP
+    deleted_at TIMESTAMP,
+    data_key TEXT -- DEK envuelta con la llave maestra ('id:base64'); cifra allergies, current_medications y medical_background
 );
 
 
@@ -119,10 +125,32 @@ CREATE TABLE admisionists (
     created_at TIMESTAMP DEFAULT NOW(),
     updated_at TIMESTAMP,
     deleted_at TIMESTAMP
-);''
+);
 
 
 -- Tabla de Citas Médicas
+-- btree_gist permite combinar = y && en las restricciones de exclusión de las citas
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Series de citas recurrentes (diálisis, control prenatal, fisioterapia...).
+-- rrule es un subconjunto de RFC 5545: FREQ, INTERVAL, COUNT/UNTIL y BYDAY
+CREATE TABLE appointment_series (
+    id_series SERIAL PRIMARY KEY,
+    id_patient INT NOT NULL REFERENCES patients(id_patient),
+    id_doctor INT NOT NULL REFERENCES doctors(id_doctor),
+    id_area INT NOT NULL REFERENCES areas(id_area),
+    id_service INT NOT NULL REFERENCES services(id_service),
+    rrule VARCHAR(200) NOT NULL,
+    dtstart TIMESTAMP NOT NULL,
+    duration_minutes INT CHECK (duration_minutes BETWEEN 5 AND 480),
+    building VARCHAR(10),
+    room VARCHAR(10),
+    notes TEXT,
+    created_by INT REFERENCES users(id_user),
+    created_at TIMESTAMP DEFAULT NOW()
+);
+CREATE INDEX idx_appointment_series_patient ON appointment_series (id_patient);
+
 CREATE TABLE medical_appointments (
     id_appointment SERIAL PRIMARY KEY,
     id_patient INT NOT NULL REFERENCES patients(id_patient),
@@ -130,14 +158,109 @@ CREATE TABLE medical_appointments (
     id_area INT NOT NULL REFERENCES areas(id_area),
     id_service INT NOT NULL REFERENCES services(id_service),
     appointment_datetime TIMESTAMP NOT NULL,
+    duration_minutes INT NOT NULL DEFAULT 30 CHECK (duration_minutes BETWEEN 5 AND 480),
+    -- Veces que se reprogramó; el detalle de cada cambio queda en audit_logs ('reschedule')
+    reschedule_count INT NOT NULL DEFAULT 0,
+    -- Serie a la que pertenece y posición de la ocurrencia dentro de la regla (desde 1)
+    id_series INT REFERENCES appointment_series(id_series),
+    series_index INT,
     building VARCHAR(10),
     room VARCHAR(10),
     notes TEXT,
     prescription TEXT,
-    status VARCHAR(20) DEFAULT 'pending', -- pending, confirmed, completed, canceled
+    -- Solo cambia con las transiciones de /appointments/{id}/... (ver AppointmentTransition)
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending','confirmed','checked_in','in_progress','completed','canceled','no_show')),
     created_at TIMESTAMP DEFAULT NOW(),
     updated_at TIMESTAMP,
-    deleted_at TIMESTAMP
+    deleted_at TIMESTAMP,
+    -- Ni el doctor, ni el paciente, ni la sala pueden tener dos citas activas que se crucen;
+    -- dos reservas simultáneas del mismo horario no pueden pasar las dos
+    CONSTRAINT excl_appointments_doctor EXCLUDE USING gist (
+        id_doctor WITH =,
+        tsrange(appointment_datetime, appointment_datetime + duration_minutes * INTERVAL '1 minute') WITH &&
+    ) WHERE (deleted_at IS NULL AND status NOT IN ('canceled', 'no_show')),
+    CONSTRAINT excl_appointments_patient EXCLUDE USING gist (
+        id_patient WITH =,
+        tsrange(appointment_datetime, appointment_datetime + duration_minutes * INTERVAL '1 minute') WITH &&
+    ) WHERE (deleted_at IS NULL AND status NOT IN ('canceled', 'no_show')),
+    CONSTRAINT excl_appointments_room EXCLUDE USING gist (
+        building WITH =,
+        room WITH =,
+        tsrange(appointment_datetime, appointment_datetime + duration_minutes * INTERVAL '1 minute') WITH &&
+    ) WHERE (deleted_at IS NULL AND status NOT IN ('canceled', 'no_show') AND building IS NOT NULL AND room IS NOT NULL)
+);
+CREATE INDEX idx_appointments_doctor_datetime ON medical_appointments (id_doctor, appointment_datetime);
+CREATE INDEX idx_appointments_patient_datetime ON medical_appointments (id_patient, appointment_datetime);
+CREATE INDEX idx_appointments_series ON medical_appointments (id_series, series_index);
+
+-- Lista de espera: pacientes que aceptan un turno liberado dentro de su rango de fechas
+-- (y de horas, si lo indican) con un doctor, una especialidad o un servicio
+CREATE TABLE waitlist_entries (
+    id_entry SERIAL PRIMARY KEY,
+    id_patient INT NOT NULL REFERENCES patients(id_patient),
+    id_doctor INT REFERENCES doctors(id_doctor),
+    id_speciality INT REFERENCES specialities(id_speciality),
+    id_service INT REFERENCES services(id_service),
+    date_from DATE NOT NULL,
+    date_to DATE NOT NULL,
+    time_from TIME,
+    time_to TIME,
+    notes TEXT,
+    -- waiting: en espera; offered: tiene una oferta pendiente; booked: aceptó un turno; canceled: salió de la lista
+    status VARCHAR(20) NOT NULL DEFAULT 'waiting'
+        CHECK (status IN ('waiting','offered','booked','canceled')),
+    created_by INT REFERENCES users(id_user),
+    created_at TIMESTAMP DEFAULT NOW(),
+    updated_at TIMESTAMP,
+    CHECK (num_nonnulls(id_doctor, id_speciality, id_service) > 0),
+    CHECK (date_from <= date_to),
+    CHECK ((time_from IS NULL) = (time_to IS NULL)),
+    CHECK (time_from < time_to)
+);
+CREATE INDEX idx_waitlist_entries_waiting ON waitlist_entries (created_at) WHERE status = 'waiting';
+
+-- Turno liberado ofrecido a una entrada de la lista; queda reservado hasta expires_at
+CREATE TABLE waitlist_offers (
+    id_offer SERIAL PRIMARY KEY,
+    id_entry INT NOT NULL REFERENCES waitlist_entries(id_entry),
+    id_patient INT NOT NULL REFERENCES patients(id_patient),
+    id_doctor INT NOT NULL REFERENCES doctors(id_doctor),
+    id_area INT NOT NULL REFERENCES areas(id_area),
+    id_service INT NOT NULL REFERENCES services(id_service),
+    appointment_datetime TIMESTAMP NOT NULL,
+    duration_minutes INT NOT NULL,
+    building VARCHAR(10),
+    room VARCHAR(10),
+    -- Cita cancelada que liberó el turno
+    id_canceled_appointment INT REFERENCES medical_appointments(id_appointment),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending','accepted','declined','expired')),
+    expires_at TIMESTAMP NOT NULL,
+    -- Cita creada al aceptar
+    id_appointment INT REFERENCES medical_appointments(id_appointment),
+    created_at TIMESTAMP DEFAULT NOW(),
+    responded_at TIMESTAMP
+);
+-- Un turno se ofrece a una sola persona a la vez
+CREATE UNIQUE INDEX idx_waitlist_offers_pending_slot ON waitlist_offers (id_doctor, appointment_datetime) WHERE status = 'pending';
+CREATE INDEX idx_waitlist_offers_entry ON waitlist_offers (id_entry);
+
+-- Recordatorios de citas: uno por cita, anticipación y horario. La fila se guarda
+-- antes de enviar el aviso, así un reinicio no lo repite; si la cita se reprograma
+-- el horario nuevo recibe sus propios recordatorios
+CREATE TABLE appointment_reminders (
+    id_reminder SERIAL PRIMARY KEY,
+    id_appointment INT NOT NULL REFERENCES medical_appointments(id_appointment) ON DELETE CASCADE,
+    offset_minutes INT NOT NULL CHECK (offset_minutes > 0),  -- anticipación configurada
+    appointment_datetime TIMESTAMP NOT NULL,                  -- horario recordado
+    token UUID NOT NULL UNIQUE,             -- de los enlaces para confirmar o cancelar
+    channel VARCHAR(10) CHECK (channel IN ('phone','email')), -- NULL si no tiene contacto
+    destination VARCHAR(100),
+    sent_at TIMESTAMP,
+    error TEXT,                             -- motivo si el envío falló; no se reintenta
+    created_at TIMESTAMP DEFAULT NOW(),
+    UNIQUE (id_appointment, offset_minutes, appointment_datetime)
 );
 
 -- Historial clinico
@@ -150,8 +273,10 @@ CREATE TABLE medical_history (
     notes TEXT,
     record_date TIMESTAMP DEFAULT NOW(),
     created_at TIMESTAMP DEFAULT NOW(),
-    updated_at TIMESTAMP
+    updated_at TIMESTAMP,
+    deleted_at TIMESTAMP
 );
+CREATE INDEX idx_medical_history_patient ON medical_history (id_patient, record_date);
 
 -- Resultados de laboratorio
 CREATE TABLE lab_results (
@@ -163,8 +288,10 @@ CREATE TABLE lab_results (
     result TEXT NOT NULL,
     result_date TIMESTAMP DEFAULT NOW(),
     created_at TIMESTAMP DEFAULT NOW(),
-    updated_at TIMESTAMP
+    updated_at TIMESTAMP,
+    deleted_at TIMESTAMP
 );
+CREATE INDEX idx_lab_results_patient ON lab_results (id_patient, result_date);
 
 -- Notificaciones
 CREATE TABLE notifications (
@@ -185,10 +312,17 @@ CREATE TABLE medical_documents (
     id_doctor INT REFERENCES doctors(id_doctor),
     document_type VARCHAR(100), -- 'imagen', 'PDF', 'radiografía'
     file_path TEXT NOT NULL,    -- ruta en el servidor o URL
+    file_name VARCHAR(255),     -- nombre original del archivo
+    content_type VARCHAR(100),  -- 'image/png', 'application/pdf', ...
+    file_size BIGINT,           -- tamaño en bytes
+    preview_path TEXT,          -- miniatura PNG junto al original
     description TEXT,
-    uploaded_at TIMESTAMP DEFAULT NOW()
+    uploaded_at TIMESTAMP DEFAULT NOW(),
+    deleted_at TIMESTAMP
 );
 
+CREATE INDEX idx_medical_documents_patient ON medical_documents (id_patient);
+
 
 -- Tabla de turnos virtuales
 CREATE TABLE virtual_turns (
@@ -255,3 +389,216 @@ CREATE TABLE audit_logs (
     performed_by INT REFERENCES users(id_user), -- quién hizo el cambio
     performed_at TIMESTAMP DEFAULT NOW()
 );
+-- Historial de un registro (cambios de estado, reprogramaciones, hora de llegada en la agenda)
+CREATE INDEX idx_audit_logs_record ON audit_logs (table_name, record_id, performed_at);
+-- Una fusión de pacientes ('merge') solo se puede revertir una vez
+CREATE UNIQUE INDEX idx_audit_logs_unmerge ON audit_logs ((old_data->>'id_audit')) WHERE action = 'unmerge';
+
+
+-- Registro de lecturas de datos clínicos sensibles
+CREATE TABLE access_logs (
+    id_access SERIAL PRIMARY KEY,
+    id_user INT NOT NULL REFERENCES users(id_user),        -- quién leyó
+    id_patient INT NOT NULL REFERENCES patients(id_patient), -- de quién es el expediente
+    resource VARCHAR(50) NOT NULL,         -- 'patient', 'medical_history', 'lab_result', 'medical_document'
+    record_id INT NOT NULL,                -- id del registro leído
+    accessed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_access_logs_patient ON access_logs (id_patient, accessed_at);
+
+
+-- Búsqueda de pacientes sin acentos y tolerante a errores (GET /patients/search)
+CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- unaccent() es STABLE; estas funciones IMMUTABLE permiten indexar las expresiones
+CREATE OR REPLACE FUNCTION immutable_unaccent(text) RETURNS text
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT
+    AS $$ SELECT public.unaccent('public.unaccent'::regdictionary, $1) $$;
+
+-- 'José Pérez López' -> 'jose perez lopez'
+CREATE OR REPLACE FUNCTION patient_search_name(first_name text, first_lastname text, second_lastname text) RETURNS text
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$ SELECT immutable_unaccent(lower(concat_ws(' ', first_name, first_lastname, second_lastname))) $$;
+
+-- '001-010190-0001a' -> '0010101900001A'
+CREATE OR REPLACE FUNCTION normalize_identity(text) RETURNS text
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT
+    AS $$ SELECT regexp_replace(upper($1), '[^0-9A-Z]', '', 'g') $$;
+
+-- '+505 8888-7777' -> '50588887777'
+CREATE OR REPLACE FUNCTION phone_digits(text) RETURNS text
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT
+    AS $$ SELECT regexp_replace($1, '[^0-9]', '', 'g') $$;
+
+CREATE INDEX idx_patients_search_name ON patients
+    USING gin (patient_search_name(first_name, first_lastname, second_lastname) gin_trgm_ops);
+CREATE INDEX idx_patients_search_identity ON patients
+    USING gin (normalize_identity(identity_number) gin_trgm_ops);
+CREATE INDEX idx_patients_search_phone ON patients
+    USING gin (phone_digits(phone) gin_trgm_ops);
+
+
+-- Contactos y tutores de un paciente: otro paciente (id_related_patient)
+-- o una persona externa (contact_name / contact_phone / contact_email)
+CREATE TABLE patient_contacts (
+    id_contact SERIAL PRIMARY KEY,
+    id_patient INT NOT NULL REFERENCES patients(id_patient),          -- paciente (ej. el menor)
+    id_related_patient INT NULL REFERENCES patients(id_patient),      -- el contacto, si también es paciente
+    contact_name VARCHAR(100),
+    contact_phone VARCHAR(20),
+    contact_email VARCHAR(100),
+    relationship VARCHAR(20) NOT NULL
+        CHECK (relationship IN ('madre','padre','tutor','conyuge','hijo','hermano','abuelo','otro')),
+    is_legal_guardian BOOLEAN NOT NULL DEFAULT FALSE,   -- puede ver y agendar por el paciente
+    is_emergency_contact BOOLEAN NOT NULL DEFAULT TRUE,
+    priority INT NOT NULL DEFAULT 0,                    -- orden de llamada en emergencias (menor = primero)
+    created_at TIMESTAMP DEFAULT NOW(),
+    updated_at TIMESTAMP,
+    deleted_at TIMESTAMP,
+    CHECK (id_related_patient IS NOT NULL OR contact_name IS NOT NULL),
+    CHECK (id_related_patient IS NULL OR id_related_patient <> id_patient)
+);
+
+CREATE INDEX idx_patient_contacts_patient ON patient_contacts (id_patient) WHERE deleted_at IS NULL;
+CREATE INDEX idx_patient_contacts_related ON patient_contacts (id_related_patient) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX idx_patient_contacts_unique_related ON patient_contacts (id_patient, id_related_patient)
+    WHERE deleted_at IS NULL AND id_related_patient IS NOT NULL;
+
+-- El usuario puede actuar por el paciente: es el propio paciente o su tutor legal
+-- (un contacto con is_legal_guardian que también es paciente con usuario)
+CREATE OR REPLACE FUNCTION can_act_for_patient(p_id_user INT, p_id_patient INT) RETURNS BOOLEAN
+    LANGUAGE sql STABLE
+    AS $$
+    SELECT EXISTS (
+        SELECT 1 FROM patients
+        WHERE id_patient = p_id_patient AND id_user = p_id_user AND deleted_at IS NULL
+    ) OR EXISTS (
+        SELECT 1
+        FROM patient_contacts c
+        JOIN patients g ON g.id_patient = c.id_related_patient
+        WHERE c.id_patient = p_id_patient
+          AND c.is_legal_guardian
+          AND c.deleted_at IS NULL
+          AND g.id_user = p_id_user
+          AND g.deleted_at IS NULL
+    )
+    $$;
+
+
+-- Autorregistro de pacientes (POST /auth/register): los datos esperan aquí hasta
+-- que el paciente verifica el código enviado a su teléfono o correo
+CREATE TABLE patient_registrations (
+    id_registration UUID PRIMARY KEY,
+    payload JSONB NOT NULL,                 -- datos de CreatePatient, sin información clínica
+    password_hash TEXT NOT NULL,
+    channel VARCHAR(10) NOT NULL CHECK (channel IN ('phone','email')),
+    destination VARCHAR(100) NOT NULL,      -- teléfono o correo al que se envió el código
+    code_hash TEXT NOT NULL,                -- bcrypt del código de 6 dígitos
+    attempts INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP NOT NULL,
+    verified_at TIMESTAMP,
+    id_patient INT REFERENCES patients(id_patient),  -- paciente creado al verificar
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+
+-- Importaciones masivas de pacientes (CSV/XLSX): resumen y errores por fila
+CREATE TABLE patient_imports (
+    id_import SERIAL PRIMARY KEY,
+    file_name VARCHAR(255),
+    dry_run BOOLEAN NOT NULL DEFAULT FALSE,   -- solo validó, no guardó pacientes
+    total_rows INT NOT NULL DEFAULT 0,
+    valid_rows INT NOT NULL DEFAULT 0,
+    imported_rows INT NOT NULL DEFAULT 0,
+    failed_rows INT NOT NULL DEFAULT 0,
+    errors JSONB NOT NULL DEFAULT '[]'::jsonb, -- [{row, identity_number, field, code, message}]
+    performed_by INT NULL REFERENCES users(id_user) ON DELETE SET NULL, -- NULL si se ejecutó por CLI
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+
+-- Efectos del estado 'deceased' que se aplican aunque el registro no pase por la API:
+-- un paciente fallecido no recibe turnos nuevos y no se le crean notificaciones
+CREATE OR REPLACE FUNCTION reject_turns_for_deceased() RETURNS TRIGGER
+    LANGUAGE plpgsql
+    AS $$
+    BEGIN
+        IF EXISTS (SELECT 1 FROM patients WHERE id_patient = NEW.id_patient AND status = 'deceased') THEN
+            RAISE EXCEPTION 'El paciente % falleció; no puede recibir turnos', NEW.id_patient
+                USING ERRCODE = 'check_violation';
+        END IF;
+        RETURN NEW;
+    END
+    $$;
+
+CREATE TRIGGER trg_virtual_turns_deceased
+    BEFORE INSERT ON virtual_turns
+    FOR EACH ROW EXECUTE FUNCTION reject_turns_for_deceased();
+
+CREATE OR REPLACE FUNCTION skip_notifications_for_deceased() RETURNS TRIGGER
+    LANGUAGE plpgsql
+    AS $$
+    BEGIN
+        -- RETURN NULL descarta la fila sin error
+        IF EXISTS (SELECT 1 FROM patients WHERE id_user = NEW.id_user AND status = 'deceased') THEN
+            RETURN NULL;
+        END IF;
+        RETURN NEW;
+    END
+    $$;
+
+CREATE TRIGGER trg_notifications_deceased
+    BEFORE INSERT ON notifications
+    FOR EACH ROW EXECUTE FUNCTION skip_notifications_for_deceased();
+
+
+-- Horario semanal de cada doctor: bloques por día con turnos de `slot_minutes`.
+-- Las citas solo se agendan al inicio de un turno dentro de un bloque.
+CREATE TABLE doctor_schedules (
+    id_schedule SERIAL PRIMARY KEY,
+    id_doctor INT NOT NULL REFERENCES doctors(id_doctor),
+    id_area INT NOT NULL REFERENCES areas(id_area),
+    day_of_week SMALLINT NOT NULL CHECK (day_of_week BETWEEN 1 AND 7), -- ISO: 1 lunes ... 7 domingo
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL,
+    slot_minutes INT NOT NULL CHECK (slot_minutes BETWEEN 5 AND 240),
+    building VARCHAR(10),
+    room VARCHAR(10),
+    created_at TIMESTAMP DEFAULT NOW(),
+    updated_at TIMESTAMP,
+    deleted_at TIMESTAMP,
+    CHECK (start_time < end_time)
+);
+CREATE INDEX idx_doctor_schedules_doctor ON doctor_schedules (id_doctor, day_of_week) WHERE deleted_at IS NULL;
+
+-- Ausencias por fecha (vacaciones, congresos, incapacidades). Sin horas cubren los días
+-- completos; con horas van de start_date + start_time a end_date + end_time.
+CREATE TABLE doctor_schedule_exceptions (
+    id_exception SERIAL PRIMARY KEY,
+    id_doctor INT NOT NULL REFERENCES doctors(id_doctor),
+    exception_type VARCHAR(20) NOT NULL CHECK (exception_type IN ('vacation', 'congress', 'sick_leave', 'other')),
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    start_time TIME,
+    end_time TIME,
+    notes TEXT,
+    created_by INT REFERENCES users(id_user),
+    created_at TIMESTAMP DEFAULT NOW(),
+    deleted_at TIMESTAMP,
+    CHECK (start_date <= end_date),
+    CHECK ((start_time IS NULL) = (end_time IS NULL)),
+    CHECK (start_time IS NULL OR start_date < end_date OR start_time < end_time)
+);
+CREATE INDEX idx_doctor_schedule_exceptions_doctor ON doctor_schedule_exceptions (id_doctor, start_date, end_date)
+    WHERE deleted_at IS NULL;
+
+-- Calendario (.ics) de cada usuario: la URL secreta lleva el token. Generar otro
+-- token invalida la URL anterior
+CREATE TABLE calendar_feeds (
+    id_user INT PRIMARY KEY REFERENCES users(id_user) ON DELETE CASCADE,
+    token UUID NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT NOW(),
+    last_accessed_at TIMESTAMP
+);
@@ -1,33 +1,769 @@
-use crate::domain::appointment::{Appointment, CreateAppointment, UpdateAppointment};
+use crate::application::waitlist_service::offer_slot;
+use crate::domain::appointment::{
+    Appointment, AppointmentFilter, AppointmentStatus, AppointmentTransition, BookAppointment, CreateAppointment, RescheduleAppointment,
+    TransitionAppointment, TransitionResult, UpdateAppointment, DEFAULT_DURATION_MINUTES, DELETABLE, RESCHEDULABLE,
+};
+use crate::domain::appointment_series::{
+    CreateAppointmentSeries, OccurrenceChange, OccurrenceConflict, RecurrenceRule, SeriesChange, SeriesDetail,
+    SeriesOccurrence, SeriesResult, SeriesScope, UpdateSeriesOccurrences,
+};
+use crate::domain::audit_log::AuditLog;
+use crate::domain::doctor_schedule::{check_slot_filter, DoctorSchedule, FreeSlot, SlotFilter, DEFAULT_SLOT_LIMIT};
 use crate::infrastructure::appointment_repository::AppointmentRepository;
+use crate::infrastructure::doctor_schedule_repository::DoctorScheduleRepository;
+use crate::infrastructure::notifier::Notifier;
+use crate::infrastructure::waitlist_repository::WaitlistRepository;
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::waitlist::{WaitlistPolicy, WaitlistSlot};
+use crate::domain::patient::STATUS_DECEASED;
+use crate::helpers::errors::{InvalidState, Overlap};
+use crate::helpers::auth::AuthUser;
 use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime};
+use uuid::Uuid;
+use validator::{ValidationError, ValidationErrors};
 
-pub struct AppointmentService<R: AppointmentRepository> {
+pub struct AppointmentService<R: AppointmentRepository, S: DoctorScheduleRepository, W: WaitlistRepository, N: Notifier> {
     repo: R,
+    schedules: S,
+    waitlist: W,
+    notifier: N,
+    policy: WaitlistPolicy,
 }
 
-impl<R: AppointmentRepository> AppointmentService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+fn schedule_error(field: &'static str, message: String) -> ValidationErrors {
+    let mut e = ValidationError::new("schedule");
+    e.message = Some(message.into());
+    let mut errors = ValidationErrors::new();
+    errors.add(field, e);
+    errors
+}
+
+fn rrule_error(message: String) -> ValidationErrors {
+    let mut e = ValidationError::new("rrule");
+    e.message = Some(message.into());
+    let mut errors = ValidationErrors::new();
+    errors.add("rrule", e);
+    errors
+}
+
+/// Campo y mensaje del primer error, para informarlo por cita en las series
+fn first_error(errors: &ValidationErrors) -> (&'static str, String) {
+    errors
+        .field_errors()
+        .into_iter()
+        .find_map(|(field, list)| list.iter().find_map(|e| e.message.as_ref()).map(|m| (field, m.to_string())))
+        .unwrap_or(("appointment_datetime", "El doctor no atiende en ese horario".to_string()))
+}
+
+fn check_transition(transition: AppointmentTransition, status: AppointmentStatus) -> Result<()> {
+    if !transition.allowed_from().contains(&status) {
+        return Err(InvalidState(format!(
+            "La transición '{}' no se permite desde el estado '{}'",
+            transition.name(),
+            status
+        ))
+        .into());
     }
+    Ok(())
+}
 
-    pub async fn get_all(&self) -> Result<Vec<Appointment>> {
-        self.repo.get_all().await
+impl<R: AppointmentRepository, S: DoctorScheduleRepository, W: WaitlistRepository, N: Notifier>
+    AppointmentService<R, S, W, N>
+{
+    pub fn new(repo: R, schedules: S, waitlist: W, notifier: N, policy: WaitlistPolicy) -> Self {
+        Self { repo, schedules, waitlist, notifier, policy }
+    }
+
+    pub async fn get_all(&self, filter: &AppointmentFilter, page: &PageParams) -> Result<Page<Appointment>> {
+        self.repo.get_all(filter, page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<Appointment>> {
         self.repo.get_by_id(id).await
     }
 
-    pub async fn create(&self, data: CreateAppointment) -> Result<Appointment> {
-        self.repo.create(data).await
+    /// El personal agenda para cualquier paciente; un paciente, para sí mismo o
+    /// para quienes tiene a cargo como tutor legal. None si no tiene permiso.
+    pub async fn create(&self, mut data: CreateAppointment, auth: &AuthUser) -> Result<Option<Appointment>> {
+        if !self.can_book_for(data.id_patient, auth).await? {
+            return Ok(None);
+        }
+        // Sin duración, edificio ni sala se usan los del bloque del horario
+        let block = self
+            .check_schedule(data.id_doctor, data.id_area, data.appointment_datetime, data.duration_minutes)
+            .await?;
+        if let Some(block) = block {
+            data.duration_minutes = data.duration_minutes.or(Some(block.slot_minutes));
+            data.building = data.building.or(block.building);
+            data.room = data.room.or(block.room);
+        }
+        self.check_hold(
+            data.id_doctor,
+            data.id_patient,
+            data.appointment_datetime,
+            data.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES),
+        )
+        .await?;
+        let appointment = self.repo.create(data, Local::now().naive_local()).await?;
+        Ok(Some(appointment))
+    }
+
+    /// Permiso para agendar al paciente; error si el paciente falleció
+    async fn can_book_for(&self, id_patient: i32, auth: &AuthUser) -> Result<bool> {
+        if !auth.is_staff() && !self.repo.can_act_for(id_patient, auth.id_user).await? {
+            return Ok(false);
+        }
+        if self.repo.get_patient_status(id_patient).await?.as_deref() == Some(STATUS_DECEASED) {
+            return Err(InvalidState("El paciente falleció; no se le pueden agendar citas".to_string()).into());
+        }
+        Ok(true)
+    }
+
+    /// Turnos libres según el horario de los doctores que cumplen el filtro
+    pub async fn find_free_slots(&self, filter: &SlotFilter) -> Result<Vec<FreeSlot>> {
+        let now = Local::now().naive_local();
+        let (from, to) = check_slot_filter(filter, now.date())?;
+        let limit = filter.limit.unwrap_or(DEFAULT_SLOT_LIMIT);
+        self.schedules.find_free_slots(filter, from, to, now, limit).await
+    }
+
+    /// Reserva un turno del horario del doctor con los mismos permisos que `create`.
+    /// Si otra reserva toma el turno a la vez, la restricción de exclusión de la
+    /// tabla rechaza la segunda con un 409.
+    pub async fn book(&self, data: BookAppointment, auth: &AuthUser) -> Result<Option<Appointment>> {
+        if data.appointment_datetime <= Local::now().naive_local() {
+            return Err(schedule_error("appointment_datetime", "El turno ya pasó".to_string()).into());
+        }
+        let Some(block) = self.schedules.find_block(data.id_doctor, data.appointment_datetime).await? else {
+            return Err(schedule_error("appointment_datetime", "El doctor no atiende en ese horario".to_string()).into());
+        };
+        let appointment = CreateAppointment {
+            id_patient: data.id_patient,
+            id_doctor: data.id_doctor,
+            id_area: block.id_area,
+            id_service: data.id_service,
+            appointment_datetime: data.appointment_datetime,
+            duration_minutes: None,
+            building: None,
+            room: None,
+            notes: data.notes,
+        };
+        self.create(appointment, auth).await
     }
 
-    pub async fn update(&self, id: i32, data: UpdateAppointment) -> Result<Option<Appointment>> {        
+    pub async fn update(&self, id: i32, data: UpdateAppointment) -> Result<Option<Appointment>> {
         self.repo.update(id, data).await
     }
 
+    /// Mueve la cita a otro horario con las mismas reglas que al crearla, guarda el
+    /// cambio en su historial y avisa al paciente. Puede hacerlo el personal, el
+    /// paciente o su tutor legal. None si la cita no existe.
+    pub async fn reschedule(
+        &self,
+        id: i32,
+        data: RescheduleAppointment,
+        auth: &AuthUser,
+    ) -> Result<Option<TransitionResult>> {
+        let Some(appointment) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        if !auth.is_staff() && !self.repo.can_act_for(appointment.id_patient, auth.id_user).await? {
+            return Ok(Some(TransitionResult::Forbidden(
+                "Solo el paciente, su tutor legal o el personal pueden reprogramar la cita",
+            )));
+        }
+        if !RESCHEDULABLE.contains(&appointment.status) {
+            return Err(InvalidState(format!(
+                "Una cita en estado '{}' no se puede reprogramar",
+                appointment.status
+            ))
+            .into());
+        }
+        if data.appointment_datetime <= Local::now().naive_local() {
+            return Err(schedule_error("appointment_datetime", "La nueva fecha ya pasó".to_string()).into());
+        }
+        let duration_minutes = data.duration_minutes.unwrap_or(appointment.duration_minutes);
+        if data.appointment_datetime == appointment.appointment_datetime
+            && duration_minutes == appointment.duration_minutes
+        {
+            return Err(InvalidState("La cita ya está en ese horario".to_string()).into());
+        }
+        self.check_schedule(
+            appointment.id_doctor,
+            appointment.id_area,
+            data.appointment_datetime,
+            Some(duration_minutes),
+        )
+        .await?;
+        self.check_hold(appointment.id_doctor, appointment.id_patient, data.appointment_datetime, duration_minutes)
+            .await?;
+
+        let Some(updated) = self.repo.reschedule(&appointment, &data, auth.id_user).await? else {
+            return Err(InvalidState("La cita cambió entretanto; intente de nuevo".to_string()).into());
+        };
+
+        let body = format!(
+            "Su cita del {} fue reprogramada para el {}. Motivo: {}",
+            appointment.appointment_datetime.format("%d/%m/%Y %H:%M"),
+            updated.appointment_datetime.format("%d/%m/%Y %H:%M"),
+            data.reason
+        );
+        self.notify_reschedule(updated.id_patient, &body).await?;
+        Ok(Some(TransitionResult::Done(Box::new(updated))))
+    }
+
+    /// Avisa al paciente de un cambio de horario. El cambio ya quedó guardado:
+    /// un aviso que falla no lo revierte.
+    async fn notify_reschedule(&self, id_patient: i32, body: &str) -> Result<()> {
+        if let Some((channel, to)) = self.repo.get_patient_contact(id_patient).await? {
+            if let Err(e) = self.notifier.send(&channel, &to, "Cita reprogramada", body).await {
+                eprintln!("No se pudo avisar la reprogramación al paciente {}: {:?}", id_patient, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// None si la cita no existe
+    pub async fn get_reschedules(&self, id: i32) -> Result<Option<Vec<AuditLog>>> {
+        if self.repo.get_by_id(id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.repo.get_reschedules(id).await?))
+    }
+
+    /// Crea una serie de citas recurrentes con los mismos permisos que `create`.
+    /// Cada cita se valida contra el horario del doctor y contra las demás citas:
+    /// con `skip_conflicts` se crean solo las que no tienen conflicto; si no, un
+    /// conflicto rechaza la serie entera. Con `dry_run` se devuelve el resultado sin
+    /// guardar nada. None si no tiene permiso.
+    pub async fn create_series(
+        &self,
+        data: CreateAppointmentSeries,
+        auth: &AuthUser,
+        dry_run: bool,
+    ) -> Result<Option<SeriesResult>> {
+        if !self.can_book_for(data.id_patient, auth).await? {
+            return Ok(None);
+        }
+        let rule: RecurrenceRule = data.rrule.parse().map_err(rrule_error)?;
+        let now = Local::now().naive_local();
+        if data.appointment_datetime <= now {
+            return Err(schedule_error("appointment_datetime", "La primera cita ya pasó".to_string()).into());
+        }
+        let dates = rule.occurrences(data.appointment_datetime).map_err(rrule_error)?;
+        let total = dates.len();
+
+        let mut occurrences = Vec::with_capacity(total);
+        let mut conflicts = Vec::new();
+        for (i, at) in dates.into_iter().enumerate() {
+            let series_index = i as i32 + 1;
+            let checked = match self.check_schedule(data.id_doctor, data.id_area, at, data.duration_minutes).await {
+                // Sin duración, edificio ni sala se usan los del bloque de cada cita
+                Ok(block) => {
+                    let occurrence = SeriesOccurrence {
+                        series_index,
+                        appointment_datetime: at,
+                        duration_minutes: data
+                            .duration_minutes
+                            .or(block.as_ref().map(|b| b.slot_minutes))
+                            .unwrap_or(DEFAULT_DURATION_MINUTES),
+                        building: data.building.clone().or_else(|| block.as_ref().and_then(|b| b.building.clone())),
+                        room: data.room.clone().or_else(|| block.as_ref().and_then(|b| b.room.clone())),
+                    };
+                    self.check_hold(data.id_doctor, data.id_patient, at, occurrence.duration_minutes)
+                        .await
+                        .map(|_| occurrence)
+                }
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok(occurrence) => occurrences.push(occurrence),
+                Err(e) => {
+                    let detail = match (e.downcast_ref::<ValidationErrors>(), e.downcast_ref::<Overlap>()) {
+                        (Some(errors), _) => first_error(errors).1,
+                        (_, Some(overlap)) => overlap.detail.clone(),
+                        _ => return Err(e),
+                    };
+                    conflicts.push(OccurrenceConflict {
+                        series_index,
+                        appointment_datetime: at,
+                        detail,
+                        conflicting_id: None,
+                    });
+                }
+            }
+        }
+
+        let result = self
+            .repo
+            .create_series(&data, &rule.to_string(), &occurrences, conflicts, auth.id_user, dry_run, now)
+            .await?;
+        if !dry_run {
+            if let Some(first) = result.conflicts.first() {
+                if result.appointments.is_empty() || !data.skip_conflicts {
+                    return Err(Overlap {
+                        detail: format!(
+                            "{} de {} citas de la serie tienen conflicto; la primera, el {}: {}",
+                            result.conflicts.len(),
+                            total,
+                            first.appointment_datetime.format("%Y-%m-%d %H:%M"),
+                            first.detail
+                        ),
+                        conflicting_id: first.conflicting_id,
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(Some(result))
+    }
+
+    /// None si la serie no existe
+    pub async fn get_series(&self, id_series: i32) -> Result<Option<SeriesDetail>> {
+        let Some(series) = self.repo.get_series(id_series).await? else {
+            return Ok(None);
+        };
+        let appointments = self.repo.get_series_appointments(id_series).await?;
+        Ok(Some(SeriesDetail { series, appointments }))
+    }
+
+    /// Citas de la serie de `appointment` a las que aplica `scope` y que cumplen
+    /// `eligible`; las demás (pasadas, canceladas, atendidas) no se tocan.
+    /// Con `This` la propia cita tiene que cumplirlo.
+    async fn series_scope(
+        &self,
+        appointment: Appointment,
+        scope: SeriesScope,
+        eligible: impl Fn(&Appointment) -> bool,
+    ) -> Result<Vec<Appointment>> {
+        let Some(id_series) = appointment.id_series else {
+            return Err(InvalidState("La cita no pertenece a una serie".to_string()).into());
+        };
+        let selected: Vec<Appointment> = match scope {
+            SeriesScope::This => vec![appointment].into_iter().filter(&eligible).collect(),
+            SeriesScope::Following => {
+                let series = self.repo.get_series_appointments(id_series).await?;
+                series
+                    .into_iter()
+                    .filter(|a| a.series_index >= appointment.series_index && eligible(a))
+                    .collect()
+            }
+            SeriesScope::All => {
+                let series = self.repo.get_series_appointments(id_series).await?;
+                series.into_iter().filter(&eligible).collect()
+            }
+        };
+        if selected.is_empty() {
+            return Err(InvalidState(
+                "No hay citas futuras pendientes o confirmadas de la serie en ese alcance".to_string(),
+            )
+            .into());
+        }
+        Ok(selected)
+    }
+
+    /// Cambia hora, duración, edificio, sala o notas de una cita de la serie, de
+    /// ella y las siguientes o de toda la serie, en una sola transacción. Las que
+    /// cambian de horario se reprograman con las mismas reglas que `reschedule` y
+    /// el paciente recibe un solo aviso. None si la cita no existe.
+    pub async fn update_series(
+        &self,
+        id: i32,
+        scope: SeriesScope,
+        data: UpdateSeriesOccurrences,
+        auth: &AuthUser,
+    ) -> Result<Option<SeriesChange>> {
+        let Some(appointment) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        if !auth.is_staff() && !self.repo.can_act_for(appointment.id_patient, auth.id_user).await? {
+            return Ok(Some(SeriesChange::Forbidden(
+                "Solo el paciente, su tutor legal o el personal pueden cambiar la serie",
+            )));
+        }
+        if data.changes_schedule() && data.reason.is_none() {
+            let mut e = ValidationError::new("required");
+            e.message = Some("El motivo es obligatorio para cambiar el horario".into());
+            let mut errors = ValidationErrors::new();
+            errors.add("reason", e);
+            return Err(errors.into());
+        }
+        if !data.changes_schedule() && data.building.is_none() && data.room.is_none() && data.notes.is_none() {
+            return Err(InvalidState("No hay cambios que aplicar".to_string()).into());
+        }
+
+        let now = Local::now().naive_local();
+        let selected = self
+            .series_scope(appointment, scope, |a| {
+                RESCHEDULABLE.contains(&a.status) && a.appointment_datetime > now
+            })
+            .await?;
+
+        let mut changes = Vec::with_capacity(selected.len());
+        for from in selected {
+            let change = OccurrenceChange {
+                appointment_datetime: data
+                    .time
+                    .map(|t| from.appointment_datetime.date().and_time(t))
+                    .unwrap_or(from.appointment_datetime),
+                duration_minutes: data.duration_minutes.unwrap_or(from.duration_minutes),
+                from,
+            };
+            if change.is_reschedule() {
+                let checked = if change.appointment_datetime <= now {
+                    Err(schedule_error("time", "La nueva hora ya pasó".to_string()).into())
+                } else {
+                    self.check_schedule(
+                        change.from.id_doctor,
+                        change.from.id_area,
+                        change.appointment_datetime,
+                        Some(change.duration_minutes),
+                    )
+                    .await
+                    .map(|_| ())
+                };
+                let checked = match checked {
+                    Ok(()) => {
+                        self.check_hold(
+                            change.from.id_doctor,
+                            change.from.id_patient,
+                            change.appointment_datetime,
+                            change.duration_minutes,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                // El error dice a qué cita de la serie corresponde
+                if let Err(e) = checked {
+                    return Err(match e.downcast::<ValidationErrors>() {
+                        Ok(errors) => {
+                            let (field, message) = first_error(&errors);
+                            schedule_error(
+                                field,
+                                format!(
+                                    "Cita {} del {}: {}",
+                                    change.from.id_appointment,
+                                    change.from.appointment_datetime.format("%Y-%m-%d"),
+                                    message
+                                ),
+                            )
+                            .into()
+                        }
+                        Err(e) => e,
+                    });
+                }
+            }
+            changes.push(change);
+        }
+
+        let Some(updated) = self.repo.update_occurrences(&changes, &data, auth.id_user).await? else {
+            return Err(InvalidState("Una cita de la serie cambió entretanto; intente de nuevo".to_string()).into());
+        };
+
+        let moved: Vec<String> = changes
+            .iter()
+            .zip(&updated)
+            .filter(|(change, _)| change.is_reschedule())
+            .map(|(change, a)| {
+                format!(
+                    "{} → {}",
+                    change.from.appointment_datetime.format("%d/%m/%Y %H:%M"),
+                    a.appointment_datetime.format("%d/%m/%Y %H:%M")
+                )
+            })
+            .collect();
+        if let (Some(first), false) = (updated.first(), moved.is_empty()) {
+            let body = format!(
+                "Se reprogramaron {} citas de su serie: {}. Motivo: {}",
+                moved.len(),
+                moved.join(", "),
+                data.reason.as_deref().unwrap_or_default()
+            );
+            self.notify_reschedule(first.id_patient, &body).await?;
+        }
+        Ok(Some(SeriesChange::Done(updated)))
+    }
+
+    /// Cancela una cita de la serie, ella y las siguientes o toda la serie, con el
+    /// mismo motivo y los mismos permisos que la cancelación de una cita. Las citas
+    /// pasadas o ya cerradas no se tocan. None si la cita no existe.
+    pub async fn cancel_series(
+        &self,
+        id: i32,
+        scope: SeriesScope,
+        data: TransitionAppointment,
+        auth: &AuthUser,
+    ) -> Result<Option<SeriesChange>> {
+        let Some(appointment) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        if let Some(message) = self.forbidden_reason(&appointment, AppointmentTransition::Cancel, auth).await? {
+            return Ok(Some(SeriesChange::Forbidden(message)));
+        }
+        let Some(reason) = data.reason else {
+            let mut e = ValidationError::new("required");
+            e.message = Some("El motivo es obligatorio para cancelar".into());
+            let mut errors = ValidationErrors::new();
+            errors.add("reason", e);
+            return Err(errors.into());
+        };
+
+        let now = Local::now().naive_local();
+        let allowed = AppointmentTransition::Cancel.allowed_from();
+        let selected = self
+            .series_scope(appointment, scope, |a| allowed.contains(&a.status) && a.appointment_datetime > now)
+            .await?;
+
+        let Some(canceled) = self.repo.cancel_occurrences(&selected, &reason, auth.id_user).await? else {
+            return Err(InvalidState("Una cita de la serie cambió de estado; intente de nuevo".to_string()).into());
+        };
+        for appointment in &canceled {
+            self.release_slot(appointment).await;
+        }
+        Ok(Some(SeriesChange::Done(canceled)))
+    }
+
+    /// Comprueba que el doctor atienda a esa hora: dentro de un bloque de su horario,
+    /// en el área del bloque, al inicio de un turno y sin ausencia registrada.
+    /// Sin duración la cita dura un turno. Un doctor sin horario cargado no
+    /// admite citas. Devuelve el bloque que contiene la cita.
+    async fn check_schedule(
+        &self,
+        id_doctor: i32,
+        id_area: i32,
+        at: NaiveDateTime,
+        duration_minutes: Option<i32>,
+    ) -> Result<Option<DoctorSchedule>> {
+        let block = self.schedules.find_block(id_doctor, at).await?;
+        let minutes = match &block {
+            Some(block) => {
+                if block.id_area != id_area {
+                    return Err(schedule_error(
+                        "id_area",
+                        format!("En ese horario el doctor atiende en el área {}", block.id_area),
+                    )
+                    .into());
+                }
+                if !block.fits_slot(at.time()) {
+                    return Err(schedule_error(
+                        "appointment_datetime",
+                        format!(
+                            "La cita debe empezar al inicio de un turno de {} minutos desde las {}",
+                            block.slot_minutes,
+                            block.start_time.format("%H:%M")
+                        ),
+                    )
+                    .into());
+                }
+                let minutes = duration_minutes.unwrap_or(block.slot_minutes);
+                if i64::from(minutes) > (block.end_time - at.time()).num_minutes() {
+                    return Err(schedule_error(
+                        "duration_minutes",
+                        format!("La cita debe terminar antes de las {}", block.end_time.format("%H:%M")),
+                    )
+                    .into());
+                }
+                minutes
+            }
+            None if self.schedules.get_schedules(id_doctor).await?.is_empty() => {
+                return Err(schedule_error(
+                    "id_doctor",
+                    "El doctor no tiene horario registrado; cargue su horario antes de agendar".to_string(),
+                )
+                .into());
+            }
+            None => {
+                return Err(schedule_error("appointment_datetime", "El doctor no atiende en ese horario".to_string()).into());
+            }
+        };
+
+        let end = at + Duration::minutes(i64::from(minutes));
+        if let Some(exception) = self.schedules.find_exception(id_doctor, at, end).await? {
+            return Err(schedule_error(
+                "appointment_datetime",
+                format!(
+                    "El doctor está ausente ({}) del {} al {}",
+                    exception.exception_type, exception.start_date, exception.end_date
+                ),
+            )
+            .into());
+        }
+        Ok(block)
+    }
+
+    /// Solo citas completadas, canceladas o no presentadas: una activa se cancela
+    /// con su transición para que quede en el historial y el turno se ofrezca a
+    /// la lista de espera. None si no existe.
     pub async fn delete(&self, id: i32) -> Result<Option<Appointment>> {
-        self.repo.delete(id).await
+        let Some(appointment) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        if !DELETABLE.contains(&appointment.status) {
+            return Err(InvalidState(format!(
+                "Una cita en estado '{}' no se puede eliminar; cancélela primero",
+                appointment.status
+            ))
+            .into());
+        }
+        let Some(deleted) = self.repo.delete(id, appointment.status).await? else {
+            return Err(InvalidState("La cita cambió entretanto; intente de nuevo".to_string()).into());
+        };
+        Ok(Some(deleted))
+    }
+
+    /// Aplica una transición de estado. None si la cita no existe; error de estado
+    /// si no se permite desde el estado actual.
+    pub async fn transition(
+        &self,
+        id: i32,
+        transition: AppointmentTransition,
+        data: TransitionAppointment,
+        auth: &AuthUser,
+    ) -> Result<Option<TransitionResult>> {
+        let Some(appointment) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        if let Some(message) = self.forbidden_reason(&appointment, transition, auth).await? {
+            return Ok(Some(TransitionResult::Forbidden(message)));
+        }
+
+        check_transition(transition, appointment.status)?;
+        if transition == AppointmentTransition::Cancel && data.reason.is_none() {
+            let mut e = ValidationError::new("required");
+            e.message = Some("El motivo es obligatorio para cancelar".into());
+            let mut errors = ValidationErrors::new();
+            errors.add("reason", e);
+            return Err(errors.into());
+        }
+        if transition == AppointmentTransition::NoShow && appointment.appointment_datetime > Local::now().naive_local() {
+            return Err(InvalidState("La cita todavía no ha llegado".to_string()).into());
+        }
+
+        // El UPDATE vuelve a comprobar el estado por si otra petición lo cambió
+        let updated = self
+            .repo
+            .transition(id, appointment.status, transition, data.reason.as_deref(), Some(auth.id_user))
+            .await?;
+        let Some(updated) = updated else {
+            return Err(InvalidState("La cita cambió de estado; intente de nuevo".to_string()).into());
+        };
+        if transition == AppointmentTransition::Cancel {
+            self.release_slot(&updated).await;
+        }
+        Ok(Some(TransitionResult::Done(Box::new(updated))))
+    }
+
+    /// Confirma o cancela la cita desde el enlace de un recordatorio, sin iniciar
+    /// sesión: el token del enlace identifica la cita. None si el token no existe.
+    /// El enlace deja de servir si la cita se reprogramó o ya pasó.
+    pub async fn respond_to_reminder(
+        &self,
+        token: Uuid,
+        transition: AppointmentTransition,
+        data: TransitionAppointment,
+    ) -> Result<Option<Appointment>> {
+        let Some(reminder) = self.repo.get_reminder(token).await? else {
+            return Ok(None);
+        };
+        let Some(appointment) = self.repo.get_by_id(reminder.id_appointment).await? else {
+            return Ok(None);
+        };
+        if appointment.appointment_datetime != reminder.appointment_datetime {
+            return Err(InvalidState(
+                "La cita cambió de horario; use el enlace del recordatorio más reciente".to_string(),
+            )
+            .into());
+        }
+        if appointment.appointment_datetime <= Local::now().naive_local() {
+            return Err(InvalidState("La cita ya pasó".to_string()).into());
+        }
+        check_transition(transition, appointment.status)?;
+
+        let reason = match (transition, data.reason) {
+            (AppointmentTransition::Cancel, None) => Some("Cancelada desde el recordatorio".to_string()),
+            (_, reason) => reason,
+        };
+        let updated = self
+            .repo
+            .transition(appointment.id_appointment, appointment.status, transition, reason.as_deref(), None)
+            .await?;
+        let Some(updated) = updated else {
+            return Err(InvalidState("La cita cambió de estado; intente de nuevo".to_string()).into());
+        };
+        if transition == AppointmentTransition::Cancel {
+            self.release_slot(&updated).await;
+        }
+        Ok(Some(updated))
+    }
+
+    /// Ofrece el turno de una cita cancelada a la lista de espera. La cancelación
+    /// ya quedó guardada: si la oferta falla solo se registra el error.
+    async fn release_slot(&self, appointment: &Appointment) {
+        let slot = WaitlistSlot::from(appointment);
+        if let Err(e) = offer_slot(&self.waitlist, &self.notifier, &self.policy, &slot).await {
+            eprintln!("No se pudo ofrecer el turno de la cita {}: {:?}", appointment.id_appointment, e);
+        }
     }
-}
\ No newline at end of file
+
+    /// Un turno ofrecido a la lista de espera queda reservado para ese paciente
+    /// hasta que responde o vence la oferta
+    async fn check_hold(&self, id_doctor: i32, id_patient: i32, at: NaiveDateTime, duration_minutes: i32) -> Result<()> {
+        let end = at + Duration::minutes(i64::from(duration_minutes));
+        match self.waitlist.find_hold(id_doctor, at, end, Local::now().naive_local()).await? {
+            Some(hold) if hold.id_patient != id_patient => Err(Overlap {
+                detail: format!(
+                    "El turno de las {} está reservado para la lista de espera hasta el {}",
+                    hold.appointment_datetime.format("%Y-%m-%d %H:%M"),
+                    hold.expires_at.format("%Y-%m-%d %H:%M")
+                ),
+                conflicting_id: None,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Quién puede hacer cada transición; None si el usuario puede
+    async fn forbidden_reason(
+        &self,
+        appointment: &Appointment,
+        transition: AppointmentTransition,
+        auth: &AuthUser,
+    ) -> Result<Option<&'static str>> {
+        let allowed = match transition {
+            // El paciente (o su tutor) confirma o cancela sus propias citas
+            AppointmentTransition::Confirm | AppointmentTransition::Cancel => {
+                auth.is_staff() || self.repo.can_act_for(appointment.id_patient, auth.id_user).await?
+            }
+            AppointmentTransition::CheckIn => auth.is_admisionist() || auth.is_admin(),
+            // Solo el doctor asignado atiende la cita
+            AppointmentTransition::Start | AppointmentTransition::Complete => {
+                auth.is_doctor() && self.repo.get_doctor_user(appointment.id_doctor).await? == Some(auth.id_user)
+            }
+            AppointmentTransition::NoShow => auth.is_staff(),
+        };
+        if allowed {
+            return Ok(None);
+        }
+        Ok(Some(match transition {
+            AppointmentTransition::Confirm | AppointmentTransition::Cancel => {
+                "Solo el paciente, su tutor legal o el personal pueden hacer este cambio"
+            }
+            AppointmentTransition::CheckIn => "Solo admisión puede registrar la llegada del paciente",
+            AppointmentTransition::Start | AppointmentTransition::Complete => {
+                "Solo el doctor asignado puede atender la cita"
+            }
+            AppointmentTransition::NoShow => "Solo el personal puede marcar la inasistencia",
+        }))
+    }
+
+    /// None si la cita no existe
+    pub async fn get_status_history(&self, id: i32) -> Result<Option<Vec<AuditLog>>> {
+        if self.repo.get_by_id(id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.repo.get_status_history(id).await?))
+    }
+}
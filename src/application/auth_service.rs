@@ -1,5 +1,5 @@
 use crate::{
-    helpers::jwt::{generate_jwt, validate_jwt},
+    helpers::jwt::generate_jwt,
     infrastructure::auth_repository::AuthRepository,
 };
 use crate::domain::user::UserInfo;
@@ -20,16 +20,10 @@ impl<'a> AuthService<'a> {
     /// Intenta loguear y devolver un JWT
     pub async fn login(&self, username: &str, password: &str) -> Result<Option<(String, UserInfo)>> {
         if let Some(user_info) = self.repo.validate_user(username, password).await? {
-            let token = generate_jwt(user_info.username.clone(), user_info.role.clone())?;
+            let token = generate_jwt(user_info.id.to_string(), user_info.role.clone())?;
             Ok(Some((token, user_info)))
         } else {
             Ok(None)
         }
     }
-
-    /// Verifica un JWT y devuelve claims
-    pub fn verify_token(&self, token: &str) -> Result<String> {
-        let data = validate_jwt(token)?;
-        Ok(data.claims.sub)
-    }
 }
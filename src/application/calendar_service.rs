@@ -0,0 +1,61 @@
+use crate::domain::calendar::{render_calendar, CalendarFeed, CalendarPolicy, FeedOwner, FEED_PAST_DAYS};
+use crate::infrastructure::calendar_repository::CalendarRepository;
+use anyhow::Result;
+use chrono::{Duration, Local};
+use uuid::Uuid;
+
+pub struct CalendarService<R: CalendarRepository> {
+    repo: R,
+    policy: CalendarPolicy,
+}
+
+/// Qué calendario corresponde al rol; None si el rol no tiene agenda propia
+pub fn feed_owner(role: &str) -> Option<FeedOwner> {
+    match role {
+        "doctor" => Some(FeedOwner::Doctor),
+        "patient" => Some(FeedOwner::Patient),
+        _ => None,
+    }
+}
+
+impl<R: CalendarRepository> CalendarService<R> {
+    pub fn new(repo: R, policy: CalendarPolicy) -> Self {
+        Self { repo, policy }
+    }
+
+    pub async fn get_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>> {
+        self.repo.get_feed(id_user).await
+    }
+
+    /// Genera un token nuevo; la URL anterior deja de funcionar
+    pub async fn rotate_feed(&self, id_user: i32) -> Result<CalendarFeed> {
+        self.repo.rotate_feed(id_user, Uuid::new_v4()).await
+    }
+
+    pub async fn delete_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>> {
+        self.repo.delete_feed(id_user).await
+    }
+
+    /// Calendario .ics del dueño del token. None si el token no existe o el
+    /// usuario ya no es doctor ni paciente.
+    pub async fn render(&self, token: Uuid) -> Result<Option<String>> {
+        let Some((id_user, role)) = self.repo.open_feed(token).await? else {
+            return Ok(None);
+        };
+        let Some(owner) = feed_owner(&role) else {
+            return Ok(None);
+        };
+
+        let now = Local::now().naive_local();
+        let since = now - Duration::days(FEED_PAST_DAYS);
+        let (name, events) = match owner {
+            FeedOwner::Doctor => {
+                ("Agenda de citas", self.repo.get_doctor_events(id_user, since, self.policy.hospital_id).await?)
+            }
+            FeedOwner::Patient => {
+                ("Mis citas", self.repo.get_patient_events(id_user, since, self.policy.hospital_id).await?)
+            }
+        };
+        Ok(Some(render_calendar(name, owner, &events, now)))
+    }
+}
@@ -0,0 +1,62 @@
+use crate::domain::access_log::{CreateAccessLog, RESOURCE_MEDICAL_HISTORY, RESOURCE_PATIENT};
+use crate::domain::doctor_agenda::{AgendaParams, DoctorAgenda};
+use crate::infrastructure::access_log_repository::AccessLogRepository;
+use crate::infrastructure::doctor_agenda_repository::DoctorAgendaRepository;
+use anyhow::Result;
+use chrono::Local;
+use std::collections::HashSet;
+
+pub struct DoctorAgendaService<R: DoctorAgendaRepository, L: AccessLogRepository> {
+    repo: R,
+    access_log: L,
+}
+
+impl<R: DoctorAgendaRepository, L: AccessLogRepository> DoctorAgendaService<R, L> {
+    pub fn new(repo: R, access_log: L) -> Self {
+        Self { repo, access_log }
+    }
+
+    /// El usuario es el doctor. None si el doctor no existe.
+    pub async fn is_doctor_user(&self, id_doctor: i32, id_user: i32) -> Result<Option<bool>> {
+        let user = self.repo.get_doctor_user(id_doctor).await?;
+        Ok(user.map(|u| u == Some(id_user)))
+    }
+
+    /// Agenda del día con alergias y último diagnóstico de cada paciente; deja
+    /// constancia de quién leyó cada expediente. None si el doctor no existe.
+    pub async fn get_agenda(&self, id_doctor: i32, params: &AgendaParams, id_reader: i32) -> Result<Option<DoctorAgenda>> {
+        if self.repo.get_doctor_user(id_doctor).await?.is_none() {
+            return Ok(None);
+        }
+        let now = Local::now().naive_local();
+        let date = params.date.unwrap_or(now.date());
+        let mut appointments = self.repo.get_agenda(id_doctor, date, params.include_canceled).await?;
+
+        let mut reads = Vec::new();
+        let mut seen = HashSet::new();
+        for a in &mut appointments {
+            a.waiting_minutes = a.waiting_since(now);
+            // Un paciente con dos citas el mismo día se registra una sola vez
+            if !seen.insert(a.id_patient) {
+                continue;
+            }
+            reads.push(CreateAccessLog {
+                id_user: id_reader,
+                id_patient: a.id_patient,
+                resource: RESOURCE_PATIENT,
+                record_id: a.id_patient,
+            });
+            if let Some(id_history) = a.id_last_history {
+                reads.push(CreateAccessLog {
+                    id_user: id_reader,
+                    id_patient: a.id_patient,
+                    resource: RESOURCE_MEDICAL_HISTORY,
+                    record_id: id_history,
+                });
+            }
+        }
+        self.access_log.create_many(&reads).await?;
+
+        Ok(Some(DoctorAgenda { id_doctor, date, appointments }))
+    }
+}
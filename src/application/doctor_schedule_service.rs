@@ -0,0 +1,77 @@
+use crate::domain::doctor_schedule::{
+    check_exception, check_schedule, CreateDoctorSchedule, CreateDoctorScheduleException, CreatedScheduleException,
+    DoctorSchedule, DoctorScheduleException, ExceptionFilter,
+};
+use crate::helpers::errors::InvalidState;
+use crate::infrastructure::doctor_schedule_repository::DoctorScheduleRepository;
+use anyhow::Result;
+
+pub struct DoctorScheduleService<R: DoctorScheduleRepository> {
+    repo: R,
+}
+
+impl<R: DoctorScheduleRepository> DoctorScheduleService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// El usuario es el doctor. None si el doctor no existe.
+    pub async fn is_doctor_user(&self, id_doctor: i32, id_user: i32) -> Result<Option<bool>> {
+        let user = self.repo.get_doctor_user(id_doctor).await?;
+        Ok(user.map(|u| u == Some(id_user)))
+    }
+
+    /// None si el doctor no existe
+    pub async fn get_schedules(&self, id_doctor: i32) -> Result<Option<Vec<DoctorSchedule>>> {
+        if self.repo.get_doctor_user(id_doctor).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.repo.get_schedules(id_doctor).await?))
+    }
+
+    /// None si el doctor no existe; error de estado si se cruza con otro bloque del día
+    pub async fn create_schedule(&self, id_doctor: i32, data: CreateDoctorSchedule) -> Result<Option<DoctorSchedule>> {
+        check_schedule(&data)?;
+        if self.repo.get_doctor_user(id_doctor).await?.is_none() {
+            return Ok(None);
+        }
+        if let Some(id_schedule) = self
+            .repo
+            .find_overlapping(id_doctor, data.day_of_week, data.start_time, data.end_time)
+            .await?
+        {
+            return Err(InvalidState(format!("El horario se cruza con el bloque {} del mismo día", id_schedule)).into());
+        }
+        Ok(Some(self.repo.create_schedule(id_doctor, data).await?))
+    }
+
+    pub async fn delete_schedule(&self, id_doctor: i32, id_schedule: i32) -> Result<Option<DoctorSchedule>> {
+        self.repo.delete_schedule(id_doctor, id_schedule).await
+    }
+
+    /// None si el doctor no existe
+    pub async fn get_exceptions(&self, id_doctor: i32, filter: &ExceptionFilter) -> Result<Option<Vec<DoctorScheduleException>>> {
+        if self.repo.get_doctor_user(id_doctor).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.repo.get_exceptions(id_doctor, filter).await?))
+    }
+
+    /// Registra la ausencia y devuelve las citas activas que quedan dentro
+    pub async fn create_exception(
+        &self,
+        id_doctor: i32,
+        data: CreateDoctorScheduleException,
+        created_by: i32,
+    ) -> Result<CreatedScheduleException> {
+        check_exception(&data)?;
+        let (start, end) = data.range();
+        let exception = self.repo.create_exception(id_doctor, data, created_by).await?;
+        let affected_appointments = self.repo.get_appointments_between(id_doctor, start, end).await?;
+        Ok(CreatedScheduleException { exception, affected_appointments })
+    }
+
+    pub async fn delete_exception(&self, id_doctor: i32, id_exception: i32) -> Result<Option<DoctorScheduleException>> {
+        self.repo.delete_exception(id_doctor, id_exception).await
+    }
+}
@@ -1,5 +1,6 @@
-use crate::domain::doctor::{Doctor, CreateDoctor, UpdateDoctor};
+use crate::domain::doctor::{Doctor, CreateDoctor, UpdateDoctor, DoctorFilter};
 use crate::infrastructure::doctor_repository::DoctorRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
 pub struct DoctorService<R: DoctorRepository> {
@@ -11,8 +12,8 @@ impl<R: DoctorRepository> DoctorService<R> {
         Self { repo }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Doctor>> {
-        self.repo.get_all().await
+    pub async fn get_all(&self, filter: &DoctorFilter, page: &PageParams) -> Result<Page<Doctor>> {
+        self.repo.get_all(filter, page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<Doctor>> {
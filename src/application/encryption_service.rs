@@ -0,0 +1,63 @@
+use crate::infrastructure::blob_store::BlobStore;
+use crate::infrastructure::encrypted_blob_store::EncryptedBlobStore;
+use crate::infrastructure::medical_document_repository::MedicalDocumentRepository;
+use crate::infrastructure::patient_repository::PatientRepository;
+use anyhow::Result;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct RotationReport {
+    pub active_key: String,
+    pub patients: u64,
+    pub documents: u64,
+    /// Archivos que no se pudieron rotar; siguen con la llave anterior y se
+    /// reintentan en la próxima rotación
+    pub failed: Vec<RotationFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotationFailure {
+    pub key: String,
+    pub error: String,
+}
+
+/// Rotación de la llave maestra: se agrega la llave nueva al inicio de
+/// ENCRYPTION_KEYS (manteniendo las anteriores), se reinicia el servicio y se
+/// ejecuta esta rotación. Al terminar, las llaves anteriores se pueden retirar.
+pub struct EncryptionService<P: PatientRepository, D: MedicalDocumentRepository, B: BlobStore> {
+    patients: P,
+    documents: D,
+    blobs: EncryptedBlobStore<B>,
+    active_key: String,
+}
+
+impl<P: PatientRepository, D: MedicalDocumentRepository, B: BlobStore> EncryptionService<P, D, B> {
+    pub fn new(patients: P, documents: D, blobs: EncryptedBlobStore<B>, active_key: String) -> Self {
+        Self { patients, documents, blobs, active_key }
+    }
+
+    pub async fn rotate(&self) -> Result<RotationReport> {
+        let patients = self.patients.rotate_keys().await?;
+
+        // Un archivo faltante o ilegible no detiene la rotación de los demás
+        let mut documents = 0;
+        let mut failed = Vec::new();
+        for key in self.documents.get_blob_keys().await? {
+            match self.blobs.rotate(&key).await {
+                Ok(true) => documents += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("No se pudo rotar el archivo {}: {:?}", key, e);
+                    failed.push(RotationFailure { key, error: e.to_string() });
+                }
+            }
+        }
+
+        Ok(RotationReport {
+            active_key: self.active_key.clone(),
+            patients,
+            documents,
+            failed,
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::hospital::{CreateHospital, Hospital};
 use crate::infrastructure::hospital_repository::HospitalRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
 pub struct HospitalService<R: HospitalRepository> {
@@ -11,8 +12,8 @@ impl<R: HospitalRepository> HospitalService<R> {
         Self { repo }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Hospital>> {
-        self.repo.get_all().await
+    pub async fn get_all(&self, page: &PageParams) -> Result<Page<Hospital>> {
+        self.repo.get_all(page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<Hospital>> {
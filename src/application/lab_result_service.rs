@@ -1,22 +1,53 @@
-use crate::domain::lab_result::{LabResult, CreateLabResult, UpdateLabResult};
+use crate::domain::lab_result::{LabResult, CreateLabResult, UpdateLabResult, LabResultFilter};
+use crate::domain::access_log::{CreateAccessLog, RESOURCE_LAB_RESULT};
 use crate::infrastructure::lab_result::LabResultRepository;
+use crate::infrastructure::access_log_repository::AccessLogRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
-pub struct LabResultService<R: LabResultRepository> {
+pub struct LabResultService<R: LabResultRepository, L: AccessLogRepository> {
     repo: R,
+    access_log: L,
 }
 
-impl<R: LabResultRepository> LabResultService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+impl<R: LabResultRepository, L: AccessLogRepository> LabResultService<R, L> {
+    pub fn new(repo: R, access_log: L) -> Self {
+        Self { repo, access_log }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<LabResult>> {
-        self.repo.get_all().await
+    /// Listado del personal; cada registro devuelto queda en el registro de accesos
+    pub async fn get_all(&self, filter: &LabResultFilter, page: &PageParams, id_reader: i32) -> Result<Page<LabResult>> {
+        let records = self.repo.get_all(filter, page).await?;
+        let logs: Vec<CreateAccessLog> = records
+            .data
+            .iter()
+            .map(|r| CreateAccessLog {
+                id_user: id_reader,
+                id_patient: r.id_patient,
+                resource: RESOURCE_LAB_RESULT,
+                record_id: r.id_result,
+            })
+            .collect();
+        self.access_log.create_many(&logs).await?;
+        Ok(records)
     }
 
-    pub async fn get_by_id(&self, id: i32) -> Result<Option<LabResult>> {
-        self.repo.get_by_id(id).await
+    /// Obtiene un resultado de laboratorio y deja constancia de quién lo leyó
+    pub async fn get_by_id(&self, id: i32, id_reader: i32) -> Result<Option<LabResult>> {
+        let lab_result = self.repo.get_by_id(id).await?;
+
+        if let Some(l) = &lab_result {
+            self.access_log
+                .create(CreateAccessLog {
+                    id_user: id_reader,
+                    id_patient: l.id_patient,
+                    resource: RESOURCE_LAB_RESULT,
+                    record_id: l.id_result,
+                })
+                .await?;
+        }
+
+        Ok(lab_result)
     }
 
     pub async fn create(&self, data: CreateLabResult) -> Result<LabResult> {
@@ -0,0 +1,160 @@
+use crate::domain::access_log::{CreateAccessLog, RESOURCE_MEDICAL_DOCUMENT};
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::medical_document::{
+    extension_for, CreateMedicalDocument, MedicalDocument, UploadMedicalDocument,
+};
+use crate::helpers::auth::AuthUser;
+use crate::infrastructure::access_log_repository::AccessLogRepository;
+use crate::infrastructure::blob_store::{BlobStore, BlobStream};
+use crate::infrastructure::medical_document_repository::MedicalDocumentRepository;
+use crate::infrastructure::preview::generate_preview;
+use anyhow::Result;
+use uuid::Uuid;
+
+pub struct MedicalDocumentService<R: MedicalDocumentRepository, B: BlobStore, L: AccessLogRepository> {
+    repo: R,
+    blobs: B,
+    access_log: L,
+}
+
+impl<R: MedicalDocumentRepository, B: BlobStore, L: AccessLogRepository> MedicalDocumentService<R, B, L> {
+    pub fn new(repo: R, blobs: B, access_log: L) -> Self {
+        Self { repo, blobs, access_log }
+    }
+
+    /// El personal ve cualquier expediente; un paciente solo el suyo y el de
+    /// quienes tiene a cargo como tutor legal
+    async fn can_access(&self, auth: &AuthUser, id_patient: i32) -> Result<bool> {
+        if auth.is_staff() {
+            return Ok(true);
+        }
+        self.repo.can_act_for(id_patient, auth.id_user).await
+    }
+
+    /// Guarda el archivo y su vista previa en el BlobStore y registra sus metadatos.
+    /// Devuelve None si el paciente no existe.
+    pub async fn upload(&self, data: UploadMedicalDocument) -> Result<Option<MedicalDocument>> {
+        if self.repo.get_patient_user(data.id_patient).await?.is_none() {
+            return Ok(None);
+        }
+
+        let extension = extension_for(&data.content_type)
+            .ok_or_else(|| anyhow::anyhow!("Tipo de archivo no permitido: {}", data.content_type))?;
+        let stem = format!("patients/{}/{}", data.id_patient, Uuid::new_v4());
+        let key = format!("{}.{}", stem, extension);
+        let file_size = data.data.len() as i64;
+
+        self.blobs.put(&key, data.data.clone()).await?;
+
+        // La vista previa es opcional: si falla, el documento se guarda igual
+        let preview_path = match generate_preview(&data.content_type, data.data).await {
+            Ok(Some(png)) => {
+                let preview_key = format!("{}.preview.png", stem);
+                match self.blobs.put(&preview_key, png).await {
+                    Ok(()) => Some(preview_key),
+                    Err(e) => {
+                        eprintln!("Error al guardar vista previa: {:?}", e);
+                        None
+                    }
+                }
+            }
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("Error al generar vista previa: {:?}", e);
+                None
+            }
+        };
+
+        let created = self
+            .repo
+            .create(CreateMedicalDocument {
+                id_patient: data.id_patient,
+                id_doctor: data.id_doctor,
+                document_type: data.document_type,
+                file_path: key.clone(),
+                file_name: data.file_name,
+                content_type: data.content_type,
+                file_size,
+                preview_path: preview_path.clone(),
+                description: data.description,
+            })
+            .await;
+
+        match created {
+            Ok(document) => Ok(Some(document)),
+            Err(e) => {
+                // No dejar archivos huérfanos si falla el registro
+                let _ = self.blobs.delete(&key).await;
+                if let Some(preview_key) = &preview_path {
+                    let _ = self.blobs.delete(preview_key).await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Metadatos del documento, None si no existe o no hay permiso
+    pub async fn get_by_id(&self, id: i32, auth: &AuthUser) -> Result<Option<MedicalDocument>> {
+        match self.repo.get_by_id(id).await? {
+            Some(d) if self.can_access(auth, d.id_patient).await? => Ok(Some(d)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Documentos de un paciente, None si no existe o no hay permiso
+    pub async fn get_by_patient(
+        &self,
+        id_patient: i32,
+        auth: &AuthUser,
+        page: &PageParams,
+    ) -> Result<Option<Page<MedicalDocument>>> {
+        if !self.can_access(auth, id_patient).await? {
+            return Ok(None);
+        }
+        let documents = self.repo.get_by_patient(id_patient, page).await?;
+        Ok(Some(documents))
+    }
+
+    /// Abre el archivo original para descargarlo y deja constancia de la lectura
+    pub async fn download(&self, id: i32, auth: &AuthUser) -> Result<Option<(MedicalDocument, BlobStream)>> {
+        let document = match self.get_by_id(id, auth).await? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        let stream = self.blobs.get(&document.file_path).await?;
+        self.log_read(auth, &document).await?;
+
+        Ok(Some((document, stream)))
+    }
+
+    /// Abre la miniatura PNG. None si no existe el documento, no hay permiso
+    /// o el documento no tiene vista previa.
+    pub async fn preview(&self, id: i32, auth: &AuthUser) -> Result<Option<BlobStream>> {
+        let document = match self.get_by_id(id, auth).await? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        let preview_key = match &document.preview_path {
+            Some(k) => k,
+            None => return Ok(None),
+        };
+
+        let stream = self.blobs.get(preview_key).await?;
+        self.log_read(auth, &document).await?;
+
+        Ok(Some(stream))
+    }
+
+    async fn log_read(&self, auth: &AuthUser, document: &MedicalDocument) -> Result<()> {
+        self.access_log
+            .create(CreateAccessLog {
+                id_user: auth.id_user,
+                id_patient: document.id_patient,
+                resource: RESOURCE_MEDICAL_DOCUMENT,
+                record_id: document.id_document,
+            })
+            .await?;
+        Ok(())
+    }
+}
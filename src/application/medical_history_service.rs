@@ -1,22 +1,53 @@
-use crate::domain::medical_history::{MedicalHistory, CreateMedicalHistory, UpdateMedicalHistory};
+use crate::domain::medical_history::{MedicalHistory, CreateMedicalHistory, UpdateMedicalHistory, MedicalHistoryFilter};
+use crate::domain::access_log::{CreateAccessLog, RESOURCE_MEDICAL_HISTORY};
 use crate::infrastructure::medical_history_repository::MedicalHistoryRepository;
+use crate::infrastructure::access_log_repository::AccessLogRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
-pub struct MedicalHistoryService<R: MedicalHistoryRepository> {
+pub struct MedicalHistoryService<R: MedicalHistoryRepository, L: AccessLogRepository> {
     repo: R,
+    access_log: L,
 }
 
-impl<R: MedicalHistoryRepository> MedicalHistoryService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+impl<R: MedicalHistoryRepository, L: AccessLogRepository> MedicalHistoryService<R, L> {
+    pub fn new(repo: R, access_log: L) -> Self {
+        Self { repo, access_log }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<MedicalHistory>> {
-        self.repo.get_all().await
+    /// Listado del personal; cada registro devuelto queda en el registro de accesos
+    pub async fn get_all(&self, filter: &MedicalHistoryFilter, page: &PageParams, id_reader: i32) -> Result<Page<MedicalHistory>> {
+        let records = self.repo.get_all(filter, page).await?;
+        let logs: Vec<CreateAccessLog> = records
+            .data
+            .iter()
+            .map(|r| CreateAccessLog {
+                id_user: id_reader,
+                id_patient: r.id_patient,
+                resource: RESOURCE_MEDICAL_HISTORY,
+                record_id: r.id_history,
+            })
+            .collect();
+        self.access_log.create_many(&logs).await?;
+        Ok(records)
     }
 
-    pub async fn get_by_id(&self, id: i32) -> Result<Option<MedicalHistory>> {
-        self.repo.get_by_id(id).await
+    /// Obtiene un registro del historial y deja constancia de quién lo leyó
+    pub async fn get_by_id(&self, id: i32, id_reader: i32) -> Result<Option<MedicalHistory>> {
+        let history = self.repo.get_by_id(id).await?;
+
+        if let Some(h) = &history {
+            self.access_log
+                .create(CreateAccessLog {
+                    id_user: id_reader,
+                    id_patient: h.id_patient,
+                    resource: RESOURCE_MEDICAL_HISTORY,
+                    record_id: h.id_history,
+                })
+                .await?;
+        }
+
+        Ok(history)
     }
 
     pub async fn create(&self, data: CreateMedicalHistory) -> Result<MedicalHistory> {
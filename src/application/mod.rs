@@ -7,4 +7,16 @@ pub mod speciality_service;
 pub mod appointment_service;
 pub mod medical_history_service;
 pub mod lab_result_service;
-pub mod auth_service;
\ No newline at end of file
+pub mod auth_service;
+pub mod medical_document_service;
+pub mod encryption_service;
+pub mod patient_contact_service;
+pub mod registration_service;
+pub mod portal_service;
+pub mod patient_import_service;
+pub mod trash_service;
+pub mod doctor_schedule_service;
+pub mod waitlist_service;
+pub mod reminder_service;
+pub mod calendar_service;
+pub mod doctor_agenda_service;
\ No newline at end of file
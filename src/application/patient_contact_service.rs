@@ -0,0 +1,41 @@
+use crate::domain::patient_contact::{CreatePatientContact, PatientContact, UpdatePatientContact};
+use crate::helpers::auth::AuthUser;
+use crate::infrastructure::patient_contact_repository::PatientContactRepository;
+use anyhow::Result;
+
+pub struct PatientContactService<R: PatientContactRepository> {
+    repo: R,
+}
+
+impl<R: PatientContactRepository> PatientContactService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// Contactos del paciente, None si no existe o si el usuario no es
+    /// personal, el propio paciente ni su tutor legal
+    pub async fn get_by_patient(&self, id_patient: i32, auth: &AuthUser) -> Result<Option<Vec<PatientContact>>> {
+        let allowed = if auth.is_staff() {
+            self.repo.patient_exists(id_patient).await?
+        } else {
+            self.repo.can_act_for(id_patient, auth.id_user).await?
+        };
+        if !allowed {
+            return Ok(None);
+        }
+        let contacts = self.repo.get_by_patient(id_patient).await?;
+        Ok(Some(contacts))
+    }
+
+    pub async fn create(&self, id_patient: i32, data: CreatePatientContact) -> Result<Option<PatientContact>> {
+        self.repo.create(id_patient, data).await
+    }
+
+    pub async fn update(&self, id_patient: i32, id_contact: i32, data: UpdatePatientContact) -> Result<Option<PatientContact>> {
+        self.repo.update(id_patient, id_contact, data).await
+    }
+
+    pub async fn delete(&self, id_patient: i32, id_contact: i32) -> Result<Option<PatientContact>> {
+        self.repo.delete(id_patient, id_contact).await
+    }
+}
@@ -0,0 +1,216 @@
+use crate::domain::patient::{check_identity, CreatePatient, IDENTITY_CEDULA};
+use crate::domain::patient_import::{
+    CreatePatientImport, ImportFormat, ImportRowError, PatientImport, ERROR_DATABASE, ERROR_DUPLICATE_EXISTING,
+    ERROR_DUPLICATE_IN_FILE, ERROR_PARSE, ERROR_REPORT_COLUMNS, IMPORT_BATCH_SIZE, REQUIRED_COLUMNS,
+};
+use crate::helpers::utils::generate_patient_password;
+use crate::infrastructure::patient_import_repository::PatientImportRepository;
+use crate::infrastructure::patient_repository::PatientRepository;
+use crate::infrastructure::spreadsheet::{read_rows, to_csv, SheetRow};
+use anyhow::Result;
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::Datelike;
+use futures_util::future::try_join_all;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use validator::{Validate, ValidationErrors};
+
+pub struct PatientImportService<P: PatientRepository, I: PatientImportRepository> {
+    patients: P,
+    imports: I,
+}
+
+fn row_error(row: i32, patient: &CreatePatient, field: &str, code: &str, message: String) -> ImportRowError {
+    ImportRowError {
+        row,
+        identity_number: Some(patient.identity_number.clone()),
+        field: Some(field.to_string()),
+        code: code.to_string(),
+        message,
+    }
+}
+
+fn validation_errors(row: i32, patient: &CreatePatient, errors: &ValidationErrors) -> Vec<ImportRowError> {
+    let mut result: Vec<ImportRowError> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, list)| {
+            list.iter().map(move |e| {
+                let message = e.message.as_ref().map(|m| m.to_string()).unwrap_or_else(|| "Valor inválido".to_string());
+                row_error(row, patient, field, &e.code, message)
+            })
+        })
+        .collect();
+    result.sort_by(|a, b| a.field.cmp(&b.field));
+    result
+}
+
+/// Reglas de CreatePatient más la validación del documento; normaliza el número
+fn check_row(row: i32, patient: &mut CreatePatient) -> Vec<ImportRowError> {
+    let mut errors = match patient.validate() {
+        Ok(()) => Vec::new(),
+        Err(e) => validation_errors(row, patient, &e),
+    };
+
+    let identity_type = patient.identity_type.get_or_insert_with(|| IDENTITY_CEDULA.to_string());
+    match check_identity(identity_type, &patient.identity_number, patient.birthdate) {
+        Ok(normalized) => patient.identity_number = normalized,
+        Err(e) => errors.extend(validation_errors(row, patient, &e)),
+    }
+    errors
+}
+
+/// Valores que identifican al paciente (y a su usuario) y no se pueden repetir
+fn unique_values(patient: &CreatePatient) -> Vec<(&'static str, String)> {
+    let mut values = vec![("identity_number", patient.identity_number.clone())];
+    if let Some(email) = &patient.email {
+        values.push(("email", email.clone()));
+    }
+    values
+}
+
+impl<P: PatientRepository, I: PatientImportRepository> PatientImportService<P, I> {
+    pub fn new(patients: P, imports: I) -> Self {
+        Self { patients, imports }
+    }
+
+    /// Valida cada fila con las reglas de CreatePatient, descarta duplicados (en el
+    /// archivo y contra la base) y, si no es dry-run, guarda en lotes de
+    /// IMPORT_BATCH_SIZE. El resumen y los errores por fila quedan en patient_imports.
+    pub async fn import(
+        &self,
+        format: ImportFormat,
+        file_name: Option<String>,
+        data: &[u8],
+        dry_run: bool,
+        performed_by: Option<i32>,
+    ) -> Result<PatientImport> {
+        let rows = read_rows::<CreatePatient>(format, data, REQUIRED_COLUMNS)?;
+        let total_rows = rows.len() as i32;
+
+        let mut errors: Vec<ImportRowError> = Vec::new();
+        let mut candidates: Vec<(i32, CreatePatient)> = Vec::new();
+        let mut seen: HashMap<String, i32> = HashMap::new();
+
+        for SheetRow { row, value } in rows {
+            let mut patient = match value {
+                Ok(patient) => patient,
+                Err(e) => {
+                    errors.push(ImportRowError {
+                        row,
+                        identity_number: None,
+                        field: e.column,
+                        code: ERROR_PARSE.to_string(),
+                        message: e.message,
+                    });
+                    continue;
+                }
+            };
+
+            let mut row_errors = check_row(row, &mut patient);
+            for (field, value) in unique_values(&patient) {
+                if let Some(first) = seen.get(&value) {
+                    row_errors.push(row_error(
+                        row,
+                        &patient,
+                        field,
+                        ERROR_DUPLICATE_IN_FILE,
+                        format!("Repetido en la fila {}", first),
+                    ));
+                } else {
+                    seen.insert(value, row);
+                }
+            }
+
+            if row_errors.is_empty() {
+                candidates.push((row, patient));
+            } else {
+                errors.extend(row_errors);
+            }
+        }
+
+        // Duplicados contra pacientes y usuarios existentes
+        let values: Vec<String> = candidates.iter().flat_map(|(_, p)| unique_values(p)).map(|(_, v)| v).collect();
+        let taken: HashSet<String> = self.patients.find_taken(&values).await?.into_iter().collect();
+
+        let mut valid: Vec<(i32, CreatePatient)> = Vec::new();
+        for (row, patient) in candidates {
+            let duplicates: Vec<ImportRowError> = unique_values(&patient)
+                .into_iter()
+                .filter(|(_, value)| taken.contains(value))
+                .map(|(field, _)| {
+                    row_error(row, &patient, field, ERROR_DUPLICATE_EXISTING, "Ya existe un paciente o usuario con este valor".to_string())
+                })
+                .collect();
+            if duplicates.is_empty() {
+                valid.push((row, patient));
+            } else {
+                errors.extend(duplicates);
+            }
+        }
+
+        let valid_rows = valid.len() as i32;
+        let mut imported_rows = 0;
+        if !dry_run {
+            while !valid.is_empty() {
+                let batch: Vec<(i32, CreatePatient)> = valid.drain(..valid.len().min(IMPORT_BATCH_SIZE)).collect();
+                let (row_numbers, patients): (Vec<i32>, Vec<CreatePatient>) = batch.into_iter().unzip();
+
+                // bcrypt es costoso: se calcula en paralelo fuera del runtime
+                let hashes = try_join_all(patients.iter().map(|p| {
+                    let raw = generate_patient_password(&p.first_name, &p.first_lastname, p.birthdate.year());
+                    tokio::task::spawn_blocking(move || hash(raw, DEFAULT_COST))
+                }))
+                .await?
+                .into_iter()
+                .collect::<Result<Vec<String>, _>>()?;
+
+                let identities: Vec<String> = patients.iter().map(|p| p.identity_number.clone()).collect();
+                let results = self.patients.create_batch(patients.into_iter().zip(hashes).collect()).await?;
+
+                for ((row, identity_number), result) in row_numbers.into_iter().zip(identities).zip(results) {
+                    match result {
+                        Ok(_) => imported_rows += 1,
+                        Err(e) => {
+                            eprintln!("Importación: fila {} no se guardó: {:?}", row, e);
+                            errors.push(ImportRowError {
+                                row,
+                                identity_number: Some(identity_number),
+                                field: None,
+                                code: ERROR_DATABASE.to_string(),
+                                message: "No se pudo guardar la fila".to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        errors.sort_by_key(|e| e.row);
+        let failed_rows = errors.iter().map(|e| e.row).collect::<BTreeSet<i32>>().len() as i32;
+
+        self.imports
+            .create(CreatePatientImport {
+                file_name,
+                dry_run,
+                total_rows,
+                valid_rows,
+                imported_rows,
+                failed_rows,
+                errors,
+                performed_by,
+            })
+            .await
+    }
+
+    pub async fn get_by_id(&self, id: i32) -> Result<Option<PatientImport>> {
+        self.imports.get_by_id(id).await
+    }
+
+    /// Reporte de errores por fila en CSV; None si la importación no existe
+    pub async fn error_report(&self, id: i32) -> Result<Option<Vec<u8>>> {
+        match self.imports.get_by_id(id).await? {
+            Some(import) => Ok(Some(to_csv(ERROR_REPORT_COLUMNS, &import.errors.0)?)),
+            None => Ok(None),
+        }
+    }
+}
@@ -1,34 +1,195 @@
-use crate::domain::patient::{Patient, CreatePatient, UpdatePatient};
+use crate::domain::patient::{
+    allowed_transitions, check_identity, ChangePatientStatus, CreatePatient, Patient, PatientFilter, PatientSearchHit,
+    PatientStatusChange, UpdatePatient, IDENTITY_CEDULA, STATUS_ACTIVE, STATUS_PENDING_VERIFICATION,
+};
+use crate::domain::access_log::{AccessReportEntry, CreateAccessLog, RESOURCE_PATIENT};
 use crate::infrastructure::patient_repository::PatientRepository;
+use crate::infrastructure::access_log_repository::AccessLogRepository;
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::patient_merge::{DuplicateCandidate, PatientMerge};
+use crate::infrastructure::patient_merge_repository::PatientMergeRepository;
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::InvalidState;
 use anyhow::Result;
+use chrono::Local;
 
-pub struct PatientService<R: PatientRepository> {
+pub struct PatientService<R: PatientRepository, L: AccessLogRepository, M: PatientMergeRepository> {
     repo: R,
+    access_log: L,
+    merges: M,
 }
 
-impl<R: PatientRepository> PatientService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+impl<R: PatientRepository, L: AccessLogRepository, M: PatientMergeRepository> PatientService<R, L, M> {
+    pub fn new(repo: R, access_log: L, merges: M) -> Self {
+        Self { repo, access_log, merges }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Patient>> {
-        self.repo.get_all().await
+    /// Deja constancia de la lectura de cada paciente devuelto en un listado
+    async fn log_reads<'a>(&self, id_reader: i32, patients: impl Iterator<Item = &'a Patient>) -> Result<()> {
+        let logs: Vec<CreateAccessLog> = patients
+            .map(|p| CreateAccessLog {
+                id_user: id_reader,
+                id_patient: p.id_patient,
+                resource: RESOURCE_PATIENT,
+                record_id: p.id_patient,
+            })
+            .collect();
+        self.access_log.create_many(&logs).await
     }
 
-    pub async fn get_by_id(&self, id: i32) -> Result<Option<Patient>> {
-        self.repo.get_by_id(id).await
+    /// Listado del personal; incluye los datos clínicos, así que cada paciente
+    /// de la página queda en el registro de accesos
+    pub async fn get_all(&self, filter: &PatientFilter, page: &PageParams, id_reader: i32) -> Result<Page<Patient>> {
+        let patients = self.repo.get_all(filter, page).await?;
+        self.log_reads(id_reader, patients.data.iter()).await?;
+        Ok(patients)
     }
 
-    pub async fn create(&self, data: CreatePatient, raw_password: &str) -> Result<Patient> {
-        self.repo.create(data, raw_password).await
+    /// Obtiene el detalle del paciente (alergias, medicamentos, antecedentes)
+    /// y deja constancia de quién lo leyó. Fuera del personal, solo lo ve el
+    /// propio paciente o su tutor legal; si no, devuelve None.
+    pub async fn get_by_id(&self, id: i32, reader: &AuthUser) -> Result<Option<Patient>> {
+        if !reader.is_staff() && !self.repo.can_act_for(id, reader.id_user).await? {
+            return Ok(None);
+        }
+        let id_reader = reader.id_user;
+        let patient = self.repo.get_by_id(id).await?;
+
+        if let Some(p) = &patient {
+            self.access_log
+                .create(CreateAccessLog {
+                    id_user: id_reader,
+                    id_patient: p.id_patient,
+                    resource: RESOURCE_PATIENT,
+                    record_id: p.id_patient,
+                })
+                .await?;
+        }
+
+        Ok(patient)
+    }
+
+    pub async fn search(&self, term: &str, page: &PageParams, id_reader: i32) -> Result<Page<PatientSearchHit>> {
+        let hits = self.repo.search(term.trim(), page).await?;
+        self.log_reads(id_reader, hits.data.iter().map(|h| &h.patient)).await?;
+        Ok(hits)
+    }
+
+    /// Reporte de accesos al expediente. Devuelve None si el paciente no existe
+    /// o si el solicitante no es el propio paciente, su tutor legal ni un administrador.
+    pub async fn get_access_report(
+        &self,
+        id: i32,
+        id_requester: i32,
+        is_admin: bool,
+        page: &PageParams,
+    ) -> Result<Option<Page<AccessReportEntry>>> {
+        let patient = match self.repo.get_by_id(id).await? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+
+        if !is_admin && !self.repo.can_act_for(patient.id_patient, id_requester).await? {
+            return Ok(None);
+        }
+
+        let report = self.access_log.get_by_patient(patient.id_patient, page).await?;
+        Ok(Some(report))
+    }
+
+    pub async fn create(&self, mut data: CreatePatient, password_hash: &str) -> Result<Patient> {
+        let identity_type = data.identity_type.get_or_insert_with(|| IDENTITY_CEDULA.to_string());
+        data.identity_number = check_identity(identity_type, &data.identity_number, data.birthdate)?;
+        self.repo.create(data, password_hash).await
     }
 
-    pub async fn update(&self, id: i32, data: UpdatePatient) -> Result<Option<Patient>> {
+    pub async fn update(&self, id: i32, mut data: UpdatePatient) -> Result<Option<Patient>> {
+        // Si cambia el documento o la fecha de nacimiento se valida la combinación resultante
+        if data.identity_number.is_some() || data.identity_type.is_some() || data.birthdate.is_some() {
+            let Some(current) = self.repo.get_by_id(id).await? else {
+                return Ok(None);
+            };
+            let identity_type = data.identity_type.as_deref().unwrap_or(&current.identity_type);
+            let number = data.identity_number.as_deref().unwrap_or(&current.identity_number);
+            let birthdate = data.birthdate.map(|b| b.date()).unwrap_or(current.birthdate);
+            data.identity_number = Some(check_identity(identity_type, number, birthdate)?);
+        }
         self.repo.update(id, data).await
     }
 
     pub async fn delete(&self, id: i32) -> Result<Option<Patient>> {
         self.repo.delete(id).await
     }
+
+    /// Admisión verificó la cédula física de un paciente autorregistrado.
+    /// None si no existe; error de estado si no estaba pendiente.
+    pub async fn confirm_identity(&self, id: i32, performed_by: i32) -> Result<Option<Patient>> {
+        let Some(patient) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        // El UPDATE vuelve a comprobar el estado por si otra petición ya lo confirmó
+        let confirmed = if patient.status.as_deref() == Some(STATUS_PENDING_VERIFICATION) {
+            self.repo.confirm_identity(id, performed_by).await?
+        } else {
+            None
+        };
+        match confirmed {
+            Some(p) => Ok(Some(p)),
+            None => Err(InvalidState("El paciente no está pendiente de verificación".to_string()).into()),
+        }
+    }
+
+    /// Cambia el estado de vida del paciente según `allowed_transitions`.
+    /// None si no existe; error de estado si la transición no está permitida.
+    pub async fn change_status(
+        &self,
+        id: i32,
+        data: ChangePatientStatus,
+        performed_by: i32,
+    ) -> Result<Option<PatientStatusChange>> {
+        let Some(patient) = self.repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        let current = patient.status.as_deref().unwrap_or(STATUS_ACTIVE);
+        let allowed = allowed_transitions(current);
+        if !allowed.contains(&data.status.as_str()) {
+            let message = if allowed.is_empty() {
+                format!("El estado '{}' no admite cambios", current)
+            } else {
+                format!(
+                    "No se puede pasar de '{}' a '{}'; permitidos: {}",
+                    current,
+                    data.status,
+                    allowed.join(", ")
+                )
+            };
+            return Err(InvalidState(message).into());
+        }
+
+        // El UPDATE exige que el estado no haya cambiado desde la lectura
+        match self.repo.change_status(id, current, &data, performed_by, Local::now().naive_local()).await? {
+            Some(change) => Ok(Some(change)),
+            None => Err(InvalidState("El estado del paciente cambió; intente de nuevo".to_string()).into()),
+        }
+    }
+
+    pub async fn get_dependents(&self, id_user: i32) -> Result<Vec<Patient>> {
+        let dependents = self.repo.get_dependents(id_user).await?;
+        self.log_reads(id_user, dependents.iter()).await?;
+        Ok(dependents)
+    }
+
+    pub async fn find_duplicates(&self, id: i32) -> Result<Option<Vec<DuplicateCandidate>>> {
+        self.repo.find_duplicates(id).await
+    }
+
+    /// Fusiona `id_duplicate` en `id_survivor`. None si alguno no existe.
+    pub async fn merge(&self, id_survivor: i32, id_duplicate: i32, performed_by: i32) -> Result<Option<PatientMerge>> {
+        self.merges.merge(id_survivor, id_duplicate, performed_by).await
+    }
+
+    pub async fn unmerge(&self, id_audit: i32, performed_by: i32) -> Result<Option<PatientMerge>> {
+        self.merges.unmerge(id_audit, performed_by).await
+    }
  }
 
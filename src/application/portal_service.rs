@@ -0,0 +1,116 @@
+use crate::domain::appointment::{Appointment, AppointmentFilter};
+use crate::domain::lab_result::{LabResult, LabResultFilter};
+use crate::domain::medical_document::MedicalDocument;
+use crate::domain::medical_history::{MedicalHistory, MedicalHistoryFilter};
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::patient::{Patient, UpdateOwnProfile};
+use crate::domain::virtual_turn::VirtualTurn;
+use crate::infrastructure::appointment_repository::AppointmentRepository;
+use crate::infrastructure::lab_result::LabResultRepository;
+use crate::infrastructure::medical_document_repository::MedicalDocumentRepository;
+use crate::infrastructure::medical_history_repository::MedicalHistoryRepository;
+use crate::infrastructure::patient_repository::PatientRepository;
+use crate::infrastructure::virtual_turn_repository::VirtualTurnRepository;
+use anyhow::Result;
+
+/// Portal del paciente (/me/*): todo se resuelve a partir del id_user del token,
+/// así la app no necesita conocer su id_patient. Los métodos devuelven None
+/// si el usuario no tiene un expediente de paciente.
+pub struct PortalService<
+    P: PatientRepository,
+    A: AppointmentRepository,
+    H: MedicalHistoryRepository,
+    L: LabResultRepository,
+    D: MedicalDocumentRepository,
+    T: VirtualTurnRepository,
+> {
+    patients: P,
+    appointments: A,
+    history: H,
+    lab_results: L,
+    documents: D,
+    turns: T,
+}
+
+impl<P, A, H, L, D, T> PortalService<P, A, H, L, D, T>
+where
+    P: PatientRepository,
+    A: AppointmentRepository,
+    H: MedicalHistoryRepository,
+    L: LabResultRepository,
+    D: MedicalDocumentRepository,
+    T: VirtualTurnRepository,
+{
+    pub fn new(patients: P, appointments: A, history: H, lab_results: L, documents: D, turns: T) -> Self {
+        Self { patients, appointments, history, lab_results, documents, turns }
+    }
+
+    async fn patient_id(&self, id_user: i32) -> Result<Option<i32>> {
+        Ok(self.patients.get_by_user(id_user).await?.map(|p| p.id_patient))
+    }
+
+    pub async fn get_profile(&self, id_user: i32) -> Result<Option<Patient>> {
+        self.patients.get_by_user(id_user).await
+    }
+
+    /// Solo teléfono, dirección y contacto de emergencia; el resto lo cambia el personal
+    pub async fn update_profile(&self, id_user: i32, data: UpdateOwnProfile) -> Result<Option<Patient>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        self.patients.update(id_patient, data.into()).await
+    }
+
+    pub async fn get_appointments(
+        &self,
+        id_user: i32,
+        mut filter: AppointmentFilter,
+        page: &PageParams,
+    ) -> Result<Option<Page<Appointment>>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        filter.id_patient = Some(id_patient);
+        Ok(Some(self.appointments.get_all(&filter, page).await?))
+    }
+
+    pub async fn get_history(
+        &self,
+        id_user: i32,
+        mut filter: MedicalHistoryFilter,
+        page: &PageParams,
+    ) -> Result<Option<Page<MedicalHistory>>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        filter.id_patient = Some(id_patient);
+        Ok(Some(self.history.get_all(&filter, page).await?))
+    }
+
+    pub async fn get_lab_results(
+        &self,
+        id_user: i32,
+        mut filter: LabResultFilter,
+        page: &PageParams,
+    ) -> Result<Option<Page<LabResult>>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        filter.id_patient = Some(id_patient);
+        Ok(Some(self.lab_results.get_all(&filter, page).await?))
+    }
+
+    pub async fn get_documents(&self, id_user: i32, page: &PageParams) -> Result<Option<Page<MedicalDocument>>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.documents.get_by_patient(id_patient, page).await?))
+    }
+
+    pub async fn get_turns(&self, id_user: i32, page: &PageParams) -> Result<Option<Page<VirtualTurn>>> {
+        let Some(id_patient) = self.patient_id(id_user).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.turns.get_by_patient(id_patient, page).await?))
+    }
+}
@@ -0,0 +1,122 @@
+use crate::domain::patient::{check_identity, IDENTITY_CEDULA, STATUS_PENDING_VERIFICATION};
+use crate::domain::registration::{
+    CreatePatientRegistration, PendingRegistration, SelfRegistration, Verification, CHANNEL_EMAIL,
+    CODE_TTL_MINUTES, MAX_CODE_ATTEMPTS,
+};
+use crate::infrastructure::notifier::Notifier;
+use crate::infrastructure::patient_repository::PatientRepository;
+use crate::infrastructure::registration_repository::RegistrationRepository;
+use anyhow::Result;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::Local;
+use rand::Rng;
+use uuid::Uuid;
+
+pub struct RegistrationService<R: RegistrationRepository, P: PatientRepository, N: Notifier> {
+    repo: R,
+    patients: P,
+    notifier: N,
+}
+
+/// "+50588881234" -> "+505*****1234", "ana@mail.com" -> "a***@mail.com"
+fn mask(channel: &str, destination: &str) -> String {
+    if channel == CHANNEL_EMAIL {
+        return match destination.split_once('@') {
+            Some((user, domain)) => format!("{}***@{}", user.chars().next().unwrap_or('*'), domain),
+            None => "***".to_string(),
+        };
+    }
+    let chars: Vec<char> = destination.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let hidden = "*".repeat(chars.len() - 8);
+    format!(
+        "{}{}{}",
+        chars[..4].iter().collect::<String>(),
+        hidden,
+        chars[chars.len() - 4..].iter().collect::<String>()
+    )
+}
+
+impl<R: RegistrationRepository, P: PatientRepository, N: Notifier> RegistrationService<R, P, N> {
+    pub fn new(repo: R, patients: P, notifier: N) -> Self {
+        Self { repo, patients, notifier }
+    }
+
+    /// Guarda el registro pendiente y envía el código de verificación.
+    /// El paciente y su usuario se crean recién al verificar el código.
+    pub async fn register(&self, data: SelfRegistration) -> Result<PendingRegistration> {
+        let destination = data.destination().unwrap_or_default().to_string();
+        let channel = data.channel;
+        let mut patient = data.patient;
+
+        let identity_type = patient.identity_type.get_or_insert_with(|| IDENTITY_CEDULA.to_string());
+        patient.identity_number = check_identity(identity_type, &patient.identity_number, patient.birthdate)?;
+        patient.status = Some(STATUS_PENDING_VERIFICATION.to_string());
+
+        let code = format!("{:06}", rand::rng().random_range(0..1_000_000));
+        let registration = self
+            .repo
+            .create(CreatePatientRegistration {
+                payload: patient,
+                password_hash: hash(&data.password, DEFAULT_COST)?,
+                channel: channel.clone(),
+                destination: destination.clone(),
+                code_hash: hash(&code, DEFAULT_COST)?,
+            })
+            .await?;
+
+        self.notifier
+            .send(
+                &channel,
+                &destination,
+                "Código de verificación",
+                &format!("Su código de verificación es {}. Vence en {} minutos.", code, CODE_TTL_MINUTES),
+            )
+            .await?;
+
+        Ok(PendingRegistration {
+            id_registration: registration.id_registration,
+            destination: mask(&channel, &destination),
+            channel,
+            expires_at: registration.expires_at,
+        })
+    }
+
+    /// Verifica el código y crea la cuenta. None si el registro no existe o ya se verificó.
+    pub async fn verify(&self, id: Uuid, code: &str) -> Result<Option<Verification>> {
+        let Some(registration) = self.repo.register_attempt(id).await? else {
+            return Ok(None);
+        };
+
+        if registration.expires_at < Local::now().naive_local() || registration.attempts > MAX_CODE_ATTEMPTS {
+            return Ok(Some(Verification::Expired));
+        }
+        if !verify(code, &registration.code_hash)? {
+            return Ok(Some(Verification::InvalidCode {
+                attempts_left: MAX_CODE_ATTEMPTS - registration.attempts,
+            }));
+        }
+
+        // Se reclama antes de crear el paciente: de dos peticiones con el código
+        // correcto solo una lo crea; la otra ve el registro como ya verificado
+        if !self.repo.claim(id).await? {
+            return Ok(None);
+        }
+        let patient = match self
+            .patients
+            .create(registration.payload.0, &registration.password_hash)
+            .await
+        {
+            Ok(patient) => patient,
+            Err(e) => {
+                self.repo.release(id).await?;
+                return Err(e);
+            }
+        };
+        self.repo.mark_verified(id, patient.id_patient).await?;
+
+        Ok(Some(Verification::Verified(Box::new(patient))))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::domain::appointment::AppointmentStatus;
+use crate::domain::appointment_reminder::{DueReminder, ReminderPolicy};
+use crate::infrastructure::notifier::Notifier;
+use crate::infrastructure::reminder_repository::ReminderRepository;
+use anyhow::Result;
+use chrono::Local;
+use uuid::Uuid;
+
+const REMINDER_TITLE: &str = "Recordatorio de cita";
+
+pub struct ReminderService<R: ReminderRepository, N: Notifier> {
+    repo: R,
+    notifier: N,
+    policy: ReminderPolicy,
+}
+
+impl<R: ReminderRepository, N: Notifier> ReminderService<R, N> {
+    pub fn new(repo: R, notifier: N, policy: ReminderPolicy) -> Self {
+        Self { repo, notifier, policy }
+    }
+
+    /// Envía los recordatorios que ya tocan. Cada anticipación cubre hasta la
+    /// siguiente: una cita que entra tarde en la ventana (o tras un reinicio)
+    /// recibe solo el recordatorio más cercano. Devuelve cuántos se enviaron.
+    pub async fn send_due(&self) -> Result<usize> {
+        let mut sent = 0;
+        let now = Local::now().naive_local();
+        for (i, offset) in self.policy.offsets.iter().enumerate() {
+            let until = self.policy.offsets.get(i + 1).copied().unwrap_or(0);
+            for due in self.repo.find_due(*offset, until, now).await? {
+                let token = Uuid::new_v4();
+                let body = self.body(&due, token);
+                // Se guarda antes de enviar: si el proceso cae después, el aviso no se repite
+                let Some(reminder) = self.repo.claim(&due, *offset, token, REMINDER_TITLE, &body).await? else {
+                    continue;
+                };
+                sent += 1;
+
+                let (Some(channel), Some(to)) = (&due.channel, &due.destination) else {
+                    continue;
+                };
+                let error = match self.notifier.send(channel, to, REMINDER_TITLE, &body).await {
+                    Ok(()) => None,
+                    Err(e) => {
+                        eprintln!("No se pudo enviar el recordatorio de la cita {}: {:?}", due.id_appointment, e);
+                        Some(e.to_string())
+                    }
+                };
+                self.repo.mark_sent(reminder.id_reminder, error.as_deref()).await?;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Una cita pendiente se puede confirmar o cancelar; una confirmada, solo cancelar
+    fn body(&self, due: &DueReminder, token: Uuid) -> String {
+        let place = match (&due.building, &due.room) {
+            (Some(building), Some(room)) => format!(", edificio {} sala {}", building, room),
+            (None, Some(room)) => format!(", sala {}", room),
+            _ => String::new(),
+        };
+        let mut body = format!(
+            "Le recordamos su cita del {} con {}{}.",
+            due.appointment_datetime.format("%d/%m/%Y %H:%M"),
+            due.doctor_name,
+            place
+        );
+        if due.status == AppointmentStatus::Pending.as_str() {
+            body.push_str(&format!(" Confirmar: {}", self.policy.link(token, "confirm")));
+        }
+        body.push_str(&format!(" Cancelar: {}", self.policy.link(token, "cancel")));
+        body
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::services::{Service, CreateService, UpdateService};
 use crate::infrastructure::services_repository::ServiceRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
 pub struct ServicesService<R: ServiceRepository> {
@@ -11,8 +12,8 @@ impl<R: ServiceRepository> ServicesService<R> {
         Self { repo }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Service>> {
-        self.repo.get_all().await
+    pub async fn get_all(&self, page: &PageParams) -> Result<Page<Service>> {
+        self.repo.get_all(page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<Service>> {
@@ -1,5 +1,6 @@
 use crate::domain::speciality::{Speciality, CreateSpeciality, UpdateSpeciality};
 use crate::infrastructure::speciality_repository::SpecialityRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
 pub struct SpecialityService<R: SpecialityRepository>{
@@ -11,8 +12,8 @@ impl <R: SpecialityRepository> SpecialityService<R> {
         Self {repo}
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Speciality>> {
-        self.repo.get_all().await
+    pub async fn get_all(&self, page: &PageParams) -> Result<Page<Speciality>> {
+        self.repo.get_all(page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<Speciality>> {
@@ -0,0 +1,51 @@
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::trash::{PurgeResult, RetentionPolicy, TrashItem, TRASH_ENTITIES};
+use crate::helpers::errors::InvalidState;
+use crate::infrastructure::trash_repository::TrashRepository;
+use anyhow::Result;
+
+pub struct TrashService<R: TrashRepository> {
+    repo: R,
+    policy: RetentionPolicy,
+}
+
+impl<R: TrashRepository> TrashService<R> {
+    pub fn new(repo: R, policy: RetentionPolicy) -> Self {
+        Self { repo, policy }
+    }
+
+    pub async fn list(&self, entity: &str, page: &PageParams) -> Result<Option<Page<TrashItem>>> {
+        self.repo.list(entity, page).await
+    }
+
+    /// None si el registro no está en la papelera; error de estado si depende
+    /// de otro registro eliminado o es un paciente fusionado
+    pub async fn restore(&self, entity: &str, id: i32, performed_by: i32) -> Result<Option<TrashItem>> {
+        let Some(blockers) = self.repo.restore_blockers(entity, id).await? else {
+            return Ok(None);
+        };
+        if !blockers.is_empty() {
+            return Err(InvalidState(blockers.join(". ")).into());
+        }
+        self.repo.restore(entity, id, performed_by).await
+    }
+
+    /// Aplica la política de retención a todas las entidades, de dependientes a padres.
+    /// `performed_by` es None cuando se ejecuta por CLI.
+    pub async fn purge(&self, dry_run: bool, performed_by: Option<i32>) -> Result<Vec<PurgeResult>> {
+        let mut results = Vec::with_capacity(TRASH_ENTITIES.len());
+        for (entity, class) in TRASH_ENTITIES {
+            let result = match self.policy.years_for(*class) {
+                Some(years) => self.repo.purge(entity, years, dry_run, performed_by).await?,
+                None => PurgeResult {
+                    entity: entity.to_string(),
+                    retention_years: None,
+                    purged: Vec::new(),
+                    skipped: Vec::new(),
+                },
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+}
@@ -1,5 +1,6 @@
-use crate::domain::user::{User, CreateUser, UpdateUser};
+use crate::domain::user::{User, CreateUser, UpdateUser, UserFilter};
 use crate::infrastructure::user_repository::UserRepository;
+use crate::domain::pagination::{Page, PageParams};
 use anyhow::Result;
 
 pub struct UserService<R: UserRepository> {
@@ -11,8 +12,8 @@ impl<R: UserRepository> UserService<R> {
         Self { repo }
     }
 
-    pub async fn get_all(&self) -> Result<Vec<User>> {
-        self.repo.get_all().await
+    pub async fn get_all(&self, filter: &UserFilter, page: &PageParams) -> Result<Page<User>> {
+        self.repo.get_all(filter, page).await
     }
 
     pub async fn get_by_id(&self, id: i32) -> Result<Option<User>> {
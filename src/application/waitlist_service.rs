@@ -0,0 +1,187 @@
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::waitlist::{
+    check_entry, AcceptedOffer, CreateWaitlistEntry, WaitlistEntry, WaitlistFilter, WaitlistOffer, WaitlistPolicy,
+    WaitlistResult, WaitlistSlot, ENTRY_OFFERED, ENTRY_WAITING, OFFER_PENDING,
+};
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::InvalidState;
+use crate::infrastructure::notifier::Notifier;
+use crate::infrastructure::waitlist_repository::WaitlistRepository;
+use anyhow::Result;
+use chrono::{Duration, Local};
+
+/// Ofrece el turno a la siguiente persona de la lista y le avisa. Lo usan la
+/// cancelación de citas y las ofertas rechazadas o vencidas. La reserva dura
+/// `hold_minutes`, sin pasar de la hora del turno. None si nadie lo recibe.
+pub async fn offer_slot<W: WaitlistRepository, N: Notifier>(
+    repo: &W,
+    notifier: &N,
+    policy: &WaitlistPolicy,
+    slot: &WaitlistSlot,
+) -> Result<Option<WaitlistOffer>> {
+    let now = Local::now().naive_local();
+    if slot.appointment_datetime <= now {
+        return Ok(None);
+    }
+    let expires_at = (now + Duration::minutes(policy.hold_minutes)).min(slot.appointment_datetime);
+    let Some(offer) = repo.offer_next(slot, expires_at).await? else {
+        return Ok(None);
+    };
+
+    // La oferta ya quedó guardada: un aviso que falla no la revierte
+    if let Some((channel, to)) = repo.get_patient_contact(offer.id_patient).await? {
+        let body = format!(
+            "Se liberó un turno el {} ({} minutos). Acéptelo antes del {} con la oferta {}, o recházelo para que pase a la siguiente persona.",
+            offer.appointment_datetime.format("%d/%m/%Y %H:%M"),
+            offer.duration_minutes,
+            offer.expires_at.format("%d/%m/%Y %H:%M"),
+            offer.id_offer
+        );
+        if let Err(e) = notifier.send(&channel, &to, "Turno disponible", &body).await {
+            eprintln!("No se pudo avisar la oferta {} de la lista de espera: {:?}", offer.id_offer, e);
+        }
+    }
+    Ok(Some(offer))
+}
+
+pub struct WaitlistService<W: WaitlistRepository, N: Notifier> {
+    repo: W,
+    notifier: N,
+    policy: WaitlistPolicy,
+}
+
+impl<W: WaitlistRepository, N: Notifier> WaitlistService<W, N> {
+    pub fn new(repo: W, notifier: N, policy: WaitlistPolicy) -> Self {
+        Self { repo, notifier, policy }
+    }
+
+    /// El personal actúa por cualquier paciente; un paciente, por sí mismo o por quienes tiene a cargo
+    async fn can_act(&self, id_patient: i32, auth: &AuthUser) -> Result<bool> {
+        Ok(auth.is_staff() || self.repo.can_act_for(id_patient, auth.id_user).await?)
+    }
+
+    /// El personal ve toda la lista; un paciente solo las entradas del paciente
+    /// que indica en `id_patient`. None si no tiene permiso.
+    pub async fn get_entries(
+        &self,
+        filter: &WaitlistFilter,
+        page: &PageParams,
+        auth: &AuthUser,
+    ) -> Result<Option<Page<WaitlistEntry>>> {
+        if !auth.is_staff() {
+            let Some(id_patient) = filter.id_patient else {
+                return Ok(None);
+            };
+            if !self.repo.can_act_for(id_patient, auth.id_user).await? {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.repo.get_entries(filter, page).await?))
+    }
+
+    /// None si no tiene permiso
+    pub async fn create_entry(&self, data: CreateWaitlistEntry, auth: &AuthUser) -> Result<Option<WaitlistEntry>> {
+        if !self.can_act(data.id_patient, auth).await? {
+            return Ok(None);
+        }
+        check_entry(&data, Local::now().date_naive())?;
+        Ok(Some(self.repo.create_entry(data, auth.id_user).await?))
+    }
+
+    /// Ofertas recibidas por la entrada. None si la entrada no existe.
+    pub async fn get_offers(&self, id_entry: i32, auth: &AuthUser) -> Result<Option<WaitlistResult<Vec<WaitlistOffer>>>> {
+        let Some(entry) = self.repo.get_entry(id_entry).await? else {
+            return Ok(None);
+        };
+        if !self.can_act(entry.id_patient, auth).await? {
+            return Ok(Some(WaitlistResult::Forbidden("No puede ver la lista de espera de este paciente")));
+        }
+        Ok(Some(WaitlistResult::Done(self.repo.get_offers(id_entry).await?)))
+    }
+
+    /// Saca al paciente de la lista; si tenía una oferta pendiente se rechaza y
+    /// el turno pasa a la siguiente persona. None si la entrada no existe.
+    pub async fn cancel_entry(&self, id: i32, auth: &AuthUser) -> Result<Option<WaitlistResult<WaitlistEntry>>> {
+        let Some(entry) = self.repo.get_entry(id).await? else {
+            return Ok(None);
+        };
+        if !self.can_act(entry.id_patient, auth).await? {
+            return Ok(Some(WaitlistResult::Forbidden("No puede cambiar la lista de espera de este paciente")));
+        }
+        if entry.status != ENTRY_WAITING && entry.status != ENTRY_OFFERED {
+            return Err(InvalidState(format!("La entrada ya está en estado '{}'", entry.status)).into());
+        }
+
+        if let Some(offer) = self.repo.get_pending_offer(id).await? {
+            if let Some(declined) = self.repo.decline_offer(offer.id_offer).await? {
+                self.pass_on(&declined).await;
+            }
+        }
+        match self.repo.cancel_entry(id).await? {
+            Some(entry) => Ok(Some(WaitlistResult::Done(entry))),
+            None => Err(InvalidState("La entrada cambió entretanto; intente de nuevo".to_string()).into()),
+        }
+    }
+
+    /// Acepta la oferta y crea la cita. None si la oferta no existe.
+    pub async fn accept(&self, id_offer: i32, auth: &AuthUser) -> Result<Option<WaitlistResult<AcceptedOffer>>> {
+        let Some(offer) = self.repo.get_offer(id_offer).await? else {
+            return Ok(None);
+        };
+        if !self.can_act(offer.id_patient, auth).await? {
+            return Ok(Some(WaitlistResult::Forbidden("Solo el paciente, su tutor legal o el personal pueden responder la oferta")));
+        }
+        self.check_pending(&offer)?;
+
+        match self.repo.accept_offer(id_offer, Local::now().naive_local()).await? {
+            Some((offer, appointment)) => Ok(Some(WaitlistResult::Done(AcceptedOffer { offer, appointment }))),
+            None => Err(InvalidState("La oferta venció o ya fue respondida".to_string()).into()),
+        }
+    }
+
+    /// Rechaza la oferta; el turno pasa a la siguiente persona y la entrada sigue
+    /// esperando otros turnos. None si la oferta no existe.
+    pub async fn decline(&self, id_offer: i32, auth: &AuthUser) -> Result<Option<WaitlistResult<WaitlistOffer>>> {
+        let Some(offer) = self.repo.get_offer(id_offer).await? else {
+            return Ok(None);
+        };
+        if !self.can_act(offer.id_patient, auth).await? {
+            return Ok(Some(WaitlistResult::Forbidden("Solo el paciente, su tutor legal o el personal pueden responder la oferta")));
+        }
+        self.check_pending(&offer)?;
+
+        let Some(declined) = self.repo.decline_offer(id_offer).await? else {
+            return Err(InvalidState("La oferta venció o ya fue respondida".to_string()).into());
+        };
+        self.pass_on(&declined).await;
+        Ok(Some(WaitlistResult::Done(declined)))
+    }
+
+    /// Vence las ofertas sin respuesta y pasa cada turno a la siguiente persona.
+    /// Devuelve cuántas ofertas vencieron.
+    pub async fn expire_offers(&self) -> Result<usize> {
+        let expired = self.repo.expire_offers(Local::now().naive_local()).await?;
+        for offer in &expired {
+            self.pass_on(offer).await;
+        }
+        Ok(expired.len())
+    }
+
+    fn check_pending(&self, offer: &WaitlistOffer) -> Result<()> {
+        if offer.status != OFFER_PENDING {
+            return Err(InvalidState(format!("La oferta ya está en estado '{}'", offer.status)).into());
+        }
+        if offer.expires_at <= Local::now().naive_local() {
+            return Err(InvalidState("La oferta venció".to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Ofrece a la siguiente persona el turno de una oferta cerrada. La oferta ya
+    /// quedó cerrada: si no se puede pasar el turno solo se registra el error.
+    async fn pass_on(&self, offer: &WaitlistOffer) {
+        if let Err(e) = offer_slot(&self.repo, &self.notifier, &self.policy, &WaitlistSlot::from(offer)).await {
+            eprintln!("No se pudo ofrecer el turno de la oferta {}: {:?}", offer.id_offer, e);
+        }
+    }
+}
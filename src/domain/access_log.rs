@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+
+// Recursos clínicos cuya lectura queda registrada
+pub const RESOURCE_PATIENT: &str = "patient";
+pub const RESOURCE_MEDICAL_HISTORY: &str = "medical_history";
+pub const RESOURCE_LAB_RESULT: &str = "lab_result";
+pub const RESOURCE_MEDICAL_DOCUMENT: &str = "medical_document";
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct AccessLog {
+    pub id_access: i32,
+    pub id_user: i32,
+    pub id_patient: i32,
+    pub resource: String,
+    pub record_id: i32,
+    pub accessed_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct CreateAccessLog {
+    pub id_user: i32,
+    pub id_patient: i32,
+    pub resource: &'static str,
+    pub record_id: i32,
+}
+
+/// Fila del reporte "quién accedió a mi expediente"
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct AccessReportEntry {
+    pub id_access: i32,
+    pub id_user: i32,
+    pub username: String,
+    pub role: String,
+    pub resource: String,
+    pub record_id: i32,
+    pub accessed_at: NaiveDateTime,
+}
@@ -1,8 +1,115 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::fmt;
 use validator::Validate;
 
+/// Estado de la cita. Solo cambia con los endpoints de transición
+/// (`/appointments/{id}/confirm`, `/cancel`, ...), nunca con PATCH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppointmentStatus {
+    Pending,
+    Confirmed,
+    /// El paciente llegó y admisión lo registró
+    CheckedIn,
+    /// El doctor está atendiendo
+    InProgress,
+    Completed,
+    Canceled,
+    /// El paciente no se presentó
+    NoShow,
+}
+
+impl AppointmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppointmentStatus::Pending => "pending",
+            AppointmentStatus::Confirmed => "confirmed",
+            AppointmentStatus::CheckedIn => "checked_in",
+            AppointmentStatus::InProgress => "in_progress",
+            AppointmentStatus::Completed => "completed",
+            AppointmentStatus::Canceled => "canceled",
+            AppointmentStatus::NoShow => "no_show",
+        }
+    }
+}
+
+impl fmt::Display for AppointmentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for AppointmentStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(AppointmentStatus::Pending),
+            "confirmed" => Ok(AppointmentStatus::Confirmed),
+            "checked_in" => Ok(AppointmentStatus::CheckedIn),
+            "in_progress" => Ok(AppointmentStatus::InProgress),
+            "completed" => Ok(AppointmentStatus::Completed),
+            "canceled" => Ok(AppointmentStatus::Canceled),
+            "no_show" => Ok(AppointmentStatus::NoShow),
+            _ => Err(format!("Estado de cita desconocido: {}", value)),
+        }
+    }
+}
+
+/// Cambio de estado de una cita; cada uno tiene su endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppointmentTransition {
+    Confirm,
+    CheckIn,
+    Start,
+    Complete,
+    Cancel,
+    NoShow,
+}
+
+impl AppointmentTransition {
+    /// Nombre con el que queda en audit_logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppointmentTransition::Confirm => "confirm",
+            AppointmentTransition::CheckIn => "check_in",
+            AppointmentTransition::Start => "start",
+            AppointmentTransition::Complete => "complete",
+            AppointmentTransition::Cancel => "cancel",
+            AppointmentTransition::NoShow => "no_show",
+        }
+    }
+
+    pub fn target(&self) -> AppointmentStatus {
+        match self {
+            AppointmentTransition::Confirm => AppointmentStatus::Confirmed,
+            AppointmentTransition::CheckIn => AppointmentStatus::CheckedIn,
+            AppointmentTransition::Start => AppointmentStatus::InProgress,
+            AppointmentTransition::Complete => AppointmentStatus::Completed,
+            AppointmentTransition::Cancel => AppointmentStatus::Canceled,
+            AppointmentTransition::NoShow => AppointmentStatus::NoShow,
+        }
+    }
+
+    /// Estados desde los que se permite la transición
+    pub fn allowed_from(&self) -> &'static [AppointmentStatus] {
+        use AppointmentStatus::*;
+        match self {
+            AppointmentTransition::Confirm => &[Pending],
+            AppointmentTransition::CheckIn => &[Pending, Confirmed],
+            AppointmentTransition::Start => &[CheckedIn],
+            AppointmentTransition::Complete => &[InProgress],
+            AppointmentTransition::Cancel => &[Pending, Confirmed, CheckedIn],
+            AppointmentTransition::NoShow => &[Pending, Confirmed],
+        }
+    }
+}
+
+// Duración de una cita sin bloque de horario que la defina
+pub const DEFAULT_DURATION_MINUTES: i32 = 30;
+
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Appointment {
     pub id_appointment: i32,
@@ -11,16 +118,29 @@ pub struct Appointment {
     pub id_area: i32,
     pub id_service: i32,
     pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    pub reschedule_count: i32,
+    /// Serie recurrente de la que forma parte y su posición en ella
+    pub id_series: Option<i32>,
+    pub series_index: Option<i32>,
     pub building: Option<String>,
     pub room: Option<String>,
     pub notes: Option<String>,
     pub prescription: Option<String>,
-    pub status: String,
+    #[sqlx(try_from = "String")]
+    pub status: AppointmentStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: Option<NaiveDateTime>,
     pub deleted_at: Option<NaiveDateTime>,
 }
 
+impl Appointment {
+    /// Fin de la cita; el instante de fin no se incluye
+    pub fn end(&self) -> NaiveDateTime {
+        self.appointment_datetime + Duration::minutes(i64::from(self.duration_minutes))
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateAppointment {
     pub id_patient: i32,
@@ -29,18 +149,81 @@ pub struct CreateAppointment {
     pub id_service: i32,
 
     pub appointment_datetime: NaiveDateTime,
+    /// Sin duración se usa la del turno del horario, o `DEFAULT_DURATION_MINUTES`
+    #[validate(range(min = 5, max = 480, message = "La cita debe durar entre 5 y 480 minutos"))]
+    pub duration_minutes: Option<i32>,
 
     pub building: Option<String>,
     pub room: Option<String>,
     pub notes: Option<String>,
 }
 
+/// Cuerpo de POST /appointments/book: un turno devuelto por /appointments/slots.
+/// El área, el edificio y la sala salen del bloque del horario.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct BookAppointment {
+    pub id_patient: i32,
+    pub id_doctor: i32,
+    pub id_service: i32,
+    pub appointment_datetime: NaiveDateTime,
+    #[validate(length(max = 500, message = "Las notas no pueden exceder 500 caracteres"))]
+    pub notes: Option<String>,
+}
+
+/// Ni el estado ni el horario se cambian aquí: `status`, `appointment_datetime`
+/// o `duration_minutes` se rechazan
 #[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateAppointment {
-    pub appointment_datetime: Option<NaiveDateTime>,
     pub building: Option<String>,
     pub room: Option<String>,
     pub notes: Option<String>,
     pub prescription: Option<String>,
-    pub status: Option<String>, // pending, confirmed, completed, canceled
+}
+
+/// Estados desde los que una cita se puede reprogramar
+pub const RESCHEDULABLE: &[AppointmentStatus] = &[AppointmentStatus::Pending, AppointmentStatus::Confirmed];
+
+/// Estados cerrados desde los que una cita se puede eliminar; las activas se cancelan
+pub const DELETABLE: &[AppointmentStatus] =
+    &[AppointmentStatus::Completed, AppointmentStatus::Canceled, AppointmentStatus::NoShow];
+
+/// Cuerpo de POST /appointments/{id}/reschedule. Sin duración se conserva la actual.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RescheduleAppointment {
+    pub appointment_datetime: NaiveDateTime,
+    #[validate(range(min = 5, max = 480, message = "La cita debe durar entre 5 y 480 minutos"))]
+    pub duration_minutes: Option<i32>,
+    #[validate(length(min = 3, max = 500, message = "El motivo debe tener entre 3 y 500 caracteres"))]
+    pub reason: String,
+}
+
+/// Cuerpo de los endpoints de transición; `reason` es obligatorio al cancelar
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TransitionAppointment {
+    #[validate(length(min = 3, max = 500, message = "El motivo debe tener entre 3 y 500 caracteres"))]
+    pub reason: Option<String>,
+}
+
+/// Resultado de una transición o reprogramación pedida por un usuario
+#[derive(Debug)]
+pub enum TransitionResult {
+    Done(Box<Appointment>),
+    /// El rol del usuario no puede hacer este cambio
+    Forbidden(&'static str),
+}
+
+/// Filtros de GET /appointments. El rango de fechas incluye ambos extremos.
+#[derive(Debug, Default, Deserialize)]
+pub struct AppointmentFilter {
+    pub id_patient: Option<i32>,
+    pub id_doctor: Option<i32>,
+    pub status: Option<AppointmentStatus>,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    /// Solo las citas reprogramadas al menos esta cantidad de veces
+    pub min_reschedules: Option<i32>,
 }
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+use std::env;
+use uuid::Uuid;
+
+/// Anticipaciones por defecto: 48 horas y 2 horas antes de la cita
+pub const DEFAULT_REMINDER_OFFSETS: &str = "48h,2h";
+
+/// Cuándo se recuerdan las citas y a dónde llevan los enlaces del aviso.
+/// Se configura con REMINDER_OFFSETS (ej. `48h,2h,30m`; `d`, `h` o `m`) y
+/// REMINDER_LINK_URL (por defecto `FRONTEND_URL/citas/recordatorio`).
+#[derive(Debug, Clone)]
+pub struct ReminderPolicy {
+    /// Minutos antes de la cita, de la mayor a la menor anticipación
+    pub offsets: Vec<i32>,
+    pub link_url: String,
+}
+
+/// "48h" -> 2880. None si no es un número positivo seguido de `d`, `h` o `m`.
+pub fn parse_offset(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (number, unit) = value.split_at(value.len().checked_sub(1)?);
+    let number: i32 = number.parse().ok().filter(|n| *n > 0)?;
+    match unit {
+        "d" => number.checked_mul(24 * 60),
+        "h" => number.checked_mul(60),
+        "m" => Some(number),
+        _ => None,
+    }
+}
+
+/// Anticipaciones de mayor a menor y sin repetir. None si alguna no es válida.
+pub fn parse_offsets(value: &str) -> Option<Vec<i32>> {
+    let mut offsets = value.split(',').map(parse_offset).collect::<Option<Vec<i32>>>()?;
+    offsets.sort_unstable_by(|a, b| b.cmp(a));
+    offsets.dedup();
+    Some(offsets)
+}
+
+impl ReminderPolicy {
+    pub fn from_env() -> Self {
+        let offsets = env::var("REMINDER_OFFSETS")
+            .ok()
+            .and_then(|v| parse_offsets(&v))
+            .unwrap_or_else(|| parse_offsets(DEFAULT_REMINDER_OFFSETS).unwrap_or_default());
+        let link_url = env::var("REMINDER_LINK_URL").ok().filter(|v| !v.is_empty()).unwrap_or_else(|| {
+            format!("{}/citas/recordatorio", env::var("FRONTEND_URL").unwrap_or_default().trim_end_matches('/'))
+        });
+        Self { offsets, link_url }
+    }
+
+    /// Enlace que abre el frontend para confirmar o cancelar con el token
+    pub fn link(&self, token: Uuid, action: &str) -> String {
+        format!("{}?token={}&action={}", self.link_url, token, action)
+    }
+}
+
+/// Recordatorio guardado de una cita; `token` autoriza sus enlaces
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct AppointmentReminder {
+    pub id_reminder: i32,
+    pub id_appointment: i32,
+    pub offset_minutes: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub token: Uuid,
+    pub channel: Option<String>,
+    pub destination: Option<String>,
+    pub sent_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// Cita a la que le toca un recordatorio, con lo necesario para el aviso
+#[derive(Debug, Clone, FromRow)]
+pub struct DueReminder {
+    pub id_appointment: i32,
+    /// Usuario del paciente, para la notificación dentro de la aplicación
+    pub id_user: Option<i32>,
+    pub appointment_datetime: NaiveDateTime,
+    pub status: String,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub doctor_name: String,
+    /// Correo si tiene, si no teléfono; None si el paciente no tiene contacto
+    pub channel: Option<String>,
+    pub destination: Option<String>,
+}
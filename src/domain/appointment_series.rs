@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::fmt;
+use std::str::FromStr;
+use validator::Validate;
+use crate::domain::appointment::Appointment;
+use crate::helpers::validators::validate_rrule;
+
+/// Máximo de citas que puede generar una serie (dos años de citas semanales)
+pub const MAX_OCCURRENCES: usize = 104;
+const MAX_INTERVAL: u32 = 52;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// Subconjunto de RRULE (RFC 5545) que aceptan las series:
+/// `FREQ=DAILY|WEEKLY|MONTHLY`, `INTERVAL`, `COUNT` o `UNTIL=AAAAMMDD`
+/// y, solo en las semanales, `BYDAY=MO,TU,...`.
+/// La fecha y hora de inicio las da la primera cita (DTSTART).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+    /// Vacío: el día de la semana de la primera cita
+    pub by_day: Vec<Weekday>,
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Día de BYDAY desconocido: {}", value)),
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let value = value.strip_prefix("RRULE:").unwrap_or(value);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+
+        for part in value.split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Parte de la regla sin valor: {}", part))?;
+            match key {
+                "FREQ" => {
+                    freq = Some(match val {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("FREQ no soportada: {} (DAILY, WEEKLY o MONTHLY)", val)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = val
+                        .parse()
+                        .ok()
+                        .filter(|i| (1..=MAX_INTERVAL).contains(i))
+                        .ok_or_else(|| format!("INTERVAL debe ser un número entre 1 y {}", MAX_INTERVAL))?
+                }
+                "COUNT" => {
+                    count = Some(
+                        val.parse()
+                            .ok()
+                            .filter(|c| (1..=MAX_OCCURRENCES as u32).contains(c))
+                            .ok_or_else(|| format!("COUNT debe ser un número entre 1 y {}", MAX_OCCURRENCES))?,
+                    )
+                }
+                "UNTIL" => {
+                    // Se admite también la forma con hora (AAAAMMDDTHHMMSS); solo cuenta el día
+                    let date = val.get(..8).unwrap_or(val);
+                    until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| format!("UNTIL debe tener el formato AAAAMMDD: {}", val))?,
+                    )
+                }
+                "BYDAY" => {
+                    for day in val.split(',') {
+                        let day = parse_weekday(day)?;
+                        if !by_day.contains(&day) {
+                            by_day.push(day);
+                        }
+                    }
+                }
+                _ => return Err(format!("Parte de la regla no soportada: {}", key)),
+            }
+        }
+
+        let freq = freq.ok_or("La regla necesita FREQ")?;
+        match (count, until) {
+            (None, None) => return Err("La regla necesita COUNT o UNTIL".to_string()),
+            (Some(_), Some(_)) => return Err("COUNT y UNTIL no se pueden usar juntos".to_string()),
+            _ => {}
+        }
+        if !by_day.is_empty() && freq != Frequency::Weekly {
+            return Err("BYDAY solo se admite con FREQ=WEEKLY".to_string());
+        }
+        by_day.sort_by_key(|d| d.num_days_from_monday());
+
+        Ok(RecurrenceRule { freq, interval, count, until, by_day })
+    }
+}
+
+/// Forma normalizada, la que se guarda en appointment_series.rrule
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.freq.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|d| weekday_code(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl RecurrenceRule {
+    /// Fechas de las citas a partir de la primera, que siempre se incluye
+    /// aunque no caiga en BYDAY. Los meses sin el día de inicio (31, 30, 29
+    /// de febrero) se saltan, como en RFC 5545.
+    pub fn occurrences(&self, start: NaiveDateTime) -> Result<Vec<NaiveDateTime>, String> {
+        // Con UNTIL se genera una de más para detectar series demasiado largas
+        let limit = self.count.map(|c| c as usize).unwrap_or(MAX_OCCURRENCES + 1);
+        let date = start.date();
+        let mut dates = vec![date];
+
+        let mut period: i64 = 0;
+        'periods: while dates.len() < limit {
+            for day in self.period_dates(date, period) {
+                if day <= date {
+                    continue;
+                }
+                if self.until.is_some_and(|until| day > until) || dates.len() == limit {
+                    break 'periods;
+                }
+                dates.push(day);
+            }
+            period += i64::from(self.interval);
+        }
+
+        if dates.len() > MAX_OCCURRENCES {
+            return Err(format!("La serie no puede tener más de {} citas", MAX_OCCURRENCES));
+        }
+        Ok(dates.into_iter().map(|d| d.and_time(start.time())).collect())
+    }
+
+    /// Fechas candidatas del periodo (día, semana o mes) número `period` contado desde el inicio
+    fn period_dates(&self, start: NaiveDate, period: i64) -> Vec<NaiveDate> {
+        match self.freq {
+            Frequency::Daily => vec![start + Duration::days(period)],
+            Frequency::Weekly => {
+                let monday = start - Duration::days(i64::from(start.weekday().num_days_from_monday()));
+                let week = monday + Duration::weeks(period);
+                if self.by_day.is_empty() {
+                    vec![week + Duration::days(i64::from(start.weekday().num_days_from_monday()))]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|d| week + Duration::days(i64::from(d.num_days_from_monday())))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let months = i64::from(start.month0()) + period;
+                let year = start.year() + (months / 12) as i32;
+                NaiveDate::from_ymd_opt(year, (months % 12) as u32 + 1, start.day()).into_iter().collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct AppointmentSeries {
+    pub id_series: i32,
+    pub id_patient: i32,
+    pub id_doctor: i32,
+    pub id_area: i32,
+    pub id_service: i32,
+    pub rrule: String,
+    pub dtstart: NaiveDateTime,
+    pub duration_minutes: Option<i32>,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Cuerpo de POST /appointments/series. `appointment_datetime` es la primera
+/// cita; sin duración, edificio o sala cada cita toma los de su bloque del horario.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateAppointmentSeries {
+    pub id_patient: i32,
+    pub id_doctor: i32,
+    pub id_area: i32,
+    pub id_service: i32,
+
+    pub appointment_datetime: NaiveDateTime,
+    #[validate(range(min = 5, max = 480, message = "La cita debe durar entre 5 y 480 minutos"))]
+    pub duration_minutes: Option<i32>,
+
+    #[validate(custom = "validate_rrule")]
+    pub rrule: String,
+
+    #[validate(length(max = 10, message = "El edificio no puede exceder 10 caracteres"))]
+    pub building: Option<String>,
+    #[validate(length(max = 10, message = "La sala no puede exceder 10 caracteres"))]
+    pub room: Option<String>,
+    #[validate(length(max = 500, message = "Las notas no pueden exceder 500 caracteres"))]
+    pub notes: Option<String>,
+
+    /// Crea la serie sin las citas en conflicto en lugar de rechazarla entera
+    #[serde(default)]
+    pub skip_conflicts: bool,
+}
+
+/// Cita de la serie lista para insertarse, con los valores de su bloque ya aplicados
+#[derive(Debug, Clone)]
+pub struct SeriesOccurrence {
+    pub series_index: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    pub building: Option<String>,
+    pub room: Option<String>,
+}
+
+/// Cita de la serie que no se pudo crear: fuera del horario o cruzada con otra
+#[derive(Debug, Serialize, Clone)]
+pub struct OccurrenceConflict {
+    pub series_index: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub detail: String,
+    pub conflicting_id: Option<i32>,
+}
+
+/// Respuesta de POST /appointments/series; con `dry_run` nada queda guardado
+#[derive(Debug, Serialize)]
+pub struct SeriesResult {
+    pub series: AppointmentSeries,
+    pub appointments: Vec<Appointment>,
+    pub conflicts: Vec<OccurrenceConflict>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesDetail {
+    pub series: AppointmentSeries,
+    pub appointments: Vec<Appointment>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SeriesParams {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Citas de la serie a las que aplica un cambio, a partir de la indicada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesScope {
+    /// Solo esta cita
+    This,
+    /// Esta y las siguientes
+    Following,
+    /// Toda la serie
+    All,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScopeParams {
+    pub scope: SeriesScope,
+}
+
+/// Cuerpo de PATCH /appointments/{id}/series. `time` mueve cada cita a esa
+/// hora de su mismo día; cambiar hora o duración es reprogramar y pide `reason`.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateSeriesOccurrences {
+    pub time: Option<NaiveTime>,
+    #[validate(range(min = 5, max = 480, message = "La cita debe durar entre 5 y 480 minutos"))]
+    pub duration_minutes: Option<i32>,
+    #[validate(length(max = 10, message = "El edificio no puede exceder 10 caracteres"))]
+    pub building: Option<String>,
+    #[validate(length(max = 10, message = "La sala no puede exceder 10 caracteres"))]
+    pub room: Option<String>,
+    #[validate(length(max = 500, message = "Las notas no pueden exceder 500 caracteres"))]
+    pub notes: Option<String>,
+    #[validate(length(min = 3, max = 500, message = "El motivo debe tener entre 3 y 500 caracteres"))]
+    pub reason: Option<String>,
+}
+
+impl UpdateSeriesOccurrences {
+    pub fn changes_schedule(&self) -> bool {
+        self.time.is_some() || self.duration_minutes.is_some()
+    }
+}
+
+/// Nuevo horario de una cita de la serie al aplicar un cambio
+#[derive(Debug, Clone)]
+pub struct OccurrenceChange {
+    pub from: Appointment,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+}
+
+impl OccurrenceChange {
+    pub fn is_reschedule(&self) -> bool {
+        self.appointment_datetime != self.from.appointment_datetime
+            || self.duration_minutes != self.from.duration_minutes
+    }
+}
+
+/// Resultado de un cambio sobre varias citas de una serie
+#[derive(Debug)]
+pub enum SeriesChange {
+    Done(Vec<Appointment>),
+    /// El rol del usuario no puede hacer este cambio
+    Forbidden(&'static str),
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+
+// Acciones registradas en audit_logs
+pub const ACTION_MERGE: &str = "merge";
+pub const ACTION_UNMERGE: &str = "unmerge";
+pub const ACTION_CONFIRM_IDENTITY: &str = "confirm_identity";
+pub const ACTION_STATUS_CHANGE: &str = "status_change";
+pub const ACTION_RESCHEDULE: &str = "reschedule";
+pub const ACTION_RESTORE: &str = "restore";
+pub const ACTION_PURGE: &str = "purge";
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct AuditLog {
+    pub id_audit: i32,
+    pub table_name: String,
+    pub record_id: i32,
+    pub action: String,
+    pub old_data: Option<serde_json::Value>,
+    pub new_data: Option<serde_json::Value>,
+    pub performed_by: Option<i32>,
+    pub performed_at: Option<NaiveDateTime>,
+}
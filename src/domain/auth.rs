@@ -4,7 +4,7 @@ use chrono::{Utc, Duration};
 /// Datos que se guardan en el JWT
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String,   // username o id_user
+    pub sub: String,   // id_user
     pub role: String,  // "doctor", "patient", "admin"
     pub exp: usize,    // fecha de expiración (timestamp)
 }
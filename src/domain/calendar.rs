@@ -0,0 +1,203 @@
+use serde::Serialize;
+use sqlx::FromRow;
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::env;
+use uuid::Uuid;
+use crate::domain::appointment::AppointmentStatus;
+
+/// Días hacia atrás que incluye el calendario; las citas futuras van todas
+pub const FEED_PAST_DAYS: i64 = 90;
+
+/// Hospital que aparece en la ubicación de las citas. Se configura con
+/// HOSPITAL_ID; sin él se usa el primer hospital registrado.
+#[derive(Debug, Clone, Copy)]
+pub struct CalendarPolicy {
+    pub hospital_id: Option<i32>,
+}
+
+impl CalendarPolicy {
+    pub fn from_env() -> Self {
+        let hospital_id = env::var("HOSPITAL_ID").ok().and_then(|v| v.parse::<i32>().ok());
+        Self { hospital_id }
+    }
+}
+
+/// Token del calendario de un usuario
+#[derive(Debug, Clone, FromRow)]
+pub struct CalendarFeed {
+    pub token: Uuid,
+    pub created_at: Option<NaiveDateTime>,
+    pub last_accessed_at: Option<NaiveDateTime>,
+}
+
+/// Respuesta de /calendar/feed: la ruta secreta para suscribirse
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedLink {
+    pub path: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub last_accessed_at: Option<NaiveDateTime>,
+}
+
+impl From<CalendarFeed> for CalendarFeedLink {
+    fn from(feed: CalendarFeed) -> Self {
+        Self {
+            path: format!("/calendar/{}.ics", feed.token),
+            created_at: feed.created_at,
+            last_accessed_at: feed.last_accessed_at,
+        }
+    }
+}
+
+/// De quién es el calendario: cambia qué citas incluye y cómo se titulan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedOwner {
+    /// Las citas que atiende el doctor
+    Doctor,
+    /// Las citas del paciente y de quienes tiene a cargo como tutor legal
+    Patient,
+}
+
+/// Cita con los datos que se muestran en el calendario
+#[derive(Debug, Clone, FromRow)]
+pub struct CalendarEvent {
+    pub id_appointment: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    #[sqlx(try_from = "String")]
+    pub status: AppointmentStatus,
+    pub reschedule_count: i32,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub service_name: Option<String>,
+    pub doctor_name: String,
+    pub patient_name: String,
+    pub hospital_name: Option<String>,
+    pub hospital_address: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// TEXT de RFC 5545: escapa `\`, `;`, `,` y saltos de línea
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Parte las líneas de más de 75 bytes sin cortar caracteres; las
+/// continuaciones empiezan con un espacio
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Hora local de la cita sin zona ("flotante"): el calendario la muestra tal cual
+fn local_time(dt: NaiveDateTime) -> String {
+    dt.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// DTSTAMP y LAST-MODIFIED van en UTC
+fn utc_time(dt: NaiveDateTime) -> String {
+    let utc = Local.from_local_datetime(&dt).earliest().map(|l| l.with_timezone(&Utc).naive_utc()).unwrap_or(dt);
+    format!("{}Z", local_time(utc))
+}
+
+impl CalendarEvent {
+    /// UID estable: al actualizar el calendario la cita reemplaza a la anterior
+    pub fn uid(&self) -> String {
+        format!("appointment-{}@nexo", self.id_appointment)
+    }
+
+    fn ics_status(&self) -> &'static str {
+        match self.status {
+            AppointmentStatus::Pending => "TENTATIVE",
+            AppointmentStatus::Canceled | AppointmentStatus::NoShow => "CANCELLED",
+            _ => "CONFIRMED",
+        }
+    }
+
+    /// Sube con cada reprogramación y al cancelarse, para que los clientes
+    /// reconozcan la versión nueva
+    fn sequence(&self) -> i32 {
+        let closed = matches!(self.status, AppointmentStatus::Canceled | AppointmentStatus::NoShow);
+        self.reschedule_count + i32::from(closed)
+    }
+
+    fn location(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        parts.extend(self.hospital_name.clone());
+        parts.extend(self.hospital_address.clone());
+        parts.extend(self.building.as_ref().map(|b| format!("Edificio {}", b)));
+        parts.extend(self.room.as_ref().map(|r| format!("Sala {}", r)));
+        parts.join(", ")
+    }
+
+    fn summary(&self, owner: FeedOwner) -> String {
+        let service = self.service_name.as_deref().map(|s| format!(" ({})", s)).unwrap_or_default();
+        let summary = match owner {
+            FeedOwner::Doctor => format!("Cita: {}{}", self.patient_name, service),
+            FeedOwner::Patient => format!("Cita de {} con {}{}", self.patient_name, self.doctor_name, service),
+        };
+        match self.status {
+            AppointmentStatus::Canceled => format!("[Cancelada] {}", summary),
+            AppointmentStatus::NoShow => format!("[No asistió] {}", summary),
+            _ => summary,
+        }
+    }
+}
+
+/// Calendario iCalendar (RFC 5545) con una VEVENT por cita
+pub fn render_calendar(name: &str, owner: FeedOwner, events: &[CalendarEvent], now: NaiveDateTime) -> String {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//Nexo//Agenda de citas//ES",
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+        "REFRESH-INTERVAL;VALUE=DURATION:PT1H",
+        "X-PUBLISHED-TTL:PT1H",
+    ] {
+        push_line(&mut out, line);
+    }
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+
+    for event in events {
+        let end = event.appointment_datetime + chrono::Duration::minutes(i64::from(event.duration_minutes));
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", event.uid()));
+        push_line(&mut out, &format!("DTSTAMP:{}", utc_time(now)));
+        if let Some(modified) = event.updated_at.or(event.created_at) {
+            push_line(&mut out, &format!("LAST-MODIFIED:{}", utc_time(modified)));
+        }
+        push_line(&mut out, &format!("DTSTART:{}", local_time(event.appointment_datetime)));
+        push_line(&mut out, &format!("DTEND:{}", local_time(end)));
+        push_line(&mut out, &format!("SEQUENCE:{}", event.sequence()));
+        push_line(&mut out, &format!("STATUS:{}", event.ics_status()));
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&event.summary(owner))));
+        let location = event.location();
+        if !location.is_empty() {
+            push_line(&mut out, &format!("LOCATION:{}", escape(&location)));
+        }
+        let description = format!(
+            "Paciente: {}\nDoctor: {}\nEstado: {}",
+            event.patient_name, event.doctor_name, event.status
+        );
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape(&description)));
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
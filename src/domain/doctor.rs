@@ -67,4 +67,12 @@ pub struct UpdateDoctor {
 
     #[validate(email(message = "Email inválido"))]
     pub email: Option<String>,
-}
\ No newline at end of file
+}
+
+/// Filtros de GET /doctors
+#[derive(Debug, Default, Deserialize)]
+pub struct DoctorFilter {
+    pub id_area: Option<i32>,
+    pub id_speciality: Option<i32>,
+    pub id_service: Option<i32>,
+}
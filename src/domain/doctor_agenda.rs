@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::domain::appointment::AppointmentStatus;
+
+/// Parámetros de GET /doctors/{id}/agenda; sin fecha, la de hoy
+#[derive(Debug, Default, Deserialize)]
+pub struct AgendaParams {
+    pub date: Option<NaiveDate>,
+    /// Incluir las citas canceladas (por defecto no aparecen)
+    #[serde(default)]
+    pub include_canceled: bool,
+}
+
+/// Una cita de la agenda con lo que el doctor necesita ver de un vistazo
+#[derive(Debug, Serialize, Clone, FromRow)]
+pub struct AgendaAppointment {
+    pub id_appointment: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    #[sqlx(try_from = "String")]
+    pub status: AppointmentStatus,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub service_name: Option<String>,
+    pub notes: Option<String>,
+    pub id_patient: i32,
+    pub patient_name: String,
+    pub birthdate: NaiveDate,
+    /// Años cumplidos el día de la agenda
+    pub age: i32,
+    pub gender: Option<String>,
+    pub blood_type: Option<String>,
+    pub allergies: Option<String>,
+    // DEK envuelta del paciente para descifrar las alergias; nunca se expone
+    #[serde(skip)]
+    pub data_key: Option<String>,
+    /// Hora en que admisión registró la llegada
+    pub checked_in_at: Option<NaiveDateTime>,
+    /// Minutos en sala de espera: desde la llegada (o desde que sacó su turno
+    /// virtual) hasta ahora, solo mientras espera ser atendido
+    #[sqlx(skip)]
+    pub waiting_minutes: Option<i64>,
+    /// Último registro del historial clínico del paciente
+    pub id_last_history: Option<i32>,
+    pub last_diagnosis: Option<String>,
+    pub last_diagnosis_date: Option<NaiveDateTime>,
+    /// Turno virtual del paciente ese día, si sacó uno
+    pub id_turn: Option<i32>,
+    pub turn_number: Option<i32>,
+    pub turn_status: Option<String>,
+    pub turn_created_at: Option<NaiveDateTime>,
+    pub turn_called_at: Option<NaiveDateTime>,
+}
+
+impl AgendaAppointment {
+    /// Minutos que lleva esperando a `now`; None si no está en sala de espera
+    pub fn waiting_since(&self, now: NaiveDateTime) -> Option<i64> {
+        let waiting = match self.status {
+            AppointmentStatus::CheckedIn => true,
+            AppointmentStatus::Pending | AppointmentStatus::Confirmed => {
+                self.turn_status.as_deref() == Some("waiting")
+            }
+            _ => false,
+        };
+        if !waiting {
+            return None;
+        }
+        let since = self.checked_in_at.or(self.turn_created_at)?;
+        Some((now - since).num_minutes().max(0))
+    }
+}
+
+/// Respuesta de GET /doctors/{id}/agenda: citas del día en orden de hora
+#[derive(Debug, Serialize)]
+pub struct DoctorAgenda {
+    pub id_doctor: i32,
+    pub date: NaiveDate,
+    pub appointments: Vec<AgendaAppointment>,
+}
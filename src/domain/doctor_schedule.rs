@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use validator::{Validate, ValidationError, ValidationErrors};
+use crate::helpers::validators::validate_exception_type;
+
+/// Bloque semanal en el que atiende un doctor
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct DoctorSchedule {
+    pub id_schedule: i32,
+    pub id_doctor: i32,
+    pub id_area: i32,
+    /// ISO: 1 lunes ... 7 domingo
+    pub day_of_week: i16,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub slot_minutes: i32,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl DoctorSchedule {
+    /// La hora cae al inicio de un turno y el turno termina dentro del bloque
+    pub fn fits_slot(&self, time: NaiveTime) -> bool {
+        if time < self.start_time || time.second() != 0 || time.nanosecond() != 0 {
+            return false;
+        }
+        let offset = (time - self.start_time).num_minutes();
+        let slot = i64::from(self.slot_minutes);
+        offset % slot == 0 && (self.end_time - time).num_minutes() >= slot
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDoctorSchedule {
+    pub id_area: i32,
+
+    #[validate(range(min = 1, max = 7, message = "day_of_week va de 1 (lunes) a 7 (domingo)"))]
+    pub day_of_week: i16,
+
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+
+    #[validate(range(min = 5, max = 240, message = "El turno debe durar entre 5 y 240 minutos"))]
+    pub slot_minutes: i32,
+
+    #[validate(length(max = 10, message = "El edificio no puede exceder 10 caracteres"))]
+    pub building: Option<String>,
+    #[validate(length(max = 10, message = "La sala no puede exceder 10 caracteres"))]
+    pub room: Option<String>,
+}
+
+// Tipos de ausencia
+pub const EXCEPTION_VACATION: &str = "vacation";
+pub const EXCEPTION_CONGRESS: &str = "congress";
+pub const EXCEPTION_SICK_LEAVE: &str = "sick_leave";
+pub const EXCEPTION_OTHER: &str = "other";
+
+/// Ausencia del doctor por fecha
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct DoctorScheduleException {
+    pub id_exception: i32,
+    pub id_doctor: i32,
+    pub exception_type: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Sin horas la ausencia cubre los días completos
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDoctorScheduleException {
+    /// `vacation`, `congress`, `sick_leave` u `other`
+    #[validate(custom = "validate_exception_type")]
+    pub exception_type: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    #[validate(length(max = 500, message = "Las notas no pueden exceder 500 caracteres"))]
+    pub notes: Option<String>,
+}
+
+impl CreateDoctorScheduleException {
+    /// Inicio y fin de la ausencia; el fin no se incluye
+    pub fn range(&self) -> (NaiveDateTime, NaiveDateTime) {
+        match (self.start_time, self.end_time) {
+            (Some(start), Some(end)) => (self.start_date.and_time(start), self.end_date.and_time(end)),
+            _ => (
+                self.start_date.and_time(NaiveTime::MIN),
+                self.end_date.succ_opt().unwrap_or(self.end_date).and_time(NaiveTime::MIN),
+            ),
+        }
+    }
+}
+
+/// Respuesta al registrar una ausencia: las citas activas que quedan dentro
+/// no se cancelan solas, hay que reprogramarlas
+#[derive(Debug, Serialize)]
+pub struct CreatedScheduleException {
+    #[serde(flatten)]
+    pub exception: DoctorScheduleException,
+    pub affected_appointments: Vec<i32>,
+}
+
+/// Filtros de GET /doctors/{id}/exceptions (fechas incluidas)
+#[derive(Debug, Default, Deserialize)]
+pub struct ExceptionFilter {
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+}
+
+// Búsqueda de turnos libres
+pub const DEFAULT_SLOT_DAYS: i64 = 14;
+pub const MAX_SLOT_DAYS: i64 = 62;
+pub const DEFAULT_SLOT_LIMIT: i64 = 20;
+
+/// Filtros de GET /appointments/slots: al menos un criterio además del rango.
+/// Sin fechas se busca desde hoy durante `DEFAULT_SLOT_DAYS` días.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct SlotFilter {
+    pub id_speciality: Option<i32>,
+    pub id_service: Option<i32>,
+    pub id_area: Option<i32>,
+    pub id_doctor: Option<i32>,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    #[validate(range(min = 1, max = 100, message = "limit debe estar entre 1 y 100"))]
+    pub limit: Option<i64>,
+}
+
+/// Turno libre de un bloque del horario
+#[derive(Debug, Serialize, FromRow)]
+pub struct FreeSlot {
+    pub id_doctor: i32,
+    pub doctor_name: Option<String>,
+    pub id_service: i32,
+    pub id_speciality: Option<i32>,
+    pub id_area: i32,
+    pub id_schedule: i32,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub building: Option<String>,
+    pub room: Option<String>,
+}
+
+fn range_error(field: &'static str, message: &'static str) -> ValidationErrors {
+    let mut e = ValidationError::new("range");
+    e.message = Some(message.into());
+    let mut errors = ValidationErrors::new();
+    errors.add(field, e);
+    errors
+}
+
+/// Reglas que cruzan campos del bloque: el inicio antes del fin
+/// y al menos un turno completo
+pub fn check_schedule(data: &CreateDoctorSchedule) -> Result<(), ValidationErrors> {
+    if data.start_time >= data.end_time {
+        return Err(range_error("end_time", "La hora de fin debe ser posterior a la de inicio"));
+    }
+    if (data.end_time - data.start_time).num_minutes() < i64::from(data.slot_minutes) {
+        return Err(range_error("slot_minutes", "El bloque debe alcanzar al menos para un turno"));
+    }
+    Ok(())
+}
+
+/// Reglas que cruzan campos de la ausencia: fechas en orden y horas las dos o ninguna
+pub fn check_exception(data: &CreateDoctorScheduleException) -> Result<(), ValidationErrors> {
+    if data.start_date > data.end_date {
+        return Err(range_error("end_date", "La fecha de fin no puede ser anterior a la de inicio"));
+    }
+    if data.start_time.is_some() != data.end_time.is_some() {
+        return Err(range_error("end_time", "Indique hora de inicio y de fin, o ninguna para días completos"));
+    }
+    let (start, end) = data.range();
+    if start >= end {
+        return Err(range_error("end_time", "La hora de fin debe ser posterior a la de inicio"));
+    }
+    Ok(())
+}
+
+/// Rango de la búsqueda de turnos libres, ambos extremos incluidos
+pub fn check_slot_filter(filter: &SlotFilter, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), ValidationErrors> {
+    if filter.id_speciality.is_none() && filter.id_service.is_none() && filter.id_area.is_none() && filter.id_doctor.is_none() {
+        return Err(range_error(
+            "id_speciality",
+            "Indique una especialidad, servicio, área o doctor",
+        ));
+    }
+    let from = filter.date_from.unwrap_or(today).max(today);
+    let to = filter.date_to.unwrap_or(from + chrono::Duration::days(DEFAULT_SLOT_DAYS - 1));
+    if to < from {
+        return Err(range_error("date_to", "La fecha de fin no puede ser anterior a la de inicio ni a hoy"));
+    }
+    if (to - from).num_days() >= MAX_SLOT_DAYS {
+        return Err(range_error("date_to", "El rango no puede exceder 62 días"));
+    }
+    Ok((from, to))
+}
@@ -32,3 +32,11 @@ pub struct UpdateLabResult {
     pub test_type: Option<String>,
     pub result: Option<String>,
 }
+
+/// Filtros de GET /lab_results
+#[derive(Debug, Default, Deserialize)]
+pub struct LabResultFilter {
+    pub id_patient: Option<i32>,
+    pub id_doctor: Option<i32>,
+    pub test_type: Option<String>,
+}
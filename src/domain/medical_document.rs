@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+
+// Tipos de archivo aceptados y su extensión en el almacenamiento
+pub const ALLOWED_CONTENT_TYPES: &[(&str, &str)] = &[
+    ("application/pdf", "pdf"),
+    ("image/jpeg", "jpg"),
+    ("image/png", "png"),
+    ("application/dicom", "dcm"),
+];
+
+// Tamaño máximo por defecto de un documento (10 MB)
+pub const DEFAULT_MAX_DOCUMENT_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct MedicalDocument {
+    pub id_document: i32,
+    pub id_patient: i32,
+    pub id_doctor: Option<i32>,
+    pub document_type: Option<String>,
+    pub file_path: String,
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub file_size: Option<i64>,
+    pub preview_path: Option<String>,
+    pub description: Option<String>,
+    pub uploaded_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug)]
+pub struct CreateMedicalDocument {
+    pub id_patient: i32,
+    pub id_doctor: Option<i32>,
+    pub document_type: Option<String>,
+    pub file_path: String,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub file_size: i64,
+    pub preview_path: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Archivo recibido en el multipart, antes de guardarse
+#[derive(Debug)]
+pub struct UploadMedicalDocument {
+    pub id_patient: i32,
+    pub id_doctor: Option<i32>,
+    pub document_type: Option<String>,
+    pub description: Option<String>,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub data: bytes::Bytes,
+}
+
+/// Devuelve la extensión asociada a un content-type permitido
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    ALLOWED_CONTENT_TYPES
+        .iter()
+        .find(|(ct, _)| *ct == content_type)
+        .map(|(_, ext)| *ext)
+}
@@ -32,3 +32,10 @@ pub struct UpdateMedicalHistory {
     pub treatment: Option<String>,
     pub notes: Option<String>,
 }
+
+/// Filtros de GET /medical_histories
+#[derive(Debug, Default, Deserialize)]
+pub struct MedicalHistoryFilter {
+    pub id_patient: Option<i32>,
+    pub id_doctor: Option<i32>,
+}
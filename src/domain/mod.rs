@@ -7,4 +7,20 @@ pub mod speciality;
 pub mod appointment;
 pub mod medical_history;
 pub mod lab_result;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod access_log;
+pub mod medical_document;
+pub mod pagination;
+pub mod audit_log;
+pub mod patient_merge;
+pub mod patient_contact;
+pub mod registration;
+pub mod virtual_turn;
+pub mod patient_import;
+pub mod trash;
+pub mod doctor_schedule;
+pub mod appointment_series;
+pub mod waitlist;
+pub mod appointment_reminder;
+pub mod calendar;
+pub mod doctor_agenda;
\ No newline at end of file
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use validator::Validate;
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 100;
+
+/// Parámetros comunes de todos los listados:
+/// `?limit=20&offset=40` o `?limit=20&cursor=<next_cursor>`, y `sort=campo` / `sort=-campo`
+#[derive(Debug, Default, Clone, Deserialize, Validate)]
+pub struct PageParams {
+    #[validate(range(min = 1, max = 100, message = "limit debe estar entre 1 y 100"))]
+    pub limit: Option<i64>,
+    #[validate(range(min = 0, message = "offset no puede ser negativo"))]
+    pub offset: Option<i64>,
+    /// Cursor opaco devuelto en `next_cursor`; no se combina con offset
+    pub cursor: Option<String>,
+    /// Campo de orden permitido por el listado; prefijo '-' para descendente
+    pub sort: Option<String>,
+}
+
+impl PageParams {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// Página de resultados. `offset` es None cuando se paginó por cursor.
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: Option<i64>,
+    pub next_cursor: Option<String>,
+}
+
+/// Campo de orden desconocido o cursor inválido: se responde como 400
+#[derive(Debug)]
+pub struct InvalidListQuery(pub String);
+
+impl fmt::Display for InvalidListQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidListQuery {}
@@ -1,14 +1,50 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::{NaiveDate, NaiveDateTime};
-use validator::{Validate};
-use crate::helpers::validators::validate_phone;
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use validator::{Validate, ValidationError, ValidationErrors};
+use crate::helpers::validators::{validate_identity, validate_patient_status, validate_phone};
+
+// Tipos de documento de identidad
+pub const IDENTITY_CEDULA: &str = "cedula";
+/// Para menores de edad que aún no tienen cédula
+pub const IDENTITY_BIRTH_CERTIFICATE: &str = "partida_nacimiento";
+
+// Estados del paciente
+pub const STATUS_ACTIVE: &str = "active";
+/// Autorregistrado: falta que admisión verifique la cédula física
+pub const STATUS_PENDING_VERIFICATION: &str = "pending_verification";
+/// Ya no se atiende en el centro (abandono, sin contacto)
+pub const STATUS_INACTIVE: &str = "inactive";
+/// Trasladado a otro centro de salud
+pub const STATUS_TRANSFERRED_OUT: &str = "transferred_out";
+/// Estado final: cancela sus citas futuras, bloquea turnos y notificaciones
+pub const STATUS_DECEASED: &str = "deceased";
+
+pub const PATIENT_STATUSES: &[&str] = &[
+    STATUS_PENDING_VERIFICATION,
+    STATUS_ACTIVE,
+    STATUS_INACTIVE,
+    STATUS_TRANSFERRED_OUT,
+    STATUS_DECEASED,
+];
+
+/// Estados a los que se puede pasar desde `from` con POST /patients/{id}/status.
+/// `pending_verification` solo sale por confirm-identity y `deceased` no tiene salida.
+pub fn allowed_transitions(from: &str) -> &'static [&'static str] {
+    match from {
+        STATUS_ACTIVE => &[STATUS_INACTIVE, STATUS_TRANSFERRED_OUT, STATUS_DECEASED],
+        STATUS_INACTIVE => &[STATUS_ACTIVE, STATUS_TRANSFERRED_OUT, STATUS_DECEASED],
+        STATUS_TRANSFERRED_OUT => &[STATUS_ACTIVE, STATUS_INACTIVE, STATUS_DECEASED],
+        _ => &[],
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Patient {
     pub id_patient: i32,
     pub id_user: Option<i32>,
-    pub identity_number: String,  // Cédula
+    pub identity_number: String,  // Cédula o partida de nacimiento
+    pub identity_type: String,
 
     pub first_name: String,
     pub second_name: Option<String>,
@@ -32,18 +68,24 @@ pub struct Patient {
  
     pub priority: Option<i32>,
     pub status: Option<String>,
+    pub status_date: Option<NaiveDate>,
+    pub status_reason: Option<String>,
  
     pub created_at: NaiveDateTime,
     pub updated_at: Option<NaiveDateTime>,
     pub deleted_at: Option<NaiveDateTime>,
+
+    // DEK envuelta con la llave maestra; nunca se expone en la API
+    #[serde(skip)]
+    pub data_key: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreatePatient {
-    #[validate(length(min = 8, message = "La contraseña debe tener al menos 8 caracteres"))]
- 
-    #[validate(length(min = 5, message = "La cédula debe tener al menos 5 caracteres"))]
+    // Se valida contra birthdate con check_identity
     pub identity_number: String,
+    /// `cedula` (por defecto) o `partida_nacimiento`
+    pub identity_type: Option<String>,
 
     #[validate(length(min = 2, message = "El nombre debe tener al menos 2 caracteres"))]
     pub first_name: String,
@@ -78,15 +120,16 @@ pub struct CreatePatient {
     pub medical_background: Option<String>,
 
     pub priority: Option<i32>,
+    #[validate(custom = "validate_patient_status")]
     pub status: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Default, Deserialize, Validate)]
 pub struct UpdatePatient {
     pub id_user: Option<i32>,
 
-    #[validate(length(min = 5, message = "La cédula debe tener al menos 5 caracteres"))]
     pub identity_number: Option<String>,
+    pub identity_type: Option<String>,
 
     #[validate(length(min = 2, message = "El nombre debe tener al menos 2 caracteres"))]
     pub first_name: Option<String>,
@@ -116,6 +159,116 @@ pub struct UpdatePatient {
     pub medical_background: Option<String>,
 
     pub priority: Option<i32>,
+}
+
+/// Datos que el propio paciente puede cambiar desde el portal (PATCH /me/profile).
+/// Cualquier otro campo se rechaza para no ignorarlo en silencio.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateOwnProfile {
+    #[validate(custom = "validate_phone")]
+    pub phone: Option<String>,
+
+    #[validate(length(max = 300, message = "La dirección no puede exceder 300 caracteres"))]
+    pub address: Option<String>,
+
+    #[validate(length(min = 2, max = 100, message = "El nombre del contacto debe tener entre 2 y 100 caracteres"))]
+    pub emergency_contact_name: Option<String>,
+    #[validate(custom = "validate_phone")]
+    pub emergency_contact_phone: Option<String>,
+}
+
+impl From<UpdateOwnProfile> for UpdatePatient {
+    fn from(data: UpdateOwnProfile) -> Self {
+        UpdatePatient {
+            phone: data.phone,
+            address: data.address,
+            emergency_contact_name: data.emergency_contact_name,
+            emergency_contact_phone: data.emergency_contact_phone,
+            ..Default::default()
+        }
+    }
+}
+
+/// Cambio de estado del paciente (POST /patients/{id}/status)
+#[derive(Debug, Deserialize)]
+pub struct ChangePatientStatus {
+    pub status: String,
+    /// Fecha del evento (defunción, traslado); por defecto hoy
+    pub date: Option<NaiveDate>,
+    /// Obligatorio salvo al reactivar
+    pub reason: Option<String>,
+}
+
+impl Validate for ChangePatientStatus {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if let Err(e) = validate_patient_status(&self.status) {
+            errors.add("status", e);
+        }
+        if self.date.is_some_and(|d| d > Local::now().date_naive()) {
+            let mut e = ValidationError::new("date");
+            e.message = Some("La fecha no puede ser futura".into());
+            errors.add("date", e);
+        }
+        match self.reason.as_deref().map(str::trim) {
+            Some(r) if r.chars().count() > 500 => {
+                let mut e = ValidationError::new("length");
+                e.message = Some("El motivo no puede exceder 500 caracteres".into());
+                errors.add("reason", e);
+            }
+            None | Some("") if self.status != STATUS_ACTIVE => {
+                let mut e = ValidationError::new("required");
+                e.message = Some("El motivo es obligatorio para este estado".into());
+                errors.add("reason", e);
+            }
+            _ => {}
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Respuesta del cambio de estado: el paciente y las citas canceladas por el cambio
+#[derive(Debug, Serialize)]
+pub struct PatientStatusChange {
+    #[serde(flatten)]
+    pub patient: Patient,
+    pub canceled_appointments: Vec<i32>,
+}
+
+/// Valida el documento contra la fecha de nacimiento y devuelve el número
+/// normalizado. El error se reporta en `identity_type` o `identity_number`.
+pub fn check_identity(identity_type: &str, number: &str, birthdate: NaiveDate) -> Result<String, ValidationErrors> {
+    validate_identity(identity_type, number, birthdate).map_err(|e| {
+        let field = if e.code == "identity_type" { "identity_type" } else { "identity_number" };
+        let mut errors = ValidationErrors::new();
+        errors.add(field, e);
+        errors
+    })
+}
+
+/// Filtros de GET /patients
+#[derive(Debug, Default, Deserialize)]
+pub struct PatientFilter {
     pub status: Option<String>,
+    pub gender: Option<String>,
 }
 
+/// Parámetros de GET /patients/search
+#[derive(Debug, Deserialize, Validate)]
+pub struct PatientSearchParams {
+    /// Nombre, fragmento de cédula o teléfono
+    #[validate(length(min = 2, max = 100, message = "La búsqueda debe tener entre 2 y 100 caracteres"))]
+    pub q: String,
+}
+
+/// Paciente encontrado con su puntaje de coincidencia (0 a 1)
+#[derive(Debug, Serialize, FromRow)]
+pub struct PatientSearchHit {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub patient: Patient,
+    pub score: f32,
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+use validator::Validate;
+use crate::helpers::validators::{validate_phone, validate_relationship};
+
+/// Contacto de un paciente: otro paciente (`id_related_patient`) o una persona
+/// externa. Un tutor legal que también es paciente con usuario puede ver el
+/// expediente y agendar citas por el paciente.
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct PatientContact {
+    pub id_contact: i32,
+    pub id_patient: i32,
+    pub id_related_patient: Option<i32>,
+    // Si el contacto es paciente, se completan con sus datos
+    pub contact_name: Option<String>,
+    pub contact_phone: Option<String>,
+    pub contact_email: Option<String>,
+    pub relationship: String, // madre, padre, tutor, conyuge, hijo, hermano, abuelo, otro
+    pub is_legal_guardian: bool,
+    pub is_emergency_contact: bool,
+    pub priority: i32,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreatePatientContact {
+    /// Requerido si no se envía contact_name
+    pub id_related_patient: Option<i32>,
+
+    #[validate(length(min = 2, max = 100, message = "El nombre debe tener entre 2 y 100 caracteres"))]
+    pub contact_name: Option<String>,
+    #[validate(custom = "validate_phone")]
+    pub contact_phone: Option<String>,
+    #[validate(email(message = "Email inválido"))]
+    pub contact_email: Option<String>,
+
+    #[validate(custom = "validate_relationship")]
+    pub relationship: String,
+    pub is_legal_guardian: Option<bool>,
+    pub is_emergency_contact: Option<bool>,
+
+    #[validate(range(min = 0, message = "La prioridad no puede ser negativa"))]
+    pub priority: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePatientContact {
+    #[validate(length(min = 2, max = 100, message = "El nombre debe tener entre 2 y 100 caracteres"))]
+    pub contact_name: Option<String>,
+    #[validate(custom = "validate_phone")]
+    pub contact_phone: Option<String>,
+    #[validate(email(message = "Email inválido"))]
+    pub contact_email: Option<String>,
+
+    #[validate(custom = "validate_relationship")]
+    pub relationship: Option<String>,
+    pub is_legal_guardian: Option<bool>,
+    pub is_emergency_contact: Option<bool>,
+
+    #[validate(range(min = 0, message = "La prioridad no puede ser negativa"))]
+    pub priority: Option<i32>,
+}
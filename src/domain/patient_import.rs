@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow};
+use chrono::NaiveDateTime;
+use std::fmt;
+
+// Pacientes que se guardan por transacción
+pub const IMPORT_BATCH_SIZE: usize = 500;
+
+// Tamaño máximo por defecto del archivo a importar (20 MB)
+pub const DEFAULT_MAX_IMPORT_SIZE: usize = 20 * 1024 * 1024;
+
+// Columnas obligatorias; el resto de columnas usa los nombres de CreatePatient
+pub const REQUIRED_COLUMNS: &[&str] = &["identity_number", "first_name", "first_lastname", "birthdate"];
+
+// Códigos de error propios de la importación (además de los del validator)
+pub const ERROR_PARSE: &str = "parse";
+pub const ERROR_DUPLICATE_IN_FILE: &str = "duplicate_in_file";
+pub const ERROR_DUPLICATE_EXISTING: &str = "duplicate_existing";
+pub const ERROR_DATABASE: &str = "database";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ImportFormat {
+    /// Por extensión del archivo y, si no la tiene, por Content-Type
+    pub fn detect(file_name: Option<&str>, content_type: &str) -> Option<Self> {
+        let extension = file_name
+            .and_then(|f| f.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase());
+        match (extension.as_deref(), content_type) {
+            (Some("csv"), _) | (None, "text/csv") => Some(ImportFormat::Csv),
+            (Some("xlsx"), _)
+            | (None, "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet") => Some(ImportFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+/// Parámetros de POST /patients/import
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportParams {
+    /// Solo valida y genera el reporte, sin guardar pacientes
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// Columnas del reporte de errores en CSV (mismos campos que ImportRowError)
+pub const ERROR_REPORT_COLUMNS: &[&str] = &["row", "identity_number", "field", "code", "message"];
+
+/// Error de una fila. `row` es el número de fila en la hoja (la 1 es el encabezado)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowError {
+    pub row: i32,
+    pub identity_number: Option<String>,
+    pub field: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PatientImport {
+    pub id_import: i32,
+    pub file_name: Option<String>,
+    pub dry_run: bool,
+    pub total_rows: i32,
+    /// Filas sin errores de validación ni duplicados
+    pub valid_rows: i32,
+    pub imported_rows: i32,
+    pub failed_rows: i32,
+    pub errors: Json<Vec<ImportRowError>>,
+    pub performed_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct CreatePatientImport {
+    pub file_name: Option<String>,
+    pub dry_run: bool,
+    pub total_rows: i32,
+    pub valid_rows: i32,
+    pub imported_rows: i32,
+    pub failed_rows: i32,
+    pub errors: Vec<ImportRowError>,
+    pub performed_by: Option<i32>,
+}
+
+/// El archivo no se pudo leer (formato dañado, hoja vacía, faltan columnas): se responde como 400
+#[derive(Debug)]
+pub struct InvalidImportFile(pub String);
+
+impl fmt::Display for InvalidImportFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidImportFile {}
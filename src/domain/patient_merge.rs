@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use validator::Validate;
+
+use crate::domain::patient::Patient;
+
+/// Tablas cuyos registros pasan al paciente que se conserva, con su clave primaria.
+/// patient_contacts no está aquí: tiene al paciente en dos columnas y se fusiona aparte.
+pub const MERGED_TABLES: &[(&str, &str)] = &[
+    ("medical_appointments", "id_appointment"),
+    ("appointment_series", "id_series"),
+    ("medical_history", "id_history"),
+    ("lab_results", "id_result"),
+    ("medical_documents", "id_document"),
+    ("virtual_turns", "id_turn"),
+    ("waitlist_entries", "id_entry"),
+    ("waitlist_offers", "id_offer"),
+];
+
+/// Claves de `MergeRecord.moved` para patient_contacts: contactos del duplicado,
+/// contactos donde el duplicado era el familiar o tutor, y vínculos que se
+/// eliminaron por quedar repetidos o apuntando al mismo paciente
+pub const CONTACTS_MOVED: &str = "patient_contacts";
+pub const CONTACTS_RELATED_MOVED: &str = "patient_contacts.id_related_patient";
+pub const CONTACTS_REMOVED: &str = "patient_contacts.deleted";
+
+/// Cuerpo de POST /patients/{id}/merge: `{id}` es el expediente que se conserva
+#[derive(Debug, Deserialize, Validate)]
+pub struct MergePatients {
+    #[validate(range(min = 1, message = "duplicate_id inválido"))]
+    pub duplicate_id: i32,
+}
+
+/// Lo que se guarda en audit_logs.old_data para poder revertir la fusión:
+/// los ids exactos que se movieron en cada tabla
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MergeRecord {
+    pub id_survivor: i32,
+    pub id_duplicate: i32,
+    pub moved: BTreeMap<String, Vec<i32>>,
+}
+
+/// Resultado de una fusión o de su reversión
+#[derive(Debug, Serialize, Clone)]
+pub struct PatientMerge {
+    /// Entrada de audit_logs de la fusión; se usa para revertirla
+    pub id_audit: i32,
+    pub action: String,
+    #[serde(flatten)]
+    pub record: MergeRecord,
+    pub performed_at: Option<NaiveDateTime>,
+}
+
+/// Posible duplicado de un paciente. `score` (0 a 1) pondera la similitud del
+/// nombre, la fecha de nacimiento, el teléfono y la cédula.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DuplicateCandidate {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub patient: Patient,
+    pub score: f32,
+    pub name_similarity: f32,
+    pub same_birthdate: bool,
+    pub same_phone: bool,
+    pub identity_similarity: f32,
+}
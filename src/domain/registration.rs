@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::domain::patient::{CreatePatient, Patient};
+
+// Medio por el que se envía el código de verificación
+pub const CHANNEL_PHONE: &str = "phone";
+pub const CHANNEL_EMAIL: &str = "email";
+
+/// Vigencia del código de verificación
+pub const CODE_TTL_MINUTES: i64 = 15;
+/// Intentos permitidos antes de tener que registrarse de nuevo
+pub const MAX_CODE_ATTEMPTS: i32 = 5;
+
+/// Cuerpo de POST /auth/register: los datos del paciente (mismas reglas que
+/// `CreatePatient`), la contraseña y el medio para verificar el contacto
+#[derive(Debug, Deserialize)]
+pub struct SelfRegistration {
+    #[serde(flatten)]
+    pub patient: CreatePatient,
+    pub password: String,
+    pub channel: String,
+}
+
+impl Validate for SelfRegistration {
+    /// Valida `CreatePatient` y agrega los errores de los campos propios del registro
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.patient.validate().err().unwrap_or_default();
+
+        if self.password.chars().count() < 8 {
+            let mut e = ValidationError::new("length");
+            e.message = Some("La contraseña debe tener al menos 8 caracteres".into());
+            errors.add("password", e);
+        }
+
+        let destination = match self.channel.as_str() {
+            CHANNEL_PHONE => Some(("phone", self.patient.phone.is_some())),
+            CHANNEL_EMAIL => Some(("email", self.patient.email.is_some())),
+            _ => None,
+        };
+        match destination {
+            None => {
+                let mut e = ValidationError::new("channel");
+                e.message = Some("El medio de verificación debe ser phone o email".into());
+                errors.add("channel", e);
+            }
+            Some((field, false)) => {
+                let mut e = ValidationError::new("required");
+                e.message = Some("Requerido para enviar el código de verificación".into());
+                errors.add(field, e);
+            }
+            Some(_) => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl SelfRegistration {
+    /// Teléfono o correo al que se envía el código
+    pub fn destination(&self) -> Option<&str> {
+        match self.channel.as_str() {
+            CHANNEL_PHONE => self.patient.phone.as_deref(),
+            CHANNEL_EMAIL => self.patient.email.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// La información clínica, la prioridad y el estado los registra el personal
+    pub fn has_staff_fields(&self) -> bool {
+        let p = &self.patient;
+        p.allergies.is_some()
+            || p.current_medications.is_some()
+            || p.medical_background.is_some()
+            || p.priority.is_some()
+            || p.status.is_some()
+    }
+}
+
+/// Registro pendiente de verificar el código
+#[derive(Debug, FromRow)]
+pub struct PatientRegistration {
+    pub id_registration: Uuid,
+    pub payload: sqlx::types::Json<CreatePatient>,
+    pub password_hash: String,
+    pub code_hash: String,
+    pub attempts: i32,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct CreatePatientRegistration {
+    pub payload: CreatePatient,
+    pub password_hash: String,
+    pub channel: String,
+    pub destination: String,
+    pub code_hash: String,
+}
+
+/// Respuesta de POST /auth/register; el código nunca se devuelve
+#[derive(Debug, Serialize)]
+pub struct PendingRegistration {
+    pub id_registration: Uuid,
+    pub channel: String,
+    /// Destino parcialmente oculto, ej. "+505****1234"
+    pub destination: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyRegistration {
+    #[validate(length(equal = 6, message = "El código tiene 6 dígitos"))]
+    pub code: String,
+}
+
+/// Resultado de verificar el código
+#[derive(Debug)]
+pub enum Verification {
+    /// Cuenta creada con el paciente en `pending_verification`
+    Verified(Box<Patient>),
+    InvalidCode { attempts_left: i32 },
+    /// Venció o se agotaron los intentos: hay que registrarse de nuevo
+    Expired,
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::NaiveDateTime;
+use std::env;
+
+// Años que se conserva un registro administrativo eliminado antes de purgarlo
+pub const DEFAULT_RETENTION_YEARS: i32 = 5;
+
+/// Los registros clínicos tienen su propio plazo legal de conservación
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordClass {
+    Administrative,
+    Clinical,
+}
+
+/// Entidades con papelera (nombre en la URL), de dependientes a padres:
+/// la purga borra primero los hijos para que los padres queden libres
+pub const TRASH_ENTITIES: &[(&str, RecordClass)] = &[
+    ("patient_contacts", RecordClass::Administrative),
+    ("appointments", RecordClass::Clinical),
+    ("medical_history", RecordClass::Clinical),
+    ("lab_results", RecordClass::Clinical),
+    ("doctors", RecordClass::Administrative),
+    ("patients", RecordClass::Clinical),
+    ("services", RecordClass::Administrative),
+    ("specialities", RecordClass::Administrative),
+    ("users", RecordClass::Administrative),
+];
+
+/// Plazos de purga en años desde `deleted_at`.
+/// Sin `CLINICAL_RETENTION_YEARS` los registros clínicos no se purgan nunca.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub years: i32,
+    pub clinical_years: Option<i32>,
+}
+
+impl RetentionPolicy {
+    /// Lee TRASH_RETENTION_YEARS y CLINICAL_RETENTION_YEARS
+    pub fn from_env() -> Self {
+        let years = |name: &str| env::var(name).ok().and_then(|v| v.parse::<i32>().ok()).filter(|y| *y > 0);
+        Self {
+            years: years("TRASH_RETENTION_YEARS").unwrap_or(DEFAULT_RETENTION_YEARS),
+            clinical_years: years("CLINICAL_RETENTION_YEARS"),
+        }
+    }
+
+    pub fn years_for(&self, class: RecordClass) -> Option<i32> {
+        match class {
+            RecordClass::Administrative => Some(self.years),
+            RecordClass::Clinical => self.clinical_years,
+        }
+    }
+}
+
+/// Registro en la papelera; `label` identifica el registro sin abrirlo
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashItem {
+    pub id: i32,
+    pub label: Option<String>,
+    pub deleted_at: NaiveDateTime,
+}
+
+/// Parámetros de POST /trash/purge
+#[derive(Debug, Default, Deserialize)]
+pub struct PurgeParams {
+    /// Solo informa qué se purgaría
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Resultado de la purga de una entidad. `skipped` son registros vencidos
+/// que otros registros todavía referencian y se quedan en la papelera.
+#[derive(Debug, Serialize)]
+pub struct PurgeResult {
+    pub entity: String,
+    /// None: la entidad no se purga con la política actual
+    pub retention_years: Option<i32>,
+    pub purged: Vec<i32>,
+    pub skipped: Vec<i32>,
+}
@@ -58,3 +58,8 @@ pub struct UpdateUser {
     pub role: Option<String>,
 }
 
+/// Filtros de GET /users
+#[derive(Debug, Default, Deserialize)]
+pub struct UserFilter {
+    pub role: Option<String>,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct VirtualTurn {
+    pub id_turn: i32,
+    pub id_patient: Option<i32>,
+    pub id_service: i32,
+    pub id_area: Option<i32>,
+    pub turn_number: i32,
+    pub priority: Option<i32>,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub called_at: Option<NaiveDateTime>,
+    pub started_at: Option<NaiveDateTime>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub turn_date: NaiveDate,
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::env;
+use validator::{Validate, ValidationError, ValidationErrors};
+use crate::domain::appointment::Appointment;
+
+// Estados de una entrada de la lista de espera
+pub const ENTRY_WAITING: &str = "waiting";
+pub const ENTRY_OFFERED: &str = "offered";
+pub const ENTRY_BOOKED: &str = "booked";
+pub const ENTRY_CANCELED: &str = "canceled";
+
+// Estados de una oferta
+pub const OFFER_PENDING: &str = "pending";
+pub const OFFER_ACCEPTED: &str = "accepted";
+pub const OFFER_DECLINED: &str = "declined";
+pub const OFFER_EXPIRED: &str = "expired";
+
+pub const DEFAULT_HOLD_MINUTES: i64 = 30;
+
+/// Minutos que un turno ofrecido queda reservado esperando respuesta.
+/// Se configura con WAITLIST_HOLD_MINUTES.
+#[derive(Debug, Clone, Copy)]
+pub struct WaitlistPolicy {
+    pub hold_minutes: i64,
+}
+
+impl WaitlistPolicy {
+    pub fn from_env() -> Self {
+        let hold_minutes = env::var("WAITLIST_HOLD_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(DEFAULT_HOLD_MINUTES);
+        Self { hold_minutes }
+    }
+}
+
+/// Paciente que espera un turno con un doctor, una especialidad o un servicio
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct WaitlistEntry {
+    pub id_entry: i32,
+    pub id_patient: i32,
+    pub id_doctor: Option<i32>,
+    pub id_speciality: Option<i32>,
+    pub id_service: Option<i32>,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    /// Sin horas le sirve cualquier hora del día
+    pub time_from: Option<NaiveTime>,
+    pub time_to: Option<NaiveTime>,
+    pub notes: Option<String>,
+    pub status: String,
+    pub created_by: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWaitlistEntry {
+    pub id_patient: i32,
+    pub id_doctor: Option<i32>,
+    pub id_speciality: Option<i32>,
+    pub id_service: Option<i32>,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub time_from: Option<NaiveTime>,
+    pub time_to: Option<NaiveTime>,
+    #[validate(length(max = 500, message = "Las notas no pueden exceder 500 caracteres"))]
+    pub notes: Option<String>,
+}
+
+/// Filtros de GET /waitlist
+#[derive(Debug, Default, Deserialize)]
+pub struct WaitlistFilter {
+    pub id_patient: Option<i32>,
+    pub id_doctor: Option<i32>,
+    pub id_speciality: Option<i32>,
+    pub id_service: Option<i32>,
+    pub status: Option<String>,
+}
+
+/// Turno liberado ofrecido a una entrada; nadie más lo puede tomar hasta `expires_at`
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct WaitlistOffer {
+    pub id_offer: i32,
+    pub id_entry: i32,
+    pub id_patient: i32,
+    pub id_doctor: i32,
+    pub id_area: i32,
+    pub id_service: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub id_canceled_appointment: Option<i32>,
+    pub status: String,
+    pub expires_at: NaiveDateTime,
+    pub id_appointment: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub responded_at: Option<NaiveDateTime>,
+}
+
+/// Turno que se ofrece: el de una cita cancelada, o el de una oferta
+/// rechazada o vencida que pasa a la siguiente persona
+#[derive(Debug, Clone)]
+pub struct WaitlistSlot {
+    pub id_doctor: i32,
+    pub id_area: i32,
+    pub id_service: i32,
+    pub appointment_datetime: NaiveDateTime,
+    pub duration_minutes: i32,
+    pub building: Option<String>,
+    pub room: Option<String>,
+    pub id_canceled_appointment: Option<i32>,
+}
+
+impl From<&Appointment> for WaitlistSlot {
+    fn from(a: &Appointment) -> Self {
+        Self {
+            id_doctor: a.id_doctor,
+            id_area: a.id_area,
+            id_service: a.id_service,
+            appointment_datetime: a.appointment_datetime,
+            duration_minutes: a.duration_minutes,
+            building: a.building.clone(),
+            room: a.room.clone(),
+            id_canceled_appointment: Some(a.id_appointment),
+        }
+    }
+}
+
+impl From<&WaitlistOffer> for WaitlistSlot {
+    fn from(o: &WaitlistOffer) -> Self {
+        Self {
+            id_doctor: o.id_doctor,
+            id_area: o.id_area,
+            id_service: o.id_service,
+            appointment_datetime: o.appointment_datetime,
+            duration_minutes: o.duration_minutes,
+            building: o.building.clone(),
+            room: o.room.clone(),
+            id_canceled_appointment: o.id_canceled_appointment,
+        }
+    }
+}
+
+/// Respuesta al aceptar una oferta
+#[derive(Debug, Serialize)]
+pub struct AcceptedOffer {
+    pub offer: WaitlistOffer,
+    pub appointment: Appointment,
+}
+
+/// Resultado de una acción sobre la lista pedida por un usuario
+#[derive(Debug)]
+pub enum WaitlistResult<T> {
+    Done(T),
+    /// El usuario no actúa por ese paciente
+    Forbidden(&'static str),
+}
+
+fn entry_error(field: &'static str, message: &'static str) -> ValidationErrors {
+    let mut e = ValidationError::new("range");
+    e.message = Some(message.into());
+    let mut errors = ValidationErrors::new();
+    errors.add(field, e);
+    errors
+}
+
+/// Reglas que cruzan campos de la entrada: al menos un criterio, fechas en orden
+/// y no vencidas, horas las dos o ninguna
+pub fn check_entry(data: &CreateWaitlistEntry, today: NaiveDate) -> Result<(), ValidationErrors> {
+    if data.id_doctor.is_none() && data.id_speciality.is_none() && data.id_service.is_none() {
+        return Err(entry_error("id_doctor", "Indique un doctor, una especialidad o un servicio"));
+    }
+    if data.date_from > data.date_to {
+        return Err(entry_error("date_to", "La fecha de fin no puede ser anterior a la de inicio"));
+    }
+    if data.date_to < today {
+        return Err(entry_error("date_to", "El rango de fechas ya pasó"));
+    }
+    match (data.time_from, data.time_to) {
+        (Some(from), Some(to)) if from >= to => {
+            Err(entry_error("time_to", "La hora de fin debe ser posterior a la de inicio"))
+        }
+        (Some(_), None) | (None, Some(_)) => {
+            Err(entry_error("time_to", "Indique hora de inicio y de fin, o ninguna para cualquier hora"))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,73 @@
+use crate::helpers::errors::AppError;
+use crate::helpers::jwt::validate_jwt;
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+
+/// Usuario autenticado extraído del JWT (header Authorization o cookie auth_token)
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id_user: i32,
+    pub role: String,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+
+    /// Admisión: registra y verifica la identidad de los pacientes
+    pub fn is_admisionist(&self) -> bool {
+        self.role == "admisionist"
+    }
+
+    pub fn is_doctor(&self) -> bool {
+        self.role == "doctor"
+    }
+
+    /// Personal del hospital: puede consultar expedientes de cualquier paciente
+    pub fn is_staff(&self) -> bool {
+        matches!(self.role.as_str(), "doctor" | "admisionist" | "admin")
+    }
+}
+
+fn token_from_parts(parts: &Parts) -> Option<String> {
+    // 1. Authorization: Bearer <token>
+    if let Some(value) = parts.headers.get(header::AUTHORIZATION) {
+        if let Some(token) = value.to_str().ok()?.strip_prefix("Bearer ") {
+            return Some(token.trim().to_string());
+        }
+    }
+
+    // 2. Cookie auth_token=<token>
+    let cookies = parts.headers.get(header::COOKIE)?.to_str().ok()?;
+    cookies
+        .split(';')
+        .filter_map(|c| c.trim().split_once('='))
+        .find(|(name, _)| *name == "auth_token")
+        .map(|(_, value)| value.to_string())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = token_from_parts(parts)
+            .ok_or_else(|| AppError::Unauthorized("Token no proporcionado".to_string()))?;
+
+        let data = validate_jwt(&token)
+            .map_err(|_| AppError::Unauthorized("Token inválido o expirado".to_string()))?;
+
+        let id_user = data
+            .claims
+            .sub
+            .parse::<i32>()
+            .map_err(|_| AppError::Unauthorized("Token inválido o expirado".to_string()))?;
+
+        Ok(AuthUser {
+            id_user,
+            role: data.claims.role,
+        })
+    }
+}
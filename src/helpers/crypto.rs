@@ -0,0 +1,152 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use std::env;
+
+// Prefijo de los valores de columna cifrados
+const FIELD_PREFIX: &str = "enc:";
+const NONCE_LEN: usize = 12;
+
+/// Clave de datos (DEK) de un registro, en claro y solo en memoria
+#[derive(Clone)]
+pub struct DataKey {
+    cipher: Aes256Gcm,
+}
+
+impl DataKey {
+    /// Cifra un campo. La AAD liga el texto cifrado a su columna.
+    pub fn encrypt_field(&self, field: &str, plaintext: &str) -> Result<String> {
+        let sealed = seal(&self.cipher, plaintext.as_bytes(), field.as_bytes())?;
+        Ok(format!("{}{}", FIELD_PREFIX, B64.encode(sealed)))
+    }
+
+    /// Descifra un campo. Los valores sin prefijo son datos anteriores al
+    /// cifrado y se devuelven tal cual.
+    pub fn decrypt_field(&self, field: &str, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(FIELD_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let sealed = B64.decode(encoded).context("Campo cifrado mal formado")?;
+        let plain = open(&self.cipher, &sealed, field.as_bytes())?;
+        Ok(String::from_utf8(plain)?)
+    }
+
+    pub fn encrypt_opt(&self, field: &str, value: Option<&str>) -> Result<Option<String>> {
+        value.map(|v| self.encrypt_field(field, v)).transpose()
+    }
+
+    pub fn decrypt_opt(&self, field: &str, value: Option<&str>) -> Result<Option<String>> {
+        value.map(|v| self.decrypt_field(field, v)).transpose()
+    }
+
+    pub(crate) fn cipher(&self) -> &Aes256Gcm {
+        &self.cipher
+    }
+}
+
+/// Llaves maestras (KEK) cargadas de la configuración.
+/// La primera es la activa: con ella se envuelven las DEK nuevas; las demás
+/// solo se usan para desenvolver DEK antiguas hasta que se roten.
+pub struct Keyring {
+    keys: Vec<(String, Aes256Gcm)>,
+}
+
+impl Keyring {
+    /// ENCRYPTION_KEYS="id_nueva:base64,id_anterior:base64" (llaves de 32 bytes)
+    pub fn from_env() -> Result<Self> {
+        let raw = env::var("ENCRYPTION_KEYS").context("ENCRYPTION_KEYS no está definida")?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut keys = Vec::new();
+        for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Llave maestra sin id: se espera id:base64"))?;
+            let bytes = B64.decode(encoded).context("Llave maestra no es base64")?;
+            if bytes.len() != 32 {
+                bail!("La llave maestra {} debe tener 32 bytes", id);
+            }
+            keys.push((id.to_string(), Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes))));
+        }
+        if keys.is_empty() {
+            bail!("ENCRYPTION_KEYS no contiene llaves");
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn active_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    fn key(&self, id: &str) -> Result<&Aes256Gcm> {
+        self.keys
+            .iter()
+            .find(|(kid, _)| kid == id)
+            .map(|(_, k)| k)
+            .ok_or_else(|| anyhow!("Llave maestra desconocida: {}", id))
+    }
+
+    /// Genera una DEK nueva y la devuelve junto a su forma envuelta ("id:base64")
+    pub fn new_data_key(&self) -> Result<(DataKey, String)> {
+        let raw = Aes256Gcm::generate_key(OsRng);
+        let wrapped = self.wrap(&raw)?;
+        Ok((DataKey { cipher: Aes256Gcm::new(&raw) }, wrapped))
+    }
+
+    /// Desenvuelve una DEK guardada con cualquiera de las llaves maestras
+    pub fn unwrap_data_key(&self, wrapped: &str) -> Result<DataKey> {
+        let raw = self.unwrap_raw(wrapped)?;
+        Ok(DataKey { cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&raw)) })
+    }
+
+    /// true si la DEK está envuelta con una llave que no es la activa
+    pub fn needs_rewrap(&self, wrapped: &str) -> bool {
+        wrapped.split_once(':').map(|(id, _)| id) != Some(self.active_id())
+    }
+
+    /// Vuelve a envolver la DEK con la llave activa, sin tocar los datos
+    pub fn rewrap(&self, wrapped: &str) -> Result<String> {
+        let raw = self.unwrap_raw(wrapped)?;
+        self.wrap(Key::<Aes256Gcm>::from_slice(&raw))
+    }
+
+    fn wrap(&self, raw: &Key<Aes256Gcm>) -> Result<String> {
+        let (id, kek) = &self.keys[0];
+        let sealed = seal(kek, raw.as_slice(), id.as_bytes())?;
+        Ok(format!("{}:{}", id, B64.encode(sealed)))
+    }
+
+    fn unwrap_raw(&self, wrapped: &str) -> Result<Vec<u8>> {
+        let (id, encoded) = wrapped
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Llave de datos mal formada"))?;
+        let sealed = B64.decode(encoded).context("Llave de datos mal formada")?;
+        open(self.key(id)?, &sealed, id.as_bytes())
+    }
+}
+
+/// nonce || texto cifrado
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| anyhow!("Error al cifrar"))?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("Texto cifrado demasiado corto");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| anyhow!("Error al descifrar: llave incorrecta o datos alterados"))
+}
@@ -0,0 +1,345 @@
+use axum::{
+    extract::{
+        multipart::MultipartError,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts, OptionalFromRequest, Request,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::fmt;
+use crate::domain::pagination::InvalidListQuery;
+use crate::domain::patient_import::InvalidImportFile;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+/// Error de la API. Se responde como application/problem+json (RFC 7807)
+/// con un código estable en `code` para que el cliente no dependa del texto.
+#[derive(Debug)]
+pub enum AppError {
+    /// 400: el cuerpo no pasó las validaciones, con el detalle por campo
+    Validation(ValidationErrors),
+    /// 400: petición mal formada (JSON inválido, parámetros de ruta, multipart, archivo a importar)
+    BadRequest(String),
+    /// 401: falta el token o no es válido
+    Unauthorized(String),
+    /// 403: autenticado pero sin permiso
+    Forbidden(String),
+    /// 404: el recurso no existe (o no es visible para el usuario)
+    NotFound(String),
+    /// 409: violación de unicidad (23505)
+    Conflict(String),
+    /// 409: la operación no aplica al estado actual del registro
+    InvalidState(String),
+    /// 409: el horario se cruza con otro registro (23P01), que se identifica
+    Overlap { detail: String, conflicting_id: Option<i32> },
+    /// 422: referencia a un registro inexistente o en uso (23503)
+    InvalidReference(String),
+    /// 413: el archivo excede el tamaño permitido
+    PayloadTooLarge(String),
+    /// 415: tipo de archivo no permitido
+    UnsupportedMediaType(String),
+    /// 500: se registra en el log y no se expone al cliente
+    Internal(anyhow::Error),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+#[derive(Serialize)]
+struct Problem {
+    #[serde(rename = "type")]
+    kind: String,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+    /// Registro con el que se cruza, en los errores OVERLAP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflicting_id: Option<i32>,
+}
+
+/// Error de validación de un campo: `field` usa notación con puntos para
+/// estructuras anidadas y corchetes para listas (ej. `contacts[0].phone`)
+#[derive(Serialize)]
+struct FieldError {
+    field: String,
+    code: String,
+    message: String,
+}
+
+impl AppError {
+    fn parts(&self) -> (StatusCode, &'static str, &'static str) {
+        match self {
+            AppError::Validation(_) => (StatusCode::BAD_REQUEST, "VALIDATION_ERROR", "Error de validación"),
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", "Petición inválida"),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "AUTH_ERROR", "No autenticado"),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "FORBIDDEN", "Acceso denegado"),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND", "Recurso no encontrado"),
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "DUPLICATE_ENTRY", "Registro duplicado"),
+            AppError::InvalidState(_) => (StatusCode::CONFLICT, "INVALID_STATE", "Estado inválido"),
+            AppError::Overlap { .. } => (StatusCode::CONFLICT, "OVERLAP", "Horario ocupado"),
+            AppError::InvalidReference(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INVALID_REFERENCE",
+                "Referencia inválida",
+            ),
+            AppError::PayloadTooLarge(_) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "PAYLOAD_TOO_LARGE",
+                "Archivo demasiado grande",
+            ),
+            AppError::UnsupportedMediaType(_) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "UNSUPPORTED_MEDIA_TYPE",
+                "Tipo de archivo no permitido",
+            ),
+            AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", "Error interno"),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, code, title) = self.parts();
+
+        let mut conflicting_id = None;
+        let (detail, errors) = match self {
+            AppError::Validation(errors) => {
+                let mut fields = Vec::new();
+                flatten_validation("", &errors, &mut fields);
+                fields.sort_by(|a, b| a.field.cmp(&b.field));
+                ("Uno o más campos no son válidos".to_string(), fields)
+            }
+            AppError::Overlap { detail, conflicting_id: id } => {
+                conflicting_id = id;
+                (detail, Vec::new())
+            }
+            AppError::Internal(e) => {
+                eprintln!("Error interno: {:?}", e);
+                ("Ocurrió un error inesperado".to_string(), Vec::new())
+            }
+            AppError::BadRequest(d)
+            | AppError::Unauthorized(d)
+            | AppError::Forbidden(d)
+            | AppError::NotFound(d)
+            | AppError::Conflict(d)
+            | AppError::InvalidState(d)
+            | AppError::InvalidReference(d)
+            | AppError::PayloadTooLarge(d)
+            | AppError::UnsupportedMediaType(d) => (d, Vec::new()),
+        };
+
+        let problem = Problem {
+            kind: format!("urn:nexo:error:{}", code.to_lowercase().replace('_', "-")),
+            title,
+            status: status.as_u16(),
+            detail,
+            code,
+            errors,
+            conflicting_id,
+        };
+
+        let body = serde_json::to_vec(&problem).unwrap_or_default();
+        (status, [(header::CONTENT_TYPE, "application/problem+json")], body).into_response()
+    }
+}
+
+fn flatten_validation(prefix: &str, errors: &ValidationErrors, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(list) => {
+                for e in list {
+                    out.push(FieldError {
+                        field: path.clone(),
+                        code: e.code.to_string(),
+                        message: e
+                            .message
+                            .as_ref()
+                            .map(|m| m.to_string())
+                            .unwrap_or_else(|| "Valor inválido".to_string()),
+                    });
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => flatten_validation(&path, nested, out),
+            ValidationErrorsKind::List(items) => {
+                for (i, nested) in items {
+                    flatten_validation(&format!("{}[{}]", path, i), nested, out);
+                }
+            }
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(errors)
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    /// Traduce los errores de la base de datos que el cliente puede corregir.
+    /// Si el repositorio agregó contexto (ej. "El usuario ya existe") se usa
+    /// como detalle; si no, un mensaje genérico.
+    fn from(err: anyhow::Error) -> Self {
+        if let Some(e) = err.downcast_ref::<InvalidListQuery>() {
+            return AppError::BadRequest(e.to_string());
+        }
+        if let Some(e) = err.downcast_ref::<InvalidImportFile>() {
+            return AppError::BadRequest(e.to_string());
+        }
+        if let Some(e) = err.downcast_ref::<InvalidState>() {
+            return AppError::InvalidState(e.0.clone());
+        }
+        if let Some(e) = err.downcast_ref::<Overlap>() {
+            return AppError::Overlap { detail: e.detail.clone(), conflicting_id: e.conflicting_id };
+        }
+        // Validaciones que requieren datos guardados (ej. cédula contra la fecha de nacimiento)
+        let err = match err.downcast::<ValidationErrors>() {
+            Ok(errors) => return AppError::Validation(errors),
+            Err(err) => err,
+        };
+
+        let sqlx_err = err.chain().find_map(|e| e.downcast_ref::<sqlx::Error>());
+        let (not_found, db_code, context) = match sqlx_err {
+            Some(sqlx_err) => {
+                // El mensaje externo difiere del de sqlx solo si hay contexto
+                let context = (err.to_string() != sqlx_err.to_string()).then(|| err.to_string());
+                match sqlx_err {
+                    sqlx::Error::RowNotFound => (true, None, context),
+                    sqlx::Error::Database(db) => (false, db.code().map(|c| c.into_owned()), context),
+                    _ => (false, None, context),
+                }
+            }
+            None => return AppError::Internal(err),
+        };
+
+        if not_found {
+            return AppError::NotFound(context.unwrap_or_else(|| "Recurso no encontrado".to_string()));
+        }
+        match db_code.as_deref() {
+            Some("23505") => AppError::Conflict(
+                context.unwrap_or_else(|| "Ya existe un registro con esos datos".to_string()),
+            ),
+            Some("23P01") => AppError::Overlap {
+                detail: context.unwrap_or_else(|| "El horario se cruza con otro registro".to_string()),
+                conflicting_id: None,
+            },
+            Some("23503") => AppError::InvalidReference(
+                context.unwrap_or_else(|| "El registro referenciado no existe o está en uso".to_string()),
+            ),
+            _ => AppError::Internal(err),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        anyhow::Error::new(err).into()
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(rejection.body_text()),
+            _ => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<MultipartError> for AppError {
+    /// Se excedió el límite del body, se cortó la conexión o el multipart está mal formado
+    fn from(err: MultipartError) -> Self {
+        match err.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(err.body_text()),
+            _ => AppError::BadRequest(err.body_text()),
+        }
+    }
+}
+
+/// Error de servicio: la operación no aplica al estado actual (ej. confirmar
+/// un paciente que ya está activo). Se responde como 409 INVALID_STATE.
+#[derive(Debug)]
+pub struct InvalidState(pub String);
+
+impl fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidState {}
+
+/// Error de repositorio: una restricción de exclusión rechazó el registro porque
+/// se cruza con `conflicting_id`, si se conoce. Se responde como 409 OVERLAP.
+#[derive(Debug)]
+pub struct Overlap {
+    pub detail: String,
+    pub conflicting_id: Option<i32>,
+}
+
+impl fmt::Display for Overlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.detail)
+    }
+}
+
+impl std::error::Error for Overlap {}
+
+/// Agrega contexto a una violación de unicidad para que llegue como detalle
+/// del 409; cualquier otro error se propaga sin cambios.
+pub fn on_unique_violation(err: sqlx::Error, detail: &'static str) -> anyhow::Error {
+    match &err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => anyhow::Error::new(err).context(detail),
+        _ => err.into(),
+    }
+}
+
+/// `Json` cuyo rechazo (JSON mal formado, tipos incorrectos) responde como AppError
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct AppJson<T>(pub T);
+
+/// Cuerpo opcional: sin `Content-Type` de JSON es None; uno mal formado se
+/// rechaza igual que con `AppJson`
+impl<T, S> OptionalFromRequest<S> for AppJson<T>
+where
+    axum::Json<T>: OptionalFromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let json = <axum::Json<T> as OptionalFromRequest<S>>::from_request(req, state).await?;
+        Ok(json.map(|axum::Json(value)| AppJson(value)))
+    }
+}
+
+/// `Path` cuyo rechazo (ej. id no numérico) responde como AppError
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct AppPath<T>(pub T);
+
+/// `Query` cuyo rechazo (ej. fecha o número mal escrito) responde como AppError
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct AppQuery<T>(pub T);
@@ -1,3 +1,5 @@
 pub mod validators;
 pub mod jwt;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod auth;
+pub mod crypto;pub mod errors;
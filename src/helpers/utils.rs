@@ -1,11 +1,13 @@
 use rand::Rng;
 
 pub fn generate_patient_password(first_name: &str, first_lastname: &str, birth_year: i32) -> String {
-    let mut rng = rand::thread_rng();
-    let random_number: u8 = rng.gen_range(10..99); // dos dígitos aleatorios
+    let mut rng = rand::rng();
+    let random_number: u8 = rng.random_range(10..99); // dos dígitos aleatorios
+    // Primera letra por caracteres: `[0..1]` falla con nombres como "Ángel"
+    let initial: String = first_name.chars().take(1).flat_map(char::to_uppercase).collect();
     format!(
         "{}{}{}!{}",
-        &first_name[0..1].to_uppercase(),
+        initial,
         first_lastname,
         birth_year,
         random_number
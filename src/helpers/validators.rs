@@ -1,5 +1,10 @@
 use validator::{ValidationError};
 use regex::Regex;
+use chrono::{Datelike, Local, NaiveDate};
+use std::borrow::Cow;
+use crate::domain::appointment_series::RecurrenceRule;
+use crate::domain::doctor_schedule::{EXCEPTION_CONGRESS, EXCEPTION_OTHER, EXCEPTION_SICK_LEAVE, EXCEPTION_VACATION};
+use crate::domain::patient::{IDENTITY_BIRTH_CERTIFICATE, IDENTITY_CEDULA, PATIENT_STATUSES};
 
 
 pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
@@ -19,3 +24,153 @@ pub fn validate_role(role: &str) -> Result<(), ValidationError> {
         _ => Err(ValidationError::new("role")),
     }
 }
+
+fn identity_error(code: &'static str, message: &'static str) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(Cow::Borrowed(message));
+    error
+}
+
+// Letras de verificación de la cédula: sin I, Ñ, O ni Z
+const CEDULA_LETTERS: &[u8; 23] = b"ABCDEFGHJKLMNPQRSTUVWXY";
+
+/// Edad hasta la que se admite la partida de nacimiento en lugar de la cédula
+pub const MINOR_AGE: u32 = 18;
+
+/// Cédula nicaragüense `MMM-DDMMAA-NNNNL`: municipio de emisión, fecha de
+/// nacimiento, consecutivo y letra de verificación (los 13 dígitos módulo 23).
+/// La cédula no codifica el sexo, así que el género no se puede contrastar.
+#[derive(Debug)]
+pub struct Cedula {
+    pub municipality: String,
+    pub day: u32,
+    pub month: u32,
+    /// Año con dos dígitos; el siglo sale de `birthdate`
+    pub year: u32,
+    pub sequence: String,
+    pub letter: char,
+}
+
+impl Cedula {
+    /// Acepta la cédula con o sin guiones/espacios y con la letra en minúscula
+    pub fn parse(raw: &str) -> Result<Self, ValidationError> {
+        lazy_static::lazy_static! {
+            static ref CEDULA_REGEX: Regex = Regex::new(r"^([0-9]{3})([0-9]{2})([0-9]{2})([0-9]{2})([0-9]{4})([A-Z])$").unwrap();
+        }
+        let compact: String = raw
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' '))
+            .collect::<String>()
+            .to_uppercase();
+
+        let caps = CEDULA_REGEX.captures(&compact).ok_or_else(|| {
+            identity_error("cedula_format", "La cédula debe tener el formato 001-010190-0001N")
+        })?;
+        let number = |i: usize| caps[i].parse::<u32>().unwrap_or_default();
+
+        let cedula = Cedula {
+            municipality: caps[1].to_string(),
+            day: number(2),
+            month: number(3),
+            year: number(4),
+            sequence: caps[5].to_string(),
+            letter: caps[6].chars().next().unwrap_or_default(),
+        };
+
+        if cedula.municipality == "000" || !(1..=12).contains(&cedula.month) || !(1..=31).contains(&cedula.day) {
+            return Err(identity_error("cedula_format", "La cédula tiene un municipio o una fecha inválida"));
+        }
+        if cedula.letter != cedula.expected_letter() {
+            return Err(identity_error("cedula_letter", "La letra de verificación de la cédula no es correcta"));
+        }
+        Ok(cedula)
+    }
+
+    fn expected_letter(&self) -> char {
+        let digits = format!("{}{:02}{:02}{:02}{}", self.municipality, self.day, self.month, self.year, self.sequence);
+        let value: u64 = digits.parse().unwrap_or_default();
+        CEDULA_LETTERS[(value % 23) as usize] as char
+    }
+
+    /// Formato con el que se guarda: `001-010190-0001N`
+    pub fn normalized(&self) -> String {
+        format!(
+            "{}-{:02}{:02}{:02}-{}{}",
+            self.municipality, self.day, self.month, self.year, self.sequence, self.letter
+        )
+    }
+
+    pub fn matches_birthdate(&self, birthdate: NaiveDate) -> bool {
+        birthdate.day() == self.day
+            && birthdate.month() == self.month
+            && birthdate.year().rem_euclid(100) as u32 == self.year
+    }
+}
+
+/// Valida el documento según su tipo (`cedula` o `partida_nacimiento`) contra
+/// la fecha de nacimiento y devuelve el número normalizado para guardarlo.
+pub fn validate_identity(identity_type: &str, number: &str, birthdate: NaiveDate) -> Result<String, ValidationError> {
+    match identity_type {
+        IDENTITY_CEDULA => {
+            let cedula = Cedula::parse(number)?;
+            if !cedula.matches_birthdate(birthdate) {
+                return Err(identity_error(
+                    "cedula_birthdate",
+                    "La fecha de nacimiento de la cédula no coincide con birthdate",
+                ));
+            }
+            Ok(cedula.normalized())
+        }
+        IDENTITY_BIRTH_CERTIFICATE => {
+            lazy_static::lazy_static! {
+                static ref CERTIFICATE_REGEX: Regex = Regex::new(r"^[0-9A-Z][0-9A-Z/-]{2,19}$").unwrap();
+            }
+            let certificate = number.trim().to_uppercase();
+            if !CERTIFICATE_REGEX.is_match(&certificate) {
+                return Err(identity_error(
+                    "birth_certificate_format",
+                    "La partida de nacimiento debe tener entre 3 y 20 letras, números, '-' o '/'",
+                ));
+            }
+            let today = Local::now().date_naive();
+            if today.years_since(birthdate).unwrap_or(0) >= MINOR_AGE {
+                return Err(identity_error(
+                    "birth_certificate_adult",
+                    "La partida de nacimiento solo se admite para menores de edad; los adultos se registran con cédula",
+                ));
+            }
+            Ok(certificate)
+        }
+        _ => Err(identity_error("identity_type", "El tipo de documento debe ser cedula o partida_nacimiento")),
+    }
+}
+
+pub fn validate_relationship(relationship: &str) -> Result<(), ValidationError> {
+    match relationship {
+        "madre" | "padre" | "tutor" | "conyuge" | "hijo" | "hermano" | "abuelo" | "otro" => Ok(()),
+        _ => Err(ValidationError::new("relationship")),
+    }
+}
+
+pub fn validate_patient_status(status: &str) -> Result<(), ValidationError> {
+    if PATIENT_STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("status"))
+    }
+}
+
+pub fn validate_exception_type(exception_type: &str) -> Result<(), ValidationError> {
+    match exception_type {
+        EXCEPTION_VACATION | EXCEPTION_CONGRESS | EXCEPTION_SICK_LEAVE | EXCEPTION_OTHER => Ok(()),
+        _ => Err(ValidationError::new("exception_type")),
+    }
+}
+
+pub fn validate_rrule(rrule: &str) -> Result<(), ValidationError> {
+    rrule.parse::<RecurrenceRule>().map(|_| ()).map_err(|message| {
+        let mut error = ValidationError::new("rrule");
+        error.message = Some(Cow::Owned(message));
+        error
+    })
+}
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+
+use crate::domain::access_log::{AccessLog, AccessReportEntry, CreateAccessLog};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const REPORT: ListQuery = ListQuery {
+    columns: "a.id_access, a.id_user, u.username, u.role, a.resource, a.record_id, a.accessed_at",
+    from: "access_logs a JOIN users u ON u.id_user = a.id_user",
+    base_filter: "TRUE",
+    id_column: "a.id_access",
+    sort_fields: &[
+        SortField { name: "accessed_at", column: "a.accessed_at", sql_type: "timestamp" },
+    ],
+    default_sort: "-accessed_at",
+};
+
+#[async_trait]
+pub trait AccessLogRepository: Send + Sync + 'static {
+    async fn create(&self, data: CreateAccessLog) -> Result<AccessLog>;
+    /// Registra varias lecturas en un solo INSERT (ej. una agenda con varios pacientes)
+    async fn create_many(&self, data: &[CreateAccessLog]) -> Result<()>;
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<AccessReportEntry>>;
+}
+
+pub struct PgAccessLogRepository {
+    pool: PgPool,
+}
+
+impl PgAccessLogRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AccessLogRepository for PgAccessLogRepository {
+    async fn create(&self, data: CreateAccessLog) -> Result<AccessLog> {
+        let result = sqlx::query_as::<_, AccessLog>(
+            "INSERT INTO access_logs
+            (id_user, id_patient, resource, record_id)
+            VALUES ($1,$2,$3,$4)
+            RETURNING *"
+        )
+        .bind(data.id_user)
+        .bind(data.id_patient)
+        .bind(data.resource)
+        .bind(data.record_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_many(&self, data: &[CreateAccessLog]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let id_users: Vec<i32> = data.iter().map(|d| d.id_user).collect();
+        let id_patients: Vec<i32> = data.iter().map(|d| d.id_patient).collect();
+        let resources: Vec<&str> = data.iter().map(|d| d.resource).collect();
+        let record_ids: Vec<i32> = data.iter().map(|d| d.record_id).collect();
+        sqlx::query(
+            "INSERT INTO access_logs (id_user, id_patient, resource, record_id)
+             SELECT * FROM unnest($1::int[], $2::int[], $3::text[], $4::int[])",
+        )
+        .bind(&id_users)
+        .bind(&id_patients)
+        .bind(&resources)
+        .bind(&record_ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<AccessReportEntry>> {
+        let result = fetch_page(&self.pool, &REPORT, page, |q| {
+            q.push(" AND a.id_patient = ").push_bind(id_patient);
+        })
+        .await?;
+
+        Ok(result)
+    }
+}
@@ -1,15 +1,179 @@
-use crate::domain::appointment::{Appointment, CreateAppointment, UpdateAppointment};
+use crate::domain::appointment::{
+    Appointment, AppointmentFilter, AppointmentStatus, AppointmentTransition, CreateAppointment, RescheduleAppointment,
+    UpdateAppointment, DEFAULT_DURATION_MINUTES,
+};
+use crate::domain::appointment_series::{
+    AppointmentSeries, CreateAppointmentSeries, OccurrenceChange, OccurrenceConflict, SeriesOccurrence, SeriesResult,
+    UpdateSeriesOccurrences,
+};
+use crate::domain::appointment_reminder::AppointmentReminder;
+use crate::domain::audit_log::{AuditLog, ACTION_RESCHEDULE, ACTION_STATUS_CHANGE};
+use crate::helpers::errors::Overlap;
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::{PgPool};
+use chrono::{Duration, NaiveDateTime};
+use sqlx::{Connection, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+/// Horario, doctor, paciente y sala de una cita que se intenta guardar
+struct Booking<'a> {
+    id_appointment: Option<i32>,
+    id_doctor: i32,
+    id_patient: i32,
+    building: Option<&'a str>,
+    room: Option<&'a str>,
+    start: NaiveDateTime,
+    duration_minutes: i32,
+}
+
+const LIST: ListQuery = ListQuery {
+    columns: "*",
+    from: "medical_appointments",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_appointment",
+    sort_fields: &[
+        SortField { name: "id_appointment", column: "id_appointment", sql_type: "int" },
+        SortField { name: "appointment_datetime", column: "appointment_datetime", sql_type: "timestamp" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+        SortField { name: "reschedule_count", column: "reschedule_count", sql_type: "int" },
+    ],
+    default_sort: "appointment_datetime",
+};
+
+/// Registra en audit_logs el horario anterior y el nuevo de una cita reprogramada
+async fn log_reschedule(
+    tx: &mut Transaction<'_, Postgres>,
+    from: &Appointment,
+    to: &Appointment,
+    reason: &str,
+    performed_by: i32,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO audit_logs (table_name, record_id, action, old_data, new_data, performed_by)
+         VALUES ('medical_appointments', $1, $2,
+                 jsonb_build_object('appointment_datetime', $3::timestamp, 'duration_minutes', $4::int),
+                 jsonb_build_object('appointment_datetime', $5::timestamp, 'duration_minutes', $6::int,
+                                    'reason', $7::text),
+                 $8)",
+    )
+    .bind(from.id_appointment)
+    .bind(ACTION_RESCHEDULE)
+    .bind(from.appointment_datetime)
+    .bind(from.duration_minutes)
+    .bind(to.appointment_datetime)
+    .bind(to.duration_minutes)
+    .bind(reason)
+    .bind(performed_by)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Registra en audit_logs un cambio de estado con su motivo. Sin usuario si el
+/// paciente respondió desde el enlace de un recordatorio.
+async fn log_status_change(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i32,
+    from: AppointmentStatus,
+    transition: AppointmentTransition,
+    reason: Option<&str>,
+    performed_by: Option<i32>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO audit_logs (table_name, record_id, action, old_data, new_data, performed_by)
+         VALUES ('medical_appointments', $1, $2, jsonb_build_object('status', $3::text),
+                 jsonb_build_object('status', $4::text, 'transition', $5::text, 'reason', $6::text), $7)",
+    )
+    .bind(id)
+    .bind(ACTION_STATUS_CHANGE)
+    .bind(from.as_str())
+    .bind(transition.target().as_str())
+    .bind(transition.name())
+    .bind(reason)
+    .bind(performed_by)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
 
 #[async_trait]
 pub trait AppointmentRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<Appointment>>;
+    async fn get_all(&self, filter: &AppointmentFilter, page: &PageParams) -> Result<Page<Appointment>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Appointment>>;
-    async fn create(&self, data: CreateAppointment) -> Result<Appointment>;
+    /// `now` es la hora local de la aplicación y queda como `created_at`: los
+    /// recordatorios la comparan con la hora de la cita
+    async fn create(&self, data: CreateAppointment, now: NaiveDateTime) -> Result<Appointment>;
     async fn update(&self, id: i32, data: UpdateAppointment) -> Result<Option<Appointment>>;
-    async fn delete(&self, id: i32) -> Result<Option<Appointment>>;
+    /// Eliminación lógica si la cita sigue en `from`; None si no existe o cambió de estado
+    async fn delete(&self, id: i32, from: AppointmentStatus) -> Result<Option<Appointment>>;
+    /// El usuario es el propio paciente o su tutor legal
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool>;
+    /// Estado del paciente (`active`, `deceased`, ...); None si no existe
+    async fn get_patient_status(&self, id_patient: i32) -> Result<Option<String>>;
+    /// Usuario del doctor (None si el doctor no tiene usuario o no existe)
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<i32>>;
+    /// Aplica la transición si la cita sigue en `from` y la registra en audit_logs
+    /// con el motivo y el usuario. None si la cita ya cambió de estado.
+    async fn transition(
+        &self,
+        id: i32,
+        from: AppointmentStatus,
+        transition: AppointmentTransition,
+        reason: Option<&str>,
+        performed_by: Option<i32>,
+    ) -> Result<Option<Appointment>>;
+    /// Cambios de estado de la cita, del más antiguo al más reciente
+    async fn get_status_history(&self, id: i32) -> Result<Vec<AuditLog>>;
+    /// Mueve la cita si sigue en `from` (mismo horario y estado), suma uno a
+    /// `reschedule_count` y registra el horario anterior, el nuevo y el motivo en
+    /// audit_logs. None si la cita cambió entretanto.
+    async fn reschedule(
+        &self,
+        from: &Appointment,
+        data: &RescheduleAppointment,
+        performed_by: i32,
+    ) -> Result<Option<Appointment>>;
+    /// Reprogramaciones de la cita, de la más antigua a la más reciente
+    async fn get_reschedules(&self, id: i32) -> Result<Vec<AuditLog>>;
+    /// Canal y destino para avisar al paciente (correo si tiene, si no teléfono)
+    async fn get_patient_contact(&self, id_patient: i32) -> Result<Option<(String, String)>>;
+    /// Recordatorio enviado con ese token de enlace
+    async fn get_reminder(&self, token: Uuid) -> Result<Option<AppointmentReminder>>;
+    /// Crea la serie y sus citas en una transacción. Las citas que se cruzan con
+    /// otra se suman a `conflicts`; no se guarda nada si es `dry_run`, si no quedó
+    /// ninguna cita o si hubo conflictos y no se pidió `skip_conflicts`. `now`
+    /// queda como `created_at` de las citas, igual que en `create`.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_series(
+        &self,
+        data: &CreateAppointmentSeries,
+        rrule: &str,
+        occurrences: &[SeriesOccurrence],
+        conflicts: Vec<OccurrenceConflict>,
+        created_by: i32,
+        dry_run: bool,
+        now: NaiveDateTime,
+    ) -> Result<SeriesResult>;
+    async fn get_series(&self, id_series: i32) -> Result<Option<AppointmentSeries>>;
+    /// Citas de la serie en el orden de la regla
+    async fn get_series_appointments(&self, id_series: i32) -> Result<Vec<Appointment>>;
+    /// Aplica los cambios a todas las citas o a ninguna; las que cambian de horario
+    /// se reprograman (contador y audit_logs con `reason`). None si alguna cambió entretanto.
+    async fn update_occurrences(
+        &self,
+        changes: &[OccurrenceChange],
+        data: &UpdateSeriesOccurrences,
+        performed_by: i32,
+    ) -> Result<Option<Vec<Appointment>>>;
+    /// Cancela todas las citas o ninguna. None si alguna cambió de estado entretanto.
+    async fn cancel_occurrences(
+        &self,
+        appointments: &[Appointment],
+        reason: &str,
+        performed_by: i32,
+    ) -> Result<Option<Vec<Appointment>>>;
 }
 
 pub struct PgAppointmentRepository {
@@ -20,15 +184,86 @@ impl PgAppointmentRepository {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Si el error es de una restricción excl_appointments_*, busca la cita con la que
+    /// se cruza y lo devuelve como `Overlap`; cualquier otro error se propaga igual
+    async fn overlap_error(&self, err: sqlx::Error, booking: &Booking<'_>) -> anyhow::Error {
+        let constraint = match &err {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some("23P01") => db.constraint().map(str::to_owned),
+            _ => None,
+        };
+        let (condition, who) = match constraint.as_deref() {
+            Some("excl_appointments_doctor") => ("a.id_doctor = $4", "El doctor".to_string()),
+            Some("excl_appointments_patient") => ("a.id_patient = $4", "El paciente".to_string()),
+            Some("excl_appointments_room") => (
+                "a.building = $4 AND a.room = $5",
+                format!("La sala {} {}", booking.building.unwrap_or_default(), booking.room.unwrap_or_default()),
+            ),
+            _ => return err.into(),
+        };
+
+        let end = booking.start + Duration::minutes(i64::from(booking.duration_minutes));
+        let sql = format!(
+            "SELECT a.* FROM medical_appointments a
+             WHERE a.deleted_at IS NULL AND a.status NOT IN ('canceled', 'no_show')
+               AND a.id_appointment IS DISTINCT FROM $1
+               AND tsrange(a.appointment_datetime, a.appointment_datetime + a.duration_minutes * INTERVAL '1 minute')
+                   && tsrange($2, $3)
+               AND {condition}
+             ORDER BY a.appointment_datetime
+             LIMIT 1"
+        );
+        let query = sqlx::query_as::<_, Appointment>(&sql)
+            .bind(booking.id_appointment)
+            .bind(booking.start)
+            .bind(end);
+        let query = match constraint.as_deref() {
+            Some("excl_appointments_doctor") => query.bind(booking.id_doctor),
+            Some("excl_appointments_patient") => query.bind(booking.id_patient),
+            _ => query.bind(booking.building).bind(booking.room),
+        };
+
+        match query.fetch_optional(&self.pool).await {
+            Ok(Some(conflict)) => Overlap {
+                detail: format!(
+                    "{} ya tiene la cita {} de {} a {}",
+                    who,
+                    conflict.id_appointment,
+                    conflict.appointment_datetime.format("%Y-%m-%d %H:%M"),
+                    conflict.end().format("%H:%M"),
+                ),
+                conflicting_id: Some(conflict.id_appointment),
+            }
+            .into(),
+            // La otra cita ya no está (se canceló entre medio): queda el 409 genérico
+            _ => err.into(),
+        }
+    }
 }
 
 #[async_trait]
 impl AppointmentRepository for PgAppointmentRepository {
-    async fn get_all(&self) -> Result<Vec<Appointment>> {
-        let result: Vec<Appointment> = sqlx::query_as::<_, Appointment>(
-            "SELECT * FROM medical_appointments WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
+    async fn get_all(&self, filter: &AppointmentFilter, page: &PageParams) -> Result<Page<Appointment>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(id_patient) = filter.id_patient {
+                q.push(" AND id_patient = ").push_bind(id_patient);
+            }
+            if let Some(id_doctor) = filter.id_doctor {
+                q.push(" AND id_doctor = ").push_bind(id_doctor);
+            }
+            if let Some(status) = &filter.status {
+                q.push(" AND status = ").push_bind(status.as_str());
+            }
+            if let Some(date_from) = filter.date_from {
+                q.push(" AND appointment_datetime >= ").push_bind(date_from);
+            }
+            if let Some(date_to) = filter.date_to {
+                q.push(" AND appointment_datetime < ").push_bind(date_to).push(" + 1");
+            }
+            if let Some(min_reschedules) = filter.min_reschedules {
+                q.push(" AND reschedule_count >= ").push_bind(min_reschedules);
+            }
+        })
         .await?;
         Ok(result)
     }
@@ -43,11 +278,12 @@ impl AppointmentRepository for PgAppointmentRepository {
         Ok(result)
     }
 
-    async fn create(&self, data: CreateAppointment) -> Result<Appointment> {
-        let result = sqlx::query_as::<_, Appointment>(
+    async fn create(&self, data: CreateAppointment, now: NaiveDateTime) -> Result<Appointment> {
+        let duration_minutes = data.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES);
+        let inserted = sqlx::query_as::<_, Appointment>(
             "INSERT INTO medical_appointments
-            (id_patient, id_doctor, id_area, id_service, appointment_datetime, building, room, notes)
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+            (id_patient, id_doctor, id_area, id_service, appointment_datetime, duration_minutes, building, room, notes, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
             RETURNING *"
         )
         .bind(data.id_patient)
@@ -55,49 +291,466 @@ impl AppointmentRepository for PgAppointmentRepository {
         .bind(data.id_area)
         .bind(data.id_service)
         .bind(data.appointment_datetime)
-        .bind(data.building)
-        .bind(data.room)
-        .bind(data.notes)
+        .bind(duration_minutes)
+        .bind(data.building.as_deref())
+        .bind(data.room.as_deref())
+        .bind(data.notes.as_deref())
+        .bind(now)
         .fetch_one(&self.pool)
-        .await?;
+        .await;
 
-        Ok(result)
+        match inserted {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                let booking = Booking {
+                    id_appointment: None,
+                    id_doctor: data.id_doctor,
+                    id_patient: data.id_patient,
+                    building: data.building.as_deref(),
+                    room: data.room.as_deref(),
+                    start: data.appointment_datetime,
+                    duration_minutes,
+                };
+                Err(self.overlap_error(e, &booking).await)
+            }
+        }
     }
 
     async fn update(&self, id: i32, data: UpdateAppointment) -> Result<Option<Appointment>> {
         let result = sqlx::query_as::<_, Appointment>(
-            "UPDATE medical_appointments SET 
-                appointment_datetime = COALESCE($1, appointment_datetime),
-                building = COALESCE($2, building),
-                room = COALESCE($3, room),
-                notes = COALESCE($4, notes),
-                prescription = COALESCE($5, prescription),
-                status = COALESCE($6, status),
+            "UPDATE medical_appointments SET
+                building = COALESCE($1, building),
+                room = COALESCE($2, room),
+                notes = COALESCE($3, notes),
+                prescription = COALESCE($4, prescription),
                 updated_at = NOW()
-             WHERE id_appointment = $7 AND deleted_at IS NULL
+             WHERE id_appointment = $5 AND deleted_at IS NULL
              RETURNING *"
         )
-        .bind(data.appointment_datetime)
-        .bind(data.building)
-        .bind(data.room)
-        .bind(data.notes)
-        .bind(data.prescription)
-        .bind(data.status)
+        .bind(data.building.as_deref())
+        .bind(data.room.as_deref())
+        .bind(data.notes.as_deref())
+        .bind(data.prescription.as_deref())
         .bind(id)
         .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                // La cita con los cambios aplicados, para buscar con cuál se cruza
+                let Some(current) = self.get_by_id(id).await? else {
+                    return Err(e.into());
+                };
+                let booking = Booking {
+                    id_appointment: Some(id),
+                    id_doctor: current.id_doctor,
+                    id_patient: current.id_patient,
+                    building: data.building.as_deref().or(current.building.as_deref()),
+                    room: data.room.as_deref().or(current.room.as_deref()),
+                    start: current.appointment_datetime,
+                    duration_minutes: current.duration_minutes,
+                };
+                Err(self.overlap_error(e, &booking).await)
+            }
+        }
+    }
+
+    async fn delete(&self, id: i32, from: AppointmentStatus) -> Result<Option<Appointment>> {
+        let result = sqlx::query_as::<_, Appointment>(
+            "UPDATE medical_appointments SET deleted_at = NOW()
+             WHERE id_appointment = $1 AND deleted_at IS NULL AND status = $2
+             RETURNING *"
+        )
+        .bind(id)
+        .bind(from.as_str())
+        .fetch_optional(&self.pool)
         .await?;
 
         Ok(result)
     }
 
-    async fn delete(&self, id: i32) -> Result<Option<Appointment>> {
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar("SELECT can_act_for_patient($1, $2)")
+            .bind(id_user)
+            .bind(id_patient)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_patient_status(&self, id_patient: i32) -> Result<Option<String>> {
+        let result: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT status FROM patients WHERE id_patient = $1 AND deleted_at IS NULL",
+        )
+        .bind(id_patient)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result.flatten())
+    }
+
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<i32>> {
+        let result: Option<Option<i32>> = sqlx::query_scalar(
+            "SELECT id_user FROM doctors WHERE id_doctor = $1 AND deleted_at IS NULL",
+        )
+        .bind(id_doctor)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result.flatten())
+    }
+
+    async fn transition(
+        &self,
+        id: i32,
+        from: AppointmentStatus,
+        transition: AppointmentTransition,
+        reason: Option<&str>,
+        performed_by: Option<i32>,
+    ) -> Result<Option<Appointment>> {
+        let mut tx = self.pool.begin().await?;
+        let to = transition.target();
+
         let result = sqlx::query_as::<_, Appointment>(
-            "UPDATE medical_appointments SET deleted_at = NOW() WHERE id_appointment = $1 RETURNING *"
+            "UPDATE medical_appointments SET status = $2, updated_at = NOW()
+             WHERE id_appointment = $1 AND status = $3 AND deleted_at IS NULL
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(to.as_str())
+        .bind(from.as_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+        if result.is_none() {
+            return Ok(None);
+        }
+
+        log_status_change(&mut tx, id, from, transition, reason, performed_by).await?;
+
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    async fn get_status_history(&self, id: i32) -> Result<Vec<AuditLog>> {
+        let result = sqlx::query_as::<_, AuditLog>(
+            "SELECT * FROM audit_logs
+             WHERE table_name = 'medical_appointments' AND record_id = $1 AND action = $2
+             ORDER BY performed_at, id_audit",
+        )
+        .bind(id)
+        .bind(ACTION_STATUS_CHANGE)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn reschedule(
+        &self,
+        from: &Appointment,
+        data: &RescheduleAppointment,
+        performed_by: i32,
+    ) -> Result<Option<Appointment>> {
+        let duration_minutes = data.duration_minutes.unwrap_or(from.duration_minutes);
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query_as::<_, Appointment>(
+            "UPDATE medical_appointments SET
+                appointment_datetime = $2,
+                duration_minutes = $3,
+                reschedule_count = reschedule_count + 1,
+                updated_at = NOW()
+             WHERE id_appointment = $1 AND deleted_at IS NULL
+               AND appointment_datetime = $4 AND status = $5
+             RETURNING *",
+        )
+        .bind(from.id_appointment)
+        .bind(data.appointment_datetime)
+        .bind(duration_minutes)
+        .bind(from.appointment_datetime)
+        .bind(from.status.as_str())
+        .fetch_optional(&mut *tx)
+        .await;
+        let result = match updated {
+            Ok(Some(result)) => result,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                let booking = Booking {
+                    id_appointment: Some(from.id_appointment),
+                    id_doctor: from.id_doctor,
+                    id_patient: from.id_patient,
+                    building: from.building.as_deref(),
+                    room: from.room.as_deref(),
+                    start: data.appointment_datetime,
+                    duration_minutes,
+                };
+                return Err(self.overlap_error(e, &booking).await);
+            }
+        };
+
+        log_reschedule(&mut tx, from, &result, &data.reason, performed_by).await?;
+
+        tx.commit().await?;
+        Ok(Some(result))
+    }
+
+    async fn get_reschedules(&self, id: i32) -> Result<Vec<AuditLog>> {
+        let result = sqlx::query_as::<_, AuditLog>(
+            "SELECT * FROM audit_logs
+             WHERE table_name = 'medical_appointments' AND record_id = $1 AND action = $2
+             ORDER BY performed_at, id_audit",
         )
         .bind(id)
+        .bind(ACTION_RESCHEDULE)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_patient_contact(&self, id_patient: i32) -> Result<Option<(String, String)>> {
+        let result = sqlx::query_as::<_, (String, String)>(
+            "SELECT CASE WHEN email IS NOT NULL THEN 'email' ELSE 'phone' END, COALESCE(email, phone)
+             FROM patients
+             WHERE id_patient = $1 AND COALESCE(email, phone) IS NOT NULL",
+        )
+        .bind(id_patient)
         .fetch_optional(&self.pool)
         .await?;
+        Ok(result)
+    }
 
+    async fn get_reminder(&self, token: Uuid) -> Result<Option<AppointmentReminder>> {
+        let result = sqlx::query_as::<_, AppointmentReminder>("SELECT * FROM appointment_reminders WHERE token = $1")
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await?;
         Ok(result)
     }
+
+    async fn create_series(
+        &self,
+        data: &CreateAppointmentSeries,
+        rrule: &str,
+        occurrences: &[SeriesOccurrence],
+        mut conflicts: Vec<OccurrenceConflict>,
+        created_by: i32,
+        dry_run: bool,
+        now: NaiveDateTime,
+    ) -> Result<SeriesResult> {
+        let mut tx = self.pool.begin().await?;
+
+        let series = sqlx::query_as::<_, AppointmentSeries>(
+            "INSERT INTO appointment_series
+            (id_patient, id_doctor, id_area, id_service, rrule, dtstart, duration_minutes, building, room, notes, created_by)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+            RETURNING *",
+        )
+        .bind(data.id_patient)
+        .bind(data.id_doctor)
+        .bind(data.id_area)
+        .bind(data.id_service)
+        .bind(rrule)
+        .bind(data.appointment_datetime)
+        .bind(data.duration_minutes)
+        .bind(data.building.as_deref())
+        .bind(data.room.as_deref())
+        .bind(data.notes.as_deref())
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut appointments = Vec::with_capacity(occurrences.len());
+        for occurrence in occurrences {
+            // Cada cita en su savepoint: un cruce no aborta la transacción entera
+            let mut savepoint = tx.begin().await?;
+            let inserted = sqlx::query_as::<_, Appointment>(
+                "INSERT INTO medical_appointments
+                (id_patient, id_doctor, id_area, id_service, appointment_datetime, duration_minutes,
+                 building, room, notes, id_series, series_index, created_at)
+                VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+                RETURNING *",
+            )
+            .bind(data.id_patient)
+            .bind(data.id_doctor)
+            .bind(data.id_area)
+            .bind(data.id_service)
+            .bind(occurrence.appointment_datetime)
+            .bind(occurrence.duration_minutes)
+            .bind(occurrence.building.as_deref())
+            .bind(occurrence.room.as_deref())
+            .bind(data.notes.as_deref())
+            .bind(series.id_series)
+            .bind(occurrence.series_index)
+            .bind(now)
+            .fetch_one(&mut *savepoint)
+            .await;
+
+            match inserted {
+                Ok(appointment) => {
+                    savepoint.commit().await?;
+                    appointments.push(appointment);
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    let booking = Booking {
+                        id_appointment: None,
+                        id_doctor: data.id_doctor,
+                        id_patient: data.id_patient,
+                        building: occurrence.building.as_deref(),
+                        room: occurrence.room.as_deref(),
+                        start: occurrence.appointment_datetime,
+                        duration_minutes: occurrence.duration_minutes,
+                    };
+                    let err = self.overlap_error(e, &booking).await;
+                    let (detail, conflicting_id) = match err.downcast_ref::<Overlap>() {
+                        Some(overlap) => (overlap.detail.clone(), overlap.conflicting_id),
+                        None => match err.downcast_ref::<sqlx::Error>() {
+                            Some(sqlx::Error::Database(db)) if db.code().as_deref() == Some("23P01") => {
+                                ("El horario se cruza con otra cita".to_string(), None)
+                            }
+                            _ => return Err(err),
+                        },
+                    };
+                    conflicts.push(OccurrenceConflict {
+                        series_index: occurrence.series_index,
+                        appointment_datetime: occurrence.appointment_datetime,
+                        detail,
+                        conflicting_id,
+                    });
+                }
+            }
+        }
+        conflicts.sort_by_key(|c| c.series_index);
+
+        let keep = !dry_run && !appointments.is_empty() && (conflicts.is_empty() || data.skip_conflicts);
+        if keep {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(SeriesResult { series, appointments, conflicts })
+    }
+
+    async fn get_series(&self, id_series: i32) -> Result<Option<AppointmentSeries>> {
+        let result = sqlx::query_as::<_, AppointmentSeries>(
+            "SELECT * FROM appointment_series WHERE id_series = $1"
+        )
+        .bind(id_series)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_series_appointments(&self, id_series: i32) -> Result<Vec<Appointment>> {
+        let result = sqlx::query_as::<_, Appointment>(
+            "SELECT * FROM medical_appointments
+             WHERE id_series = $1 AND deleted_at IS NULL
+             ORDER BY series_index, id_appointment",
+        )
+        .bind(id_series)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn update_occurrences(
+        &self,
+        changes: &[OccurrenceChange],
+        data: &UpdateSeriesOccurrences,
+        performed_by: i32,
+    ) -> Result<Option<Vec<Appointment>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut result = Vec::with_capacity(changes.len());
+
+        for change in changes {
+            let from = &change.from;
+            let updated = sqlx::query_as::<_, Appointment>(
+                "UPDATE medical_appointments SET
+                    appointment_datetime = $2,
+                    duration_minutes = $3,
+                    reschedule_count = reschedule_count + $4,
+                    building = COALESCE($5, building),
+                    room = COALESCE($6, room),
+                    notes = COALESCE($7, notes),
+                    updated_at = NOW()
+                 WHERE id_appointment = $1 AND deleted_at IS NULL
+                   AND appointment_datetime = $8 AND status = $9
+                 RETURNING *",
+            )
+            .bind(from.id_appointment)
+            .bind(change.appointment_datetime)
+            .bind(change.duration_minutes)
+            .bind(i32::from(change.is_reschedule()))
+            .bind(data.building.as_deref())
+            .bind(data.room.as_deref())
+            .bind(data.notes.as_deref())
+            .bind(from.appointment_datetime)
+            .bind(from.status.as_str())
+            .fetch_optional(&mut *tx)
+            .await;
+            let appointment = match updated {
+                Ok(Some(appointment)) => appointment,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    let booking = Booking {
+                        id_appointment: Some(from.id_appointment),
+                        id_doctor: from.id_doctor,
+                        id_patient: from.id_patient,
+                        building: data.building.as_deref().or(from.building.as_deref()),
+                        room: data.room.as_deref().or(from.room.as_deref()),
+                        start: change.appointment_datetime,
+                        duration_minutes: change.duration_minutes,
+                    };
+                    return Err(self.overlap_error(e, &booking).await);
+                }
+            };
+
+            if change.is_reschedule() {
+                let reason = data.reason.as_deref().unwrap_or_default();
+                log_reschedule(&mut tx, from, &appointment, reason, performed_by).await?;
+            }
+            result.push(appointment);
+        }
+
+        tx.commit().await?;
+        Ok(Some(result))
+    }
+
+    async fn cancel_occurrences(
+        &self,
+        appointments: &[Appointment],
+        reason: &str,
+        performed_by: i32,
+    ) -> Result<Option<Vec<Appointment>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut result = Vec::with_capacity(appointments.len());
+
+        for from in appointments {
+            let updated = sqlx::query_as::<_, Appointment>(
+                "UPDATE medical_appointments SET status = $2, updated_at = NOW()
+                 WHERE id_appointment = $1 AND status = $3 AND deleted_at IS NULL
+                 RETURNING *",
+            )
+            .bind(from.id_appointment)
+            .bind(AppointmentStatus::Canceled.as_str())
+            .bind(from.status.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(appointment) = updated else {
+                return Ok(None);
+            };
+
+            log_status_change(
+                &mut tx,
+                from.id_appointment,
+                from.status,
+                AppointmentTransition::Cancel,
+                Some(reason),
+                Some(performed_by),
+            )
+            .await?;
+            result.push(appointment);
+        }
+
+        tx.commit().await?;
+        Ok(Some(result))
+    }
 }
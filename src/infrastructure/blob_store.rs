@@ -0,0 +1,97 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{stream, Stream, TryStreamExt};
+use std::{env, path::PathBuf, pin::Pin};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+pub type BlobStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Almacenamiento de archivos binarios (documentos médicos, imágenes).
+/// La implementación local guarda en disco; una implementación compatible
+/// con S3 debe respetar las mismas claves.
+#[async_trait]
+pub trait BlobStore: Send + Sync + 'static {
+    /// Guarda o reemplaza el archivo de forma atómica: quien lo lea ve la
+    /// versión anterior o la nueva completa, nunca una a medias.
+    async fn put(&self, key: &str, data: Bytes) -> Result<()>;
+    /// Igual que `put`, pero sin cargar el archivo completo en memoria
+    async fn put_stream(&self, key: &str, data: BlobStream) -> Result<()>;
+    async fn get(&self, key: &str) -> Result<BlobStream>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Usa STORAGE_PATH o ./storage por defecto
+    pub fn from_env() -> Self {
+        let root = env::var("STORAGE_PATH").unwrap_or_else(|_| "./storage".to_string());
+        Self::new(root)
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        // Las claves las genera el servidor, pero nunca deben salir de la raíz
+        if key.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            anyhow::bail!("Clave de almacenamiento inválida: {}", key);
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        self.put_stream(key, Box::pin(stream::once(async move { Ok(data) }))).await
+    }
+
+    async fn put_stream(&self, key: &str, mut data: BlobStream) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Se escribe a un temporal en la misma carpeta y se renombra encima:
+        // si el proceso cae a mitad (ej. durante la rotación de llaves) el
+        // archivo original queda intacto
+        let tmp = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            path.file_name().and_then(|n| n.to_str()).unwrap_or("blob"),
+            uuid::Uuid::new_v4()
+        ));
+        let written = async {
+            let mut file = tokio::fs::File::create(&tmp).await?;
+            while let Some(chunk) = data.try_next().await? {
+                file.write_all(&chunk).await?;
+            }
+            file.sync_all().await?;
+            tokio::fs::rename(&tmp, &path).await
+        }
+        .await;
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        written?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream> {
+        let file = tokio::fs::File::open(self.path_for(key)?).await?;
+        Ok(Box::pin(ReaderStream::new(file)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
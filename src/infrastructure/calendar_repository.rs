@@ -0,0 +1,134 @@
+use crate::domain::calendar::{CalendarEvent, CalendarFeed};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Citas del calendario; `{filter}` elige las del doctor o las del paciente ($1 = usuario)
+const EVENTS: &str = "SELECT a.id_appointment, a.appointment_datetime, a.duration_minutes, a.status, a.reschedule_count,
+        a.building, a.room, s.service_name,
+        CONCAT_WS(' ', d.first_name, d.first_lastname) AS doctor_name,
+        CONCAT_WS(' ', p.first_name, p.first_lastname) AS patient_name,
+        h.name AS hospital_name, h.address AS hospital_address,
+        a.created_at, a.updated_at
+    FROM medical_appointments a
+    JOIN doctors d ON d.id_doctor = a.id_doctor
+    JOIN patients p ON p.id_patient = a.id_patient
+    LEFT JOIN services s ON s.id_service = a.id_service
+    LEFT JOIN hospitals h ON h.id_hospital = COALESCE($3, (SELECT MIN(id_hospital) FROM hospitals))
+    WHERE a.deleted_at IS NULL AND a.appointment_datetime >= $2 AND {filter}
+    ORDER BY a.appointment_datetime, a.id_appointment";
+
+#[async_trait]
+pub trait CalendarRepository: Send + Sync + 'static {
+    async fn get_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>>;
+    /// Crea el token del usuario o lo reemplaza, invalidando la URL anterior
+    async fn rotate_feed(&self, id_user: i32, token: Uuid) -> Result<CalendarFeed>;
+    async fn delete_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>>;
+    /// Usuario y rol dueños del token, si el usuario sigue activo; registra el acceso
+    async fn open_feed(&self, token: Uuid) -> Result<Option<(i32, String)>>;
+    /// Citas que atiende el doctor del usuario desde `since`
+    async fn get_doctor_events(
+        &self,
+        id_user: i32,
+        since: NaiveDateTime,
+        hospital_id: Option<i32>,
+    ) -> Result<Vec<CalendarEvent>>;
+    /// Citas de los pacientes por los que actúa el usuario desde `since`
+    async fn get_patient_events(
+        &self,
+        id_user: i32,
+        since: NaiveDateTime,
+        hospital_id: Option<i32>,
+    ) -> Result<Vec<CalendarEvent>>;
+}
+
+pub struct PgCalendarRepository {
+    pool: PgPool,
+}
+
+impl PgCalendarRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn get_events(
+        &self,
+        filter: &str,
+        id_user: i32,
+        since: NaiveDateTime,
+        hospital_id: Option<i32>,
+    ) -> Result<Vec<CalendarEvent>> {
+        let result = sqlx::query_as::<_, CalendarEvent>(&EVENTS.replace("{filter}", filter))
+            .bind(id_user)
+            .bind(since)
+            .bind(hospital_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl CalendarRepository for PgCalendarRepository {
+    async fn get_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>> {
+        let result = sqlx::query_as::<_, CalendarFeed>("SELECT * FROM calendar_feeds WHERE id_user = $1")
+            .bind(id_user)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn rotate_feed(&self, id_user: i32, token: Uuid) -> Result<CalendarFeed> {
+        let result = sqlx::query_as::<_, CalendarFeed>(
+            "INSERT INTO calendar_feeds (id_user, token) VALUES ($1, $2)
+             ON CONFLICT (id_user) DO UPDATE SET token = EXCLUDED.token, created_at = NOW(), last_accessed_at = NULL
+             RETURNING *",
+        )
+        .bind(id_user)
+        .bind(token)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn delete_feed(&self, id_user: i32) -> Result<Option<CalendarFeed>> {
+        let result = sqlx::query_as::<_, CalendarFeed>("DELETE FROM calendar_feeds WHERE id_user = $1 RETURNING *")
+            .bind(id_user)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn open_feed(&self, token: Uuid) -> Result<Option<(i32, String)>> {
+        let result = sqlx::query_as::<_, (i32, String)>(
+            "UPDATE calendar_feeds f SET last_accessed_at = NOW()
+             FROM users u
+             WHERE f.token = $1 AND u.id_user = f.id_user AND u.deleted_at IS NULL
+             RETURNING u.id_user, u.role",
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_doctor_events(
+        &self,
+        id_user: i32,
+        since: NaiveDateTime,
+        hospital_id: Option<i32>,
+    ) -> Result<Vec<CalendarEvent>> {
+        self.get_events("d.id_user = $1 AND d.deleted_at IS NULL", id_user, since, hospital_id).await
+    }
+
+    async fn get_patient_events(
+        &self,
+        id_user: i32,
+        since: NaiveDateTime,
+        hospital_id: Option<i32>,
+    ) -> Result<Vec<CalendarEvent>> {
+        self.get_events("can_act_for_patient($1, a.id_patient)", id_user, since, hospital_id).await
+    }
+}
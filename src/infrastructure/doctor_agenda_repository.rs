@@ -0,0 +1,101 @@
+use crate::domain::doctor_agenda::AgendaAppointment;
+use crate::helpers::crypto::Keyring;
+use crate::infrastructure::patient_repository::ALLERGIES;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[async_trait]
+pub trait DoctorAgendaRepository: Send + Sync + 'static {
+    /// Usuario del doctor. None si el doctor no existe; Some(None) si no tiene usuario.
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<Option<i32>>>;
+    /// Citas del doctor ese día en orden de hora, con los datos del paciente, su
+    /// llegada, su último diagnóstico y su turno virtual del día, en una sola consulta.
+    /// Las alergias llegan descifradas.
+    async fn get_agenda(&self, id_doctor: i32, date: NaiveDate, include_canceled: bool)
+        -> Result<Vec<AgendaAppointment>>;
+}
+
+pub struct PgDoctorAgendaRepository {
+    pool: PgPool,
+    keyring: Arc<Keyring>,
+}
+
+impl PgDoctorAgendaRepository {
+    pub fn new(pool: PgPool, keyring: Arc<Keyring>) -> Self {
+        Self { pool, keyring }
+    }
+}
+
+#[async_trait]
+impl DoctorAgendaRepository for PgDoctorAgendaRepository {
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<Option<i32>>> {
+        let result = sqlx::query_scalar("SELECT id_user FROM doctors WHERE id_doctor = $1 AND deleted_at IS NULL")
+            .bind(id_doctor)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_agenda(
+        &self,
+        id_doctor: i32,
+        date: NaiveDate,
+        include_canceled: bool,
+    ) -> Result<Vec<AgendaAppointment>> {
+        // LATERAL: una sola fila por cita aunque el paciente tenga muchos registros o turnos
+        let mut rows = sqlx::query_as::<_, AgendaAppointment>(
+            "SELECT a.id_appointment, a.appointment_datetime, a.duration_minutes, a.status, a.building, a.room,
+                    s.service_name, a.notes, a.id_patient,
+                    CONCAT_WS(' ', p.first_name, p.second_name, p.first_lastname, p.second_lastname) AS patient_name,
+                    p.birthdate, date_part('year', age($2::date, p.birthdate))::int AS age,
+                    p.gender, p.blood_type, p.allergies, p.data_key,
+                    arrival.performed_at AS checked_in_at,
+                    h.id_history AS id_last_history, h.diagnosis AS last_diagnosis, h.record_date AS last_diagnosis_date,
+                    t.id_turn, t.turn_number, t.status AS turn_status, t.created_at AS turn_created_at,
+                    t.called_at AS turn_called_at
+             FROM medical_appointments a
+             JOIN patients p ON p.id_patient = a.id_patient
+             LEFT JOIN services s ON s.id_service = a.id_service
+             LEFT JOIN LATERAL (
+                 SELECT l.performed_at FROM audit_logs l
+                 WHERE l.table_name = 'medical_appointments' AND l.record_id = a.id_appointment
+                   AND l.action = 'status_change' AND l.new_data->>'status' = 'checked_in'
+                 ORDER BY l.performed_at DESC
+                 LIMIT 1
+             ) arrival ON TRUE
+             LEFT JOIN LATERAL (
+                 SELECT m.id_history, m.diagnosis, m.record_date FROM medical_history m
+                 WHERE m.id_patient = a.id_patient AND m.deleted_at IS NULL
+                 ORDER BY m.record_date DESC, m.id_history DESC
+                 LIMIT 1
+             ) h ON TRUE
+             LEFT JOIN LATERAL (
+                 SELECT v.id_turn, v.turn_number, v.status, v.created_at, v.called_at FROM virtual_turns v
+                 WHERE v.id_patient = a.id_patient AND v.turn_date = $2::date
+                 ORDER BY (v.id_service = a.id_service) DESC, v.created_at DESC
+                 LIMIT 1
+             ) t ON TRUE
+             WHERE a.id_doctor = $1 AND a.deleted_at IS NULL
+               AND a.appointment_datetime >= $2::date
+               AND a.appointment_datetime < $2::date + 1
+               AND ($3 OR a.status <> 'canceled')
+             ORDER BY a.appointment_datetime, a.id_appointment",
+        )
+        .bind(id_doctor)
+        .bind(date)
+        .bind(include_canceled)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in &mut rows {
+            if let Some(wrapped) = &row.data_key {
+                let dek = self.keyring.unwrap_data_key(wrapped)?;
+                row.allergies = dek.decrypt_opt(ALLERGIES, row.allergies.as_deref())?;
+            }
+        }
+        Ok(rows)
+    }
+}
@@ -1,12 +1,28 @@
-use crate::domain::doctor::{Doctor, CreateDoctor, UpdateDoctor};
+use crate::domain::doctor::{Doctor, CreateDoctor, UpdateDoctor, DoctorFilter};
 use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::PgPool;
-use sqlx::Error;
+use crate::helpers::errors::on_unique_violation;
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_doctor, id_area, id_speciality, id_service, id_user, first_name, second_name, first_lastname, second_lastname, phone, email, created_at, updated_at, deleted_at",
+    from: "doctors",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_doctor",
+    sort_fields: &[
+        SortField { name: "id_doctor", column: "id_doctor", sql_type: "int" },
+        SortField { name: "first_name", column: "first_name", sql_type: "text" },
+        SortField { name: "first_lastname", column: "first_lastname", sql_type: "text" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "id_doctor",
+};
 
 #[async_trait]
 pub trait DoctorRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<Doctor>>;
+    async fn get_all(&self, filter: &DoctorFilter, page: &PageParams) -> Result<Page<Doctor>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Doctor>>;
     async fn create(&self, data: CreateDoctor) -> Result<Doctor>;
     async fn update(&self, id: i32, data: UpdateDoctor) -> Result<Option<Doctor>>;
@@ -25,11 +41,18 @@ impl PgDoctorRepository {
 
 #[async_trait]
 impl DoctorRepository for PgDoctorRepository {
-    async fn get_all(&self) -> Result<Vec<Doctor>> {
-        let result: Vec<Doctor> = sqlx::query_as::<_, Doctor>(
-            "SELECT id_doctor, id_area, id_speciality, id_service, id_user, first_name, second_name, first_lastname, second_lastname, phone, email, created_at, updated_at, deleted_at FROM doctors WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
+    async fn get_all(&self, filter: &DoctorFilter, page: &PageParams) -> Result<Page<Doctor>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(id_area) = filter.id_area {
+                q.push(" AND id_area = ").push_bind(id_area);
+            }
+            if let Some(id_speciality) = filter.id_speciality {
+                q.push(" AND id_speciality = ").push_bind(id_speciality);
+            }
+            if let Some(id_service) = filter.id_service {
+                q.push(" AND id_service = ").push_bind(id_service);
+            }
+        })
         .await?;
         Ok(result)
     }
@@ -62,19 +85,10 @@ impl DoctorRepository for PgDoctorRepository {
         .bind(data.phone)
         .bind(data.email);
 
-        let result = match query.fetch_one(&self.pool).await {
-            Ok(doctor) => doctor,
-            Err(Error::Database(db_err)) => {
-                if db_err.code().as_deref() == Some("23505") {
-                    anyhow::bail!("El correo ya existe")
-                } else {
-                    anyhow::bail!("Error en la base de datos: {}", db_err.message())
-                }
-            }
-            Err(e) => {
-                anyhow::bail!("Error inesperado: {}", e)
-            }
-        };
+        let result = query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| on_unique_violation(e, "El correo ya existe"))?;
 
         Ok(result)
     }
@@ -93,7 +107,7 @@ impl DoctorRepository for PgDoctorRepository {
             phone = $9,
             email = $10,
             updated_at = NOW()            
-         WHERE id_doctor = $11
+         WHERE id_doctor = $11 AND deleted_at IS NULL
          RETURNING *",
         )
         .bind(data.id_area)
@@ -114,7 +128,7 @@ impl DoctorRepository for PgDoctorRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<Doctor>> {
         let result: Option<Doctor> = sqlx::query_as::<_, Doctor>(
-            "UPDATE doctors SET deleted_at = NOW() WHERE id_doctor = $1 RETURNING *",
+            "UPDATE doctors SET deleted_at = NOW() WHERE id_doctor = $1 AND deleted_at IS NULL RETURNING *",
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -0,0 +1,303 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use sqlx::PgPool;
+
+use crate::domain::doctor_schedule::{
+    CreateDoctorSchedule, CreateDoctorScheduleException, DoctorSchedule, DoctorScheduleException, ExceptionFilter,
+    FreeSlot, SlotFilter,
+};
+
+// Rango de la ausencia `e`; sin horas va del inicio de start_date al final de end_date
+const EXCEPTION_RANGE: &str = "tsrange(
+    e.start_date + COALESCE(e.start_time, TIME '00:00'),
+    CASE WHEN e.end_time IS NULL THEN e.end_date + 1 ELSE e.end_date + e.end_time END
+)";
+
+#[async_trait]
+pub trait DoctorScheduleRepository: Send + Sync + 'static {
+    /// id_user del doctor (puede no tener); None si el doctor no existe
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<Option<i32>>>;
+    async fn get_schedules(&self, id_doctor: i32) -> Result<Vec<DoctorSchedule>>;
+    /// Bloque del mismo día que se cruza con [start, end), si hay
+    async fn find_overlapping(&self, id_doctor: i32, day_of_week: i16, start: NaiveTime, end: NaiveTime) -> Result<Option<i32>>;
+    async fn create_schedule(&self, id_doctor: i32, data: CreateDoctorSchedule) -> Result<DoctorSchedule>;
+    async fn delete_schedule(&self, id_doctor: i32, id_schedule: i32) -> Result<Option<DoctorSchedule>>;
+    /// Bloque que contiene la fecha y hora (por día de la semana y hora)
+    async fn find_block(&self, id_doctor: i32, at: NaiveDateTime) -> Result<Option<DoctorSchedule>>;
+    async fn get_exceptions(&self, id_doctor: i32, filter: &ExceptionFilter) -> Result<Vec<DoctorScheduleException>>;
+    async fn create_exception(
+        &self,
+        id_doctor: i32,
+        data: CreateDoctorScheduleException,
+        created_by: i32,
+    ) -> Result<DoctorScheduleException>;
+    async fn delete_exception(&self, id_doctor: i32, id_exception: i32) -> Result<Option<DoctorScheduleException>>;
+    /// Ausencia que se cruza con [start, end), si hay
+    async fn find_exception(&self, id_doctor: i32, start: NaiveDateTime, end: NaiveDateTime) -> Result<Option<DoctorScheduleException>>;
+    /// Citas activas del doctor que se cruzan con [start, end)
+    async fn get_appointments_between(&self, id_doctor: i32, start: NaiveDateTime, end: NaiveDateTime) -> Result<Vec<i32>>;
+    /// Turnos de los horarios entre `from` y `to` (días incluidos) que empiezan después
+    /// de `now` (hora local), sin cita que los ocupe, ausencia del doctor ni oferta de
+    /// la lista de espera vigente. Por fecha y doctor.
+    async fn find_free_slots(
+        &self,
+        filter: &SlotFilter,
+        from: NaiveDate,
+        to: NaiveDate,
+        now: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<FreeSlot>>;
+}
+
+pub struct PgDoctorScheduleRepository {
+    pool: PgPool,
+}
+
+impl PgDoctorScheduleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DoctorScheduleRepository for PgDoctorScheduleRepository {
+    async fn get_doctor_user(&self, id_doctor: i32) -> Result<Option<Option<i32>>> {
+        let result = sqlx::query_scalar(
+            "SELECT id_user FROM doctors WHERE id_doctor = $1 AND deleted_at IS NULL",
+        )
+        .bind(id_doctor)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_schedules(&self, id_doctor: i32) -> Result<Vec<DoctorSchedule>> {
+        let result = sqlx::query_as::<_, DoctorSchedule>(
+            "SELECT * FROM doctor_schedules
+             WHERE id_doctor = $1 AND deleted_at IS NULL
+             ORDER BY day_of_week, start_time",
+        )
+        .bind(id_doctor)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_overlapping(&self, id_doctor: i32, day_of_week: i16, start: NaiveTime, end: NaiveTime) -> Result<Option<i32>> {
+        let result = sqlx::query_scalar(
+            "SELECT id_schedule FROM doctor_schedules
+             WHERE id_doctor = $1 AND day_of_week = $2 AND deleted_at IS NULL
+               AND start_time < $4 AND $3 < end_time
+             LIMIT 1",
+        )
+        .bind(id_doctor)
+        .bind(day_of_week)
+        .bind(start)
+        .bind(end)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn create_schedule(&self, id_doctor: i32, data: CreateDoctorSchedule) -> Result<DoctorSchedule> {
+        let result = sqlx::query_as::<_, DoctorSchedule>(
+            "INSERT INTO doctor_schedules
+                (id_doctor, id_area, day_of_week, start_time, end_time, slot_minutes, building, room)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING *",
+        )
+        .bind(id_doctor)
+        .bind(data.id_area)
+        .bind(data.day_of_week)
+        .bind(data.start_time)
+        .bind(data.end_time)
+        .bind(data.slot_minutes)
+        .bind(data.building)
+        .bind(data.room)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn delete_schedule(&self, id_doctor: i32, id_schedule: i32) -> Result<Option<DoctorSchedule>> {
+        let result = sqlx::query_as::<_, DoctorSchedule>(
+            "UPDATE doctor_schedules SET deleted_at = NOW()
+             WHERE id_schedule = $1 AND id_doctor = $2 AND deleted_at IS NULL
+             RETURNING *",
+        )
+        .bind(id_schedule)
+        .bind(id_doctor)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_block(&self, id_doctor: i32, at: NaiveDateTime) -> Result<Option<DoctorSchedule>> {
+        let result = sqlx::query_as::<_, DoctorSchedule>(
+            "SELECT * FROM doctor_schedules
+             WHERE id_doctor = $1 AND deleted_at IS NULL
+               AND day_of_week = EXTRACT(ISODOW FROM $2::timestamp)
+               AND start_time <= $2::time AND $2::time < end_time",
+        )
+        .bind(id_doctor)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_exceptions(&self, id_doctor: i32, filter: &ExceptionFilter) -> Result<Vec<DoctorScheduleException>> {
+        let result = sqlx::query_as::<_, DoctorScheduleException>(
+            "SELECT * FROM doctor_schedule_exceptions
+             WHERE id_doctor = $1 AND deleted_at IS NULL
+               AND ($2::date IS NULL OR end_date >= $2)
+               AND ($3::date IS NULL OR start_date <= $3)
+             ORDER BY start_date, start_time NULLS FIRST",
+        )
+        .bind(id_doctor)
+        .bind(filter.date_from)
+        .bind(filter.date_to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn create_exception(
+        &self,
+        id_doctor: i32,
+        data: CreateDoctorScheduleException,
+        created_by: i32,
+    ) -> Result<DoctorScheduleException> {
+        let result = sqlx::query_as::<_, DoctorScheduleException>(
+            "INSERT INTO doctor_schedule_exceptions
+                (id_doctor, exception_type, start_date, end_date, start_time, end_time, notes, created_by)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING *",
+        )
+        .bind(id_doctor)
+        .bind(data.exception_type)
+        .bind(data.start_date)
+        .bind(data.end_date)
+        .bind(data.start_time)
+        .bind(data.end_time)
+        .bind(data.notes)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn delete_exception(&self, id_doctor: i32, id_exception: i32) -> Result<Option<DoctorScheduleException>> {
+        let result = sqlx::query_as::<_, DoctorScheduleException>(
+            "UPDATE doctor_schedule_exceptions SET deleted_at = NOW()
+             WHERE id_exception = $1 AND id_doctor = $2 AND deleted_at IS NULL
+             RETURNING *",
+        )
+        .bind(id_exception)
+        .bind(id_doctor)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_exception(&self, id_doctor: i32, start: NaiveDateTime, end: NaiveDateTime) -> Result<Option<DoctorScheduleException>> {
+        let result = sqlx::query_as::<_, DoctorScheduleException>(&format!(
+            "SELECT e.* FROM doctor_schedule_exceptions e
+             WHERE e.id_doctor = $1 AND e.deleted_at IS NULL
+               AND {EXCEPTION_RANGE} && tsrange($2, $3)
+             LIMIT 1"
+        ))
+        .bind(id_doctor)
+        .bind(start)
+        .bind(end)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_appointments_between(&self, id_doctor: i32, start: NaiveDateTime, end: NaiveDateTime) -> Result<Vec<i32>> {
+        let result = sqlx::query_scalar(
+            "SELECT id_appointment FROM medical_appointments
+             WHERE id_doctor = $1 AND deleted_at IS NULL
+               AND tsrange(appointment_datetime, appointment_datetime + duration_minutes * INTERVAL '1 minute')
+                   && tsrange($2, $3)
+               AND status IN ('pending', 'confirmed', 'checked_in')
+             ORDER BY appointment_datetime",
+        )
+        .bind(id_doctor)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn find_free_slots(
+        &self,
+        filter: &SlotFilter,
+        from: NaiveDate,
+        to: NaiveDate,
+        now: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<FreeSlot>> {
+        // Cada bloque se expande en sus turnos para cada día del rango que coincide
+        // con su día de la semana; una cita, o un turno reservado para la lista de
+        // espera, ocupa los turnos con los que se cruza
+        let result = sqlx::query_as::<_, FreeSlot>(&format!(
+            "WITH slots AS (
+                 SELECT s.id_schedule, s.id_doctor, s.id_area, s.building, s.room,
+                        day::date + s.start_time + make_interval(mins => s.slot_minutes * n) AS start,
+                        day::date + s.start_time + make_interval(mins => s.slot_minutes * (n + 1)) AS \"end\"
+                 FROM doctor_schedules s
+                 JOIN generate_series($1::date, $2::date, INTERVAL '1 day') AS day
+                   ON EXTRACT(ISODOW FROM day) = s.day_of_week
+                 CROSS JOIN LATERAL generate_series(
+                     0, (EXTRACT(EPOCH FROM s.end_time - s.start_time) / 60)::int / s.slot_minutes - 1
+                 ) AS n
+                 WHERE s.deleted_at IS NULL
+             )
+             SELECT sl.id_doctor, concat_ws(' ', d.first_name, d.first_lastname) AS doctor_name,
+                    d.id_service, d.id_speciality, sl.id_area, sl.id_schedule,
+                    sl.start, sl.\"end\", sl.building, sl.room
+             FROM slots sl
+             JOIN doctors d ON d.id_doctor = sl.id_doctor AND d.deleted_at IS NULL
+             WHERE sl.start > $3
+               AND ($4::int IS NULL OR d.id_speciality = $4)
+               AND ($5::int IS NULL OR d.id_service = $5)
+               AND ($6::int IS NULL OR sl.id_area = $6)
+               AND ($7::int IS NULL OR sl.id_doctor = $7)
+               AND NOT EXISTS (
+                   SELECT 1 FROM medical_appointments a
+                   WHERE a.id_doctor = sl.id_doctor AND a.deleted_at IS NULL
+                     AND a.status NOT IN ('canceled', 'no_show')
+                     AND tsrange(a.appointment_datetime, a.appointment_datetime + a.duration_minutes * INTERVAL '1 minute')
+                         && tsrange(sl.start, sl.\"end\")
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM doctor_schedule_exceptions e
+                   WHERE e.id_doctor = sl.id_doctor AND e.deleted_at IS NULL
+                     AND {EXCEPTION_RANGE} && tsrange(sl.start, sl.\"end\")
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM waitlist_offers o
+                   WHERE o.id_doctor = sl.id_doctor AND o.status = 'pending' AND o.expires_at > $3
+                     AND tsrange(o.appointment_datetime, o.appointment_datetime + o.duration_minutes * INTERVAL '1 minute')
+                         && tsrange(sl.start, sl.\"end\")
+               )
+             ORDER BY sl.start, sl.id_doctor
+             LIMIT $8"
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(now)
+        .bind(filter.id_speciality)
+        .bind(filter.id_service)
+        .bind(filter.id_area)
+        .bind(filter.id_doctor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+}
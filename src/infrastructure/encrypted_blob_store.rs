@@ -0,0 +1,248 @@
+use crate::helpers::crypto::{DataKey, Keyring};
+use crate::infrastructure::blob_store::{BlobStore, BlobStream};
+use aes_gcm::{
+    aead::{Aead, AeadCore, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use std::sync::Arc;
+
+// Formato del archivo cifrado:
+// MAGIC | u16 largo DEK | DEK envuelta ("id:base64") | prefijo de nonce (7) | bloques
+// Cada bloque cifra CHUNK_SIZE bytes (el último puede ser menor) con
+// nonce = prefijo || contador u32 || 1 si es el último bloque, 0 si no.
+const MAGIC: &[u8; 4] = b"NXE1";
+const CHUNK_SIZE: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+const PREFIX_LEN: usize = 7;
+
+/// BlobStore que cifra cada archivo con su propia DEK antes de delegar
+/// en el almacenamiento real. Los archivos sin cabecera se leen en claro.
+pub struct EncryptedBlobStore<B: BlobStore> {
+    inner: B,
+    keyring: Arc<Keyring>,
+}
+
+impl<B: BlobStore> EncryptedBlobStore<B> {
+    pub fn new(inner: B, keyring: Arc<Keyring>) -> Self {
+        Self { inner, keyring }
+    }
+
+    /// Cifra un archivo existente en claro o vuelve a envolver su DEK con la
+    /// llave maestra activa. Devuelve true si el archivo se reescribió. El
+    /// contenido pasa en streaming: nunca se carga completo en memoria.
+    pub async fn rotate(&self, key: &str) -> Result<bool> {
+        let mut inner = self.inner.get(key).await?;
+        let (mut buf, header) = read_header(&mut inner).await?;
+
+        let Some((wrapped, body_start)) = header else {
+            // Archivo anterior al cifrado
+            let plain = stream::once(async move { Ok(buf.freeze()) }).chain(inner);
+            self.put_stream(key, Box::pin(plain)).await?;
+            return Ok(true);
+        };
+        if !self.keyring.needs_rewrap(&wrapped) {
+            return Ok(false);
+        }
+
+        // Solo cambia la cabecera; los bloques cifrados se copian tal cual
+        let mut head = BytesMut::new();
+        write_header(&mut head, &self.keyring.rewrap(&wrapped)?);
+        buf.advance(body_start);
+        head.extend_from_slice(&buf);
+        let out = stream::once(async move { Ok(head.freeze()) }).chain(inner);
+        self.inner.put_stream(key, Box::pin(out)).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl<B: BlobStore> BlobStore for EncryptedBlobStore<B> {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        self.put_stream(key, Box::pin(stream::once(async move { Ok(data) }))).await
+    }
+
+    async fn put_stream(&self, key: &str, data: BlobStream) -> Result<()> {
+        let (dek, wrapped) = self.keyring.new_data_key()?;
+        let prefix: [u8; PREFIX_LEN] = Aes256Gcm::generate_nonce(&mut OsRng)[..PREFIX_LEN].try_into()?;
+
+        let mut head = BytesMut::new();
+        write_header(&mut head, &wrapped);
+        head.extend_from_slice(&prefix);
+        let out = stream::once(async move { Ok(head.freeze()) }).chain(encrypting_stream(data, dek, prefix));
+
+        self.inner.put_stream(key, Box::pin(out)).await
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream> {
+        let mut inner = self.inner.get(key).await?;
+        let (mut buf, header) = read_header(&mut inner).await?;
+
+        let Some((wrapped, body_start)) = header else {
+            // Archivo en claro: devolver lo leído seguido del resto
+            let head = stream::once(async move { Ok(buf.freeze()) });
+            return Ok(Box::pin(head.chain(inner)));
+        };
+
+        let dek = self.keyring.unwrap_data_key(&wrapped)?;
+        buf.advance(body_start);
+        let prefix: [u8; PREFIX_LEN] = buf[..PREFIX_LEN].try_into()?;
+        buf.advance(PREFIX_LEN);
+
+        Ok(decrypting_stream(inner, buf, dek, prefix))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.inner.delete(key).await
+    }
+}
+
+/// Lee del archivo hasta tener la cabecera completa y el prefijo de nonce.
+/// Devuelve lo leído y la cabecera, o None si el archivo está en claro.
+async fn read_header(inner: &mut BlobStream) -> Result<(BytesMut, Option<(String, usize)>)> {
+    let mut buf = BytesMut::new();
+    let header = loop {
+        if buf.len() >= MAGIC.len() && &buf[..MAGIC.len()] != MAGIC {
+            break None;
+        }
+        if let Some(parsed) = parse_header(&buf)? {
+            if buf.len() >= parsed.1 + PREFIX_LEN {
+                break Some(parsed);
+            }
+        }
+        match inner.try_next().await? {
+            Some(chunk) => buf.extend_from_slice(&chunk),
+            None if buf.len() < MAGIC.len() || &buf[..MAGIC.len()] != MAGIC => break None,
+            None => bail!("Archivo cifrado truncado"),
+        }
+    };
+    Ok((buf, header))
+}
+
+fn write_header(out: &mut BytesMut, wrapped: &str) {
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&(wrapped.len() as u16).to_be_bytes());
+    out.extend_from_slice(wrapped.as_bytes());
+}
+
+/// Devuelve la DEK envuelta y dónde empieza el prefijo de nonce,
+/// o None si el archivo no está cifrado (o aún no hay bytes suficientes).
+fn parse_header(raw: &[u8]) -> Result<Option<(String, usize)>> {
+    if raw.len() < MAGIC.len() + 2 || &raw[..MAGIC.len()] != MAGIC {
+        return Ok(None);
+    }
+    let len = u16::from_be_bytes([raw[4], raw[5]]) as usize;
+    let end = MAGIC.len() + 2 + len;
+    if raw.len() < end {
+        return Ok(None);
+    }
+    let wrapped = std::str::from_utf8(&raw[6..end])?.to_string();
+    Ok(Some((wrapped, end)))
+}
+
+fn chunk_nonce(prefix: &[u8], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..PREFIX_LEN].copy_from_slice(prefix);
+    nonce[PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+fn encrypting_stream(inner: BlobStream, dek: DataKey, prefix: [u8; PREFIX_LEN]) -> BlobStream {
+    struct State {
+        inner: BlobStream,
+        buf: BytesMut,
+        dek: DataKey,
+        prefix: [u8; PREFIX_LEN],
+        counter: u32,
+        done: bool,
+    }
+
+    let state = State { inner, buf: BytesMut::new(), dek, prefix, counter: 0, done: false };
+
+    let s = stream::try_unfold(state, |mut st| async move {
+        if st.done {
+            return Ok(None);
+        }
+        loop {
+            // Un bloque completo seguido de más datos no puede ser el último
+            if st.buf.len() > CHUNK_SIZE {
+                let plain = st.buf.split_to(CHUNK_SIZE);
+                let sealed = encrypt_chunk(&st.dek, &st.prefix, st.counter, false, &plain)?;
+                st.counter += 1;
+                return Ok(Some((sealed, st)));
+            }
+            match st.inner.try_next().await? {
+                Some(chunk) => st.buf.extend_from_slice(&chunk),
+                None => {
+                    // El último bloque puede ir vacío (archivo de 0 bytes)
+                    let plain = st.buf.split();
+                    let sealed = encrypt_chunk(&st.dek, &st.prefix, st.counter, true, &plain)?;
+                    st.done = true;
+                    return Ok(Some((sealed, st)));
+                }
+            }
+        }
+    });
+
+    Box::pin(s)
+}
+
+fn encrypt_chunk(dek: &DataKey, prefix: &[u8], counter: u32, last: bool, plain: &[u8]) -> std::io::Result<Bytes> {
+    let nonce = chunk_nonce(prefix, counter, last);
+    dek.cipher()
+        .encrypt(Nonce::from_slice(&nonce), plain)
+        .map(Bytes::from)
+        .map_err(|_| std::io::Error::other("Error al cifrar el archivo"))
+}
+
+fn decrypting_stream(inner: BlobStream, buf: BytesMut, dek: DataKey, prefix: [u8; PREFIX_LEN]) -> BlobStream {
+    struct State {
+        inner: BlobStream,
+        buf: BytesMut,
+        dek: DataKey,
+        prefix: [u8; PREFIX_LEN],
+        counter: u32,
+        done: bool,
+    }
+
+    let state = State { inner, buf, dek, prefix, counter: 0, done: false };
+
+    let s = stream::try_unfold(state, |mut st| async move {
+        if st.done {
+            return Ok(None);
+        }
+        let sealed_len = CHUNK_SIZE + TAG_LEN;
+        loop {
+            // Un bloque completo seguido de más datos no puede ser el último
+            if st.buf.len() > sealed_len {
+                let sealed = st.buf.split_to(sealed_len);
+                let plain = decrypt_chunk(&st.dek, &st.prefix, st.counter, false, &sealed)?;
+                st.counter += 1;
+                return Ok(Some((plain, st)));
+            }
+            match st.inner.try_next().await? {
+                Some(chunk) => st.buf.extend_from_slice(&chunk),
+                None => {
+                    let sealed = st.buf.split();
+                    let plain = decrypt_chunk(&st.dek, &st.prefix, st.counter, true, &sealed)?;
+                    st.done = true;
+                    return Ok(Some((plain, st)));
+                }
+            }
+        }
+    });
+
+    Box::pin(s)
+}
+
+fn decrypt_chunk(dek: &DataKey, prefix: &[u8], counter: u32, last: bool, sealed: &[u8]) -> std::io::Result<Bytes> {
+    let nonce = chunk_nonce(prefix, counter, last);
+    dek.cipher()
+        .decrypt(Nonce::from_slice(&nonce), sealed)
+        .map(Bytes::from)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "Archivo cifrado alterado"))
+}
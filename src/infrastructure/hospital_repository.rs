@@ -3,10 +3,24 @@ use sqlx::PgPool;
 use anyhow::Result;
 
 use crate::domain::hospital::{Hospital, CreateHospital};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_hospital, name, address",
+    from: "hospitals",
+    base_filter: "TRUE",
+    id_column: "id_hospital",
+    sort_fields: &[
+        SortField { name: "id_hospital", column: "id_hospital", sql_type: "int" },
+        SortField { name: "name", column: "name", sql_type: "text" },
+    ],
+    default_sort: "id_hospital",
+};
 
 #[async_trait]
 pub trait HospitalRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<Hospital>>;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Hospital>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Hospital>>;
     async fn create(&self, data: CreateHospital) -> Result<Hospital>;
     async fn update(&self, id: i32, data: CreateHospital) -> Result<Option<Hospital>>;
@@ -25,12 +39,8 @@ impl PgHospitalRepository {
 
 #[async_trait]
 impl HospitalRepository for PgHospitalRepository {
-    async fn get_all(&self) -> Result<Vec<Hospital>> {
-        let result = sqlx::query_as::<_, Hospital>(
-            "SELECT id_hospital, name, address FROM hospitals"
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Hospital>> {
+        let result = fetch_page(&self.pool, &LIST, page, |_| {}).await?;
         Ok(result)
     }
 
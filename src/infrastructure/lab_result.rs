@@ -2,11 +2,27 @@ use async_trait::async_trait;
 use sqlx::PgPool;
 use anyhow::Result;
 
-use crate::domain::lab_result::{LabResult, CreateLabResult, UpdateLabResult};
+use crate::domain::lab_result::{LabResult, CreateLabResult, UpdateLabResult, LabResultFilter};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "*",
+    from: "lab_results",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_result",
+    sort_fields: &[
+        SortField { name: "id_result", column: "id_result", sql_type: "int" },
+        SortField { name: "lab_name", column: "lab_name", sql_type: "text" },
+        SortField { name: "result_date", column: "result_date", sql_type: "timestamp" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "-result_date",
+};
 
 #[async_trait]
 pub trait LabResultRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<LabResult>>;
+    async fn get_all(&self, filter: &LabResultFilter, page: &PageParams) -> Result<Page<LabResult>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<LabResult>>;
     async fn create(&self, data: CreateLabResult) -> Result<LabResult>;
     async fn update(&self, id: i32, data: UpdateLabResult) -> Result<Option<LabResult>>;
@@ -25,11 +41,18 @@ impl PgLabResultRepository {
 
 #[async_trait]
 impl LabResultRepository for PgLabResultRepository {
-    async fn get_all(&self) -> Result<Vec<LabResult>> {
-        let result: Vec<LabResult> = sqlx::query_as::<_, LabResult>(
-            "SELECT * FROM lab_results WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
+    async fn get_all(&self, filter: &LabResultFilter, page: &PageParams) -> Result<Page<LabResult>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(id_patient) = filter.id_patient {
+                q.push(" AND id_patient = ").push_bind(id_patient);
+            }
+            if let Some(id_doctor) = filter.id_doctor {
+                q.push(" AND id_doctor = ").push_bind(id_doctor);
+            }
+            if let Some(test_type) = &filter.test_type {
+                q.push(" AND test_type = ").push_bind(test_type.clone());
+            }
+        })
         .await?;
         Ok(result)
     }
@@ -84,7 +107,7 @@ impl LabResultRepository for PgLabResultRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<LabResult>> {
         let result = sqlx::query_as::<_, LabResult>(
-            "UPDATE lab_results SET deleted_at = NOW() WHERE id_result = $1 RETURNING *"
+            "UPDATE lab_results SET deleted_at = NOW() WHERE id_result = $1 AND deleted_at IS NULL RETURNING *"
         )
         .bind(id)
         .fetch_optional(&self.pool)
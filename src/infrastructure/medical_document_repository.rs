@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+
+use crate::domain::medical_document::{CreateMedicalDocument, MedicalDocument};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "*",
+    from: "medical_documents",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_document",
+    sort_fields: &[
+        SortField { name: "id_document", column: "id_document", sql_type: "int" },
+        SortField { name: "uploaded_at", column: "uploaded_at", sql_type: "timestamp" },
+    ],
+    default_sort: "-uploaded_at",
+};
+
+#[async_trait]
+pub trait MedicalDocumentRepository: Send + Sync + 'static {
+    async fn get_by_id(&self, id: i32) -> Result<Option<MedicalDocument>>;
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<MedicalDocument>>;
+    async fn create(&self, data: CreateMedicalDocument) -> Result<MedicalDocument>;
+    /// id_user dueño del expediente: None si el paciente no existe
+    async fn get_patient_user(&self, id_patient: i32) -> Result<Option<Option<i32>>>;
+    /// El usuario es el propio paciente o su tutor legal
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool>;
+    /// Claves de todos los archivos guardados (originales y vistas previas)
+    async fn get_blob_keys(&self) -> Result<Vec<String>>;
+}
+
+pub struct PgMedicalDocumentRepository {
+    pool: PgPool,
+}
+
+impl PgMedicalDocumentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MedicalDocumentRepository for PgMedicalDocumentRepository {
+    async fn get_by_id(&self, id: i32) -> Result<Option<MedicalDocument>> {
+        let result = sqlx::query_as::<_, MedicalDocument>(
+            "SELECT * FROM medical_documents WHERE id_document = $1 AND deleted_at IS NULL"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<MedicalDocument>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            q.push(" AND id_patient = ").push_bind(id_patient);
+        })
+        .await?;
+        Ok(result)
+    }
+
+    async fn create(&self, data: CreateMedicalDocument) -> Result<MedicalDocument> {
+        let result = sqlx::query_as::<_, MedicalDocument>(
+            "INSERT INTO medical_documents
+            (id_patient, id_doctor, document_type, file_path, file_name, content_type, file_size, preview_path, description)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+            RETURNING *"
+        )
+        .bind(data.id_patient)
+        .bind(data.id_doctor)
+        .bind(data.document_type)
+        .bind(data.file_path)
+        .bind(data.file_name)
+        .bind(data.content_type)
+        .bind(data.file_size)
+        .bind(data.preview_path)
+        .bind(data.description)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn get_patient_user(&self, id_patient: i32) -> Result<Option<Option<i32>>> {
+        let result: Option<Option<i32>> = sqlx::query_scalar(
+            "SELECT id_user FROM patients WHERE id_patient = $1 AND deleted_at IS NULL"
+        )
+        .bind(id_patient)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar("SELECT can_act_for_patient($1, $2)")
+            .bind(id_user)
+            .bind(id_patient)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_blob_keys(&self) -> Result<Vec<String>> {
+        let result: Vec<String> = sqlx::query_scalar(
+            "SELECT file_path FROM medical_documents
+             UNION ALL
+             SELECT preview_path FROM medical_documents WHERE preview_path IS NOT NULL"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+}
@@ -2,11 +2,26 @@ use async_trait::async_trait;
 use sqlx::PgPool;
 use anyhow::Result;
 
-use crate::domain::medical_history::{MedicalHistory, CreateMedicalHistory, UpdateMedicalHistory};
+use crate::domain::medical_history::{MedicalHistory, CreateMedicalHistory, UpdateMedicalHistory, MedicalHistoryFilter};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "*",
+    from: "medical_history",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_history",
+    sort_fields: &[
+        SortField { name: "id_history", column: "id_history", sql_type: "int" },
+        SortField { name: "record_date", column: "record_date", sql_type: "timestamp" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "-record_date",
+};
 
 #[async_trait]
 pub trait MedicalHistoryRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<MedicalHistory>>;
+    async fn get_all(&self, filter: &MedicalHistoryFilter, page: &PageParams) -> Result<Page<MedicalHistory>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<MedicalHistory>>;
     async fn create(&self, data: CreateMedicalHistory) -> Result<MedicalHistory>;
     async fn update(&self, id: i32, data: UpdateMedicalHistory) -> Result<Option<MedicalHistory>>;
@@ -25,11 +40,15 @@ impl PgMedicalHistoryRepository {
 
 #[async_trait]
 impl MedicalHistoryRepository for PgMedicalHistoryRepository {
-    async fn get_all(&self) -> Result<Vec<MedicalHistory>> {
-        let result: Vec<MedicalHistory> = sqlx::query_as::<_, MedicalHistory>(
-            "SELECT * FROM medical_history WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
+    async fn get_all(&self, filter: &MedicalHistoryFilter, page: &PageParams) -> Result<Page<MedicalHistory>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(id_patient) = filter.id_patient {
+                q.push(" AND id_patient = ").push_bind(id_patient);
+            }
+            if let Some(id_doctor) = filter.id_doctor {
+                q.push(" AND id_doctor = ").push_bind(id_doctor);
+            }
+        })
         .await?;
         Ok(result)
     }
@@ -84,7 +103,7 @@ impl MedicalHistoryRepository for PgMedicalHistoryRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<MedicalHistory>> {
         let result = sqlx::query_as::<_, MedicalHistory>(
-            "UPDATE medical_history SET deleted_at = NOW() WHERE id_history = $1 RETURNING *"
+            "UPDATE medical_history SET deleted_at = NOW() WHERE id_history = $1 AND deleted_at IS NULL RETURNING *"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -7,4 +7,23 @@ pub mod speciality_repository;
 pub mod appointment_repository;
 pub mod medical_history_repository;
 pub mod lab_result;
-pub mod auth_repository;
\ No newline at end of file
+pub mod auth_repository;
+pub mod access_log_repository;
+pub mod blob_store;
+pub mod medical_document_repository;
+pub mod preview;
+pub mod encrypted_blob_store;
+pub mod pagination;
+pub mod patient_merge_repository;
+pub mod patient_contact_repository;
+pub mod notifier;
+pub mod registration_repository;
+pub mod virtual_turn_repository;
+pub mod spreadsheet;
+pub mod patient_import_repository;
+pub mod trash_repository;
+pub mod doctor_schedule_repository;
+pub mod waitlist_repository;
+pub mod reminder_repository;
+pub mod calendar_repository;
+pub mod doctor_agenda_repository;
\ No newline at end of file
@@ -0,0 +1,38 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Envío de mensajes al paciente por correo o SMS. `channel` es "email" o "phone".
+/// La implementación por defecto solo escribe en el log; un proveedor real
+/// (SMTP, pasarela de SMS) debe implementar este mismo trait.
+#[async_trait]
+pub trait Notifier: Send + Sync + 'static {
+    async fn send(&self, channel: &str, to: &str, subject: &str, body: &str) -> Result<()>;
+}
+
+/// Sin proveedor configurado: deja constancia del envío en el log del servidor.
+/// El cuerpo nunca se escribe porque lleva códigos de verificación y enlaces
+/// para confirmar o cancelar citas; el destino va enmascarado.
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn send(&self, channel: &str, to: &str, subject: &str, _body: &str) -> Result<()> {
+        println!("[{}] a {}: {}", channel, mask_destination(to), subject);
+        Ok(())
+    }
+}
+
+/// `jo***@gmail.com` para correos y `***1234` para teléfonos
+fn mask_destination(to: &str) -> String {
+    match to.split_once('@') {
+        Some((user, domain)) => {
+            let visible: String = user.chars().take(2).collect();
+            format!("{}***@{}", visible, domain)
+        }
+        None => {
+            let digits: Vec<char> = to.chars().filter(char::is_ascii_digit).collect();
+            let last: String = digits[digits.len().saturating_sub(4)..].iter().collect();
+            format!("***{}", last)
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, FromRow, PgPool, Postgres, QueryBuilder, Row};
+
+use crate::domain::pagination::{InvalidListQuery, Page, PageParams};
+
+/// Campo por el que se permite ordenar un listado.
+/// Solo columnas NOT NULL (o con DEFAULT): el cursor compara (columna, id).
+pub struct SortField {
+    /// Nombre expuesto en `?sort=`
+    pub name: &'static str,
+    /// Expresión SQL de la columna
+    pub column: &'static str,
+    /// Tipo al que se convierte el valor del cursor (ej. "timestamp", "int")
+    pub sql_type: &'static str,
+}
+
+/// Descripción estática de un listado paginable
+pub struct ListQuery {
+    pub columns: &'static str,
+    /// FROM (puede incluir JOINs)
+    pub from: &'static str,
+    /// Condición fija, ej. "deleted_at IS NULL"; los filtros se agregan con AND
+    pub base_filter: &'static str,
+    /// Clave única que desempata el orden y viaja en el cursor
+    pub id_column: &'static str,
+    pub sort_fields: &'static [SortField],
+    /// Orden por defecto, con la misma sintaxis que `?sort=`
+    pub default_sort: &'static str,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    /// Orden con el que se generó: un cursor no sirve para otro orden
+    s: String,
+    v: String,
+    id: i32,
+}
+
+fn invalid(msg: impl Into<String>) -> anyhow::Error {
+    InvalidListQuery(msg.into()).into()
+}
+
+fn resolve_sort<'a>(spec: &'a ListQuery, sort: &str) -> Result<(&'a SortField, bool)> {
+    let (name, desc) = match sort.strip_prefix('-') {
+        Some(name) => (name, true),
+        None => (sort, false),
+    };
+    let field = spec.sort_fields.iter().find(|f| f.name == name).ok_or_else(|| {
+        let allowed: Vec<&str> = spec.sort_fields.iter().map(|f| f.name).collect();
+        invalid(format!("No se puede ordenar por '{}'. Campos permitidos: {}", name, allowed.join(", ")))
+    })?;
+    Ok((field, desc))
+}
+
+/// El valor del cursor se convierte a `sql_type` en la consulta; se valida antes
+/// para que un cursor alterado responda 400 y no un error de la base
+fn is_valid_sort_value(value: &str, sql_type: &str) -> bool {
+    match sql_type {
+        "int" => value.parse::<i32>().is_ok(),
+        "timestamp" => NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").is_ok(),
+        "date" => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        "text" => !value.contains('\0'),
+        _ => false,
+    }
+}
+
+fn decode_cursor(raw: &str, sort: &str, field: &SortField) -> Result<Cursor> {
+    let cursor: Cursor = B64
+        .decode(raw)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| invalid("Cursor inválido"))?;
+    if cursor.s != sort {
+        return Err(invalid("El cursor fue generado con otro orden"));
+    }
+    if !is_valid_sort_value(&cursor.v, field.sql_type) {
+        return Err(invalid("Cursor inválido"));
+    }
+    Ok(cursor)
+}
+
+fn encode_cursor(cursor: &Cursor) -> Result<String> {
+    Ok(B64.encode(serde_json::to_vec(cursor)?))
+}
+
+/// Ejecuta un listado con filtros, orden permitido y paginación por offset o cursor.
+/// `filters` agrega condiciones " AND ..." y se aplica tanto al conteo como a la página.
+pub async fn fetch_page<T>(
+    pool: &PgPool,
+    spec: &ListQuery,
+    params: &PageParams,
+    filters: impl Fn(&mut QueryBuilder<'_, Postgres>),
+) -> Result<Page<T>>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let sort = params.sort.as_deref().unwrap_or(spec.default_sort);
+    let (field, desc) = resolve_sort(spec, sort)?;
+    let limit = params.limit();
+
+    let cursor = match &params.cursor {
+        Some(_) if params.offset.is_some() => return Err(invalid("cursor y offset no se pueden combinar")),
+        Some(raw) => Some(decode_cursor(raw, sort, field)?),
+        None => None,
+    };
+    let offset = params.offset.unwrap_or(0);
+
+    // Total sin paginar, con los mismos filtros
+    let mut count = QueryBuilder::new(format!("SELECT COUNT(*) FROM {} WHERE {}", spec.from, spec.base_filter));
+    filters(&mut count);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+    let mut query = QueryBuilder::new(format!(
+        "SELECT {}, ({})::text AS _sort_value, {} AS _sort_id FROM {} WHERE {}",
+        spec.columns, field.column, spec.id_column, spec.from, spec.base_filter
+    ));
+    filters(&mut query);
+
+    if let Some(c) = &cursor {
+        query.push(format!(" AND ({}, {}) {} (", field.column, spec.id_column, if desc { "<" } else { ">" }));
+        query.push_bind(c.v.clone());
+        query.push(format!("::{}, ", field.sql_type));
+        query.push_bind(c.id);
+        query.push(")");
+    }
+
+    let direction = if desc { "DESC" } else { "ASC" };
+    query.push(format!(" ORDER BY {} {dir}, {} {dir} LIMIT ", field.column, spec.id_column, dir = direction));
+    // Una fila extra indica si hay página siguiente
+    query.push_bind(limit + 1);
+    if cursor.is_none() {
+        query.push(" OFFSET ");
+        query.push_bind(offset);
+    }
+
+    let mut rows = query.build().fetch_all(pool).await?;
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    let next_cursor = match rows.last() {
+        Some(last) if has_more => Some(encode_cursor(&Cursor {
+            s: sort.to_string(),
+            v: last.try_get("_sort_value")?,
+            id: last.try_get("_sort_id")?,
+        })?),
+        _ => None,
+    };
+
+    let data = rows.iter().map(T::from_row).collect::<Result<Vec<T>, _>>()?;
+
+    Ok(Page {
+        data,
+        total,
+        limit,
+        offset: cursor.is_none().then_some(offset),
+        next_cursor,
+    })
+}
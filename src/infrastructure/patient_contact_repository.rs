@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+
+use crate::domain::patient_contact::{CreatePatientContact, PatientContact, UpdatePatientContact};
+use crate::helpers::errors::on_unique_violation;
+
+// Si el contacto también es paciente, el nombre, teléfono y correo salen de su expediente
+const SELECT_CONTACT: &str = "
+    SELECT c.id_contact, c.id_patient, c.id_related_patient,
+           COALESCE(c.contact_name, concat_ws(' ', r.first_name, r.first_lastname, r.second_lastname)) AS contact_name,
+           COALESCE(c.contact_phone, r.phone) AS contact_phone,
+           COALESCE(c.contact_email, r.email) AS contact_email,
+           c.relationship, c.is_legal_guardian, c.is_emergency_contact, c.priority,
+           c.created_at, c.updated_at, c.deleted_at
+    FROM patient_contacts c
+    LEFT JOIN patients r ON r.id_patient = c.id_related_patient";
+
+#[async_trait]
+pub trait PatientContactRepository: Send + Sync + 'static {
+    /// Contactos del paciente en orden de prioridad
+    async fn get_by_patient(&self, id_patient: i32) -> Result<Vec<PatientContact>>;
+    async fn get_by_id(&self, id_patient: i32, id_contact: i32) -> Result<Option<PatientContact>>;
+    /// None si el paciente no existe
+    async fn create(&self, id_patient: i32, data: CreatePatientContact) -> Result<Option<PatientContact>>;
+    async fn update(&self, id_patient: i32, id_contact: i32, data: UpdatePatientContact) -> Result<Option<PatientContact>>;
+    async fn delete(&self, id_patient: i32, id_contact: i32) -> Result<Option<PatientContact>>;
+    async fn patient_exists(&self, id_patient: i32) -> Result<bool>;
+    /// El usuario es el propio paciente o su tutor legal
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool>;
+}
+
+pub struct PgPatientContactRepository {
+    pool: PgPool,
+}
+
+impl PgPatientContactRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PatientContactRepository for PgPatientContactRepository {
+    async fn get_by_patient(&self, id_patient: i32) -> Result<Vec<PatientContact>> {
+        let result = sqlx::query_as::<_, PatientContact>(&format!(
+            "{} WHERE c.id_patient = $1 AND c.deleted_at IS NULL ORDER BY c.priority, c.id_contact",
+            SELECT_CONTACT
+        ))
+        .bind(id_patient)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_by_id(&self, id_patient: i32, id_contact: i32) -> Result<Option<PatientContact>> {
+        let result = sqlx::query_as::<_, PatientContact>(&format!(
+            "{} WHERE c.id_patient = $1 AND c.id_contact = $2 AND c.deleted_at IS NULL",
+            SELECT_CONTACT
+        ))
+        .bind(id_patient)
+        .bind(id_contact)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn create(&self, id_patient: i32, data: CreatePatientContact) -> Result<Option<PatientContact>> {
+        let id_contact: Option<i32> = sqlx::query_scalar(
+            "INSERT INTO patient_contacts
+            (id_patient, id_related_patient, contact_name, contact_phone, contact_email,
+             relationship, is_legal_guardian, is_emergency_contact, priority)
+            SELECT $1,$2,$3,$4,$5,$6,$7,$8,$9
+            WHERE EXISTS (SELECT 1 FROM patients WHERE id_patient = $1 AND deleted_at IS NULL)
+            RETURNING id_contact"
+        )
+        .bind(id_patient)
+        .bind(data.id_related_patient)
+        .bind(data.contact_name)
+        .bind(data.contact_phone)
+        .bind(data.contact_email)
+        .bind(data.relationship)
+        .bind(data.is_legal_guardian.unwrap_or(false))
+        .bind(data.is_emergency_contact.unwrap_or(true))
+        .bind(data.priority.unwrap_or(0))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| on_unique_violation(e, "El contacto ya está registrado para este paciente"))?;
+
+        match id_contact {
+            Some(id_contact) => self.get_by_id(id_patient, id_contact).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn update(&self, id_patient: i32, id_contact: i32, data: UpdatePatientContact) -> Result<Option<PatientContact>> {
+        let updated: Option<i32> = sqlx::query_scalar(
+            "UPDATE patient_contacts SET
+                contact_name = COALESCE($3, contact_name),
+                contact_phone = COALESCE($4, contact_phone),
+                contact_email = COALESCE($5, contact_email),
+                relationship = COALESCE($6, relationship),
+                is_legal_guardian = COALESCE($7, is_legal_guardian),
+                is_emergency_contact = COALESCE($8, is_emergency_contact),
+                priority = COALESCE($9, priority),
+                updated_at = NOW()
+            WHERE id_patient = $1 AND id_contact = $2 AND deleted_at IS NULL
+            RETURNING id_contact"
+        )
+        .bind(id_patient)
+        .bind(id_contact)
+        .bind(data.contact_name)
+        .bind(data.contact_phone)
+        .bind(data.contact_email)
+        .bind(data.relationship)
+        .bind(data.is_legal_guardian)
+        .bind(data.is_emergency_contact)
+        .bind(data.priority)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match updated {
+            Some(id_contact) => self.get_by_id(id_patient, id_contact).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn delete(&self, id_patient: i32, id_contact: i32) -> Result<Option<PatientContact>> {
+        let Some(mut contact) = self.get_by_id(id_patient, id_contact).await? else {
+            return Ok(None);
+        };
+        contact.deleted_at = sqlx::query_scalar(
+            "UPDATE patient_contacts SET deleted_at = NOW() WHERE id_contact = $1 RETURNING deleted_at"
+        )
+        .bind(id_contact)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(Some(contact))
+    }
+
+    async fn patient_exists(&self, id_patient: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM patients WHERE id_patient = $1 AND deleted_at IS NULL)"
+        )
+        .bind(id_patient)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar("SELECT can_act_for_patient($1, $2)")
+            .bind(id_user)
+            .bind(id_patient)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(result)
+    }
+}
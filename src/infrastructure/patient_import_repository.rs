@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use sqlx::{types::Json, PgPool};
+use anyhow::Result;
+
+use crate::domain::patient_import::{CreatePatientImport, PatientImport};
+
+#[async_trait]
+pub trait PatientImportRepository: Send + Sync + 'static {
+    async fn create(&self, data: CreatePatientImport) -> Result<PatientImport>;
+    async fn get_by_id(&self, id: i32) -> Result<Option<PatientImport>>;
+}
+
+pub struct PgPatientImportRepository {
+    pool: PgPool,
+}
+
+impl PgPatientImportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PatientImportRepository for PgPatientImportRepository {
+    async fn create(&self, data: CreatePatientImport) -> Result<PatientImport> {
+        let result = sqlx::query_as::<_, PatientImport>(
+            "INSERT INTO patient_imports
+            (file_name, dry_run, total_rows, valid_rows, imported_rows, failed_rows, errors, performed_by)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+            RETURNING *"
+        )
+        .bind(data.file_name)
+        .bind(data.dry_run)
+        .bind(data.total_rows)
+        .bind(data.valid_rows)
+        .bind(data.imported_rows)
+        .bind(data.failed_rows)
+        .bind(Json(&data.errors))
+        .bind(data.performed_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn get_by_id(&self, id: i32) -> Result<Option<PatientImport>> {
+        let result = sqlx::query_as::<_, PatientImport>(
+            "SELECT * FROM patient_imports WHERE id_import = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+}
@@ -0,0 +1,279 @@
+use async_trait::async_trait;
+use sqlx::{types::Json, PgPool, Postgres, Transaction};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::domain::audit_log::{AuditLog, ACTION_MERGE, ACTION_UNMERGE};
+use crate::domain::patient_merge::{
+    MergeRecord, PatientMerge, CONTACTS_MOVED, CONTACTS_RELATED_MOVED, CONTACTS_REMOVED, MERGED_TABLES,
+};
+use crate::helpers::errors::on_unique_violation;
+
+#[async_trait]
+pub trait PatientMergeRepository: Send + Sync + 'static {
+    /// Mueve los registros del duplicado al paciente que se conserva y elimina
+    /// (soft delete) el duplicado. None si alguno de los dos no existe.
+    async fn merge(&self, id_survivor: i32, id_duplicate: i32, performed_by: i32) -> Result<Option<PatientMerge>>;
+    /// Revierte una fusión a partir de su entrada en audit_logs.
+    /// None si la entrada no existe o no es una fusión.
+    async fn unmerge(&self, id_audit: i32, performed_by: i32) -> Result<Option<PatientMerge>>;
+}
+
+pub struct PgPatientMergeRepository {
+    pool: PgPool,
+}
+
+impl PgPatientMergeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Cambia el paciente de los registros indicados (todos si `ids` es None)
+/// y devuelve los ids que se movieron en cada tabla
+async fn move_records(
+    tx: &mut Transaction<'_, Postgres>,
+    from: i32,
+    to: i32,
+    ids: Option<&BTreeMap<String, Vec<i32>>>,
+) -> Result<BTreeMap<String, Vec<i32>>> {
+    let mut moved = BTreeMap::new();
+
+    for (table, pk) in MERGED_TABLES {
+        let only: Option<Vec<i32>> = ids.map(|m| m.get(*table).cloned().unwrap_or_default());
+        let rows: Vec<i32> = sqlx::query_scalar(&format!(
+            "UPDATE {table} SET id_patient = $1
+             WHERE id_patient = $2 AND ($3::int[] IS NULL OR {pk} = ANY($3))
+             RETURNING {pk}"
+        ))
+        .bind(to)
+        .bind(from)
+        .bind(only)
+        .fetch_all(&mut **tx)
+        .await?;
+        moved.insert(table.to_string(), rows);
+    }
+
+    Ok(moved)
+}
+
+/// Pasa los contactos del duplicado al paciente que se conserva, en ambas
+/// columnas. Antes elimina (soft delete) los vínculos entre los dos pacientes
+/// y, si ambos tenían el mismo contacto, deja uno solo: el de tutor legal o,
+/// a igualdad, el del paciente que se conserva.
+async fn merge_contacts(
+    tx: &mut Transaction<'_, Postgres>,
+    survivor: i32,
+    duplicate: i32,
+    moved: &mut BTreeMap<String, Vec<i32>>,
+) -> Result<()> {
+    let removed: Vec<i32> = sqlx::query_scalar(
+        "WITH linked AS (
+             SELECT id_contact,
+                    id_patient IN ($1, $2) AND id_related_patient IN ($1, $2) AS self_link,
+                    row_number() OVER (
+                        PARTITION BY id_patient IN ($1, $2),
+                                     CASE WHEN id_patient IN ($1, $2) THEN id_related_patient ELSE id_patient END
+                        ORDER BY is_legal_guardian DESC, (id_patient = $1 OR id_related_patient = $1) DESC, id_contact
+                    ) AS rank
+             FROM patient_contacts
+             WHERE deleted_at IS NULL AND id_related_patient IS NOT NULL
+               AND (id_patient IN ($1, $2) OR id_related_patient IN ($1, $2))
+         )
+         UPDATE patient_contacts c SET deleted_at = NOW(), updated_at = NOW()
+         FROM linked l
+         WHERE c.id_contact = l.id_contact AND (l.self_link OR l.rank > 1)
+         RETURNING c.id_contact",
+    )
+    .bind(survivor)
+    .bind(duplicate)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    // Los vínculos entre ambos no se mueven: quedarían apuntando al mismo paciente
+    let owned: Vec<i32> = sqlx::query_scalar(
+        "UPDATE patient_contacts SET id_patient = $1
+         WHERE id_patient = $2 AND id_related_patient IS DISTINCT FROM $1
+         RETURNING id_contact",
+    )
+    .bind(survivor)
+    .bind(duplicate)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let related: Vec<i32> = sqlx::query_scalar(
+        "UPDATE patient_contacts SET id_related_patient = $1
+         WHERE id_related_patient = $2 AND id_patient <> $1
+         RETURNING id_contact",
+    )
+    .bind(survivor)
+    .bind(duplicate)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    moved.insert(CONTACTS_MOVED.to_string(), owned);
+    moved.insert(CONTACTS_RELATED_MOVED.to_string(), related);
+    moved.insert(CONTACTS_REMOVED.to_string(), removed);
+    Ok(())
+}
+
+/// Deshace `merge_contacts` con los ids guardados en la fusión
+async fn unmerge_contacts(
+    tx: &mut Transaction<'_, Postgres>,
+    survivor: i32,
+    duplicate: i32,
+    original: &BTreeMap<String, Vec<i32>>,
+    moved: &mut BTreeMap<String, Vec<i32>>,
+) -> Result<()> {
+    let ids = |key: &str| original.get(key).cloned().unwrap_or_default();
+
+    let owned: Vec<i32> = sqlx::query_scalar(
+        "UPDATE patient_contacts SET id_patient = $1
+         WHERE id_patient = $2 AND id_contact = ANY($3)
+         RETURNING id_contact",
+    )
+    .bind(duplicate)
+    .bind(survivor)
+    .bind(ids(CONTACTS_MOVED))
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let related: Vec<i32> = sqlx::query_scalar(
+        "UPDATE patient_contacts SET id_related_patient = $1
+         WHERE id_related_patient = $2 AND id_contact = ANY($3)
+         RETURNING id_contact",
+    )
+    .bind(duplicate)
+    .bind(survivor)
+    .bind(ids(CONTACTS_RELATED_MOVED))
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let restored: Vec<i32> = sqlx::query_scalar(
+        "UPDATE patient_contacts SET deleted_at = NULL, updated_at = NOW()
+         WHERE deleted_at IS NOT NULL AND id_contact = ANY($1)
+         RETURNING id_contact",
+    )
+    .bind(ids(CONTACTS_REMOVED))
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| on_unique_violation(e, "Un contacto restaurado ya existe de nuevo en el expediente"))?;
+
+    moved.insert(CONTACTS_MOVED.to_string(), owned);
+    moved.insert(CONTACTS_RELATED_MOVED.to_string(), related);
+    moved.insert(CONTACTS_REMOVED.to_string(), restored);
+    Ok(())
+}
+
+#[async_trait]
+impl PatientMergeRepository for PgPatientMergeRepository {
+    async fn merge(&self, id_survivor: i32, id_duplicate: i32, performed_by: i32) -> Result<Option<PatientMerge>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Bloquear ambos expedientes en orden de id para evitar deadlocks
+        let locked: Vec<i32> = sqlx::query_scalar(
+            "SELECT id_patient FROM patients
+             WHERE id_patient IN ($1, $2) AND deleted_at IS NULL
+             ORDER BY id_patient
+             FOR UPDATE",
+        )
+        .bind(id_survivor)
+        .bind(id_duplicate)
+        .fetch_all(&mut *tx)
+        .await?;
+        if locked.len() != 2 {
+            return Ok(None);
+        }
+
+        let mut moved = move_records(&mut tx, id_duplicate, id_survivor, None).await?;
+        merge_contacts(&mut tx, id_survivor, id_duplicate, &mut moved).await?;
+
+        sqlx::query("UPDATE patients SET deleted_at = NOW(), updated_at = NOW() WHERE id_patient = $1")
+            .bind(id_duplicate)
+            .execute(&mut *tx)
+            .await?;
+
+        let record = MergeRecord { id_survivor, id_duplicate, moved };
+        let audit = sqlx::query_as::<_, AuditLog>(
+            "INSERT INTO audit_logs (table_name, record_id, action, old_data, new_data, performed_by)
+             VALUES ('patients', $1, $2, $3, jsonb_build_object('merged_into', $4::int), $5)
+             RETURNING *",
+        )
+        .bind(id_duplicate)
+        .bind(ACTION_MERGE)
+        .bind(Json(&record))
+        .bind(id_survivor)
+        .bind(performed_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(PatientMerge {
+            id_audit: audit.id_audit,
+            action: audit.action,
+            record,
+            performed_at: audit.performed_at,
+        }))
+    }
+
+    async fn unmerge(&self, id_audit: i32, performed_by: i32) -> Result<Option<PatientMerge>> {
+        let mut tx = self.pool.begin().await?;
+
+        let merge = sqlx::query_as::<_, AuditLog>(
+            "SELECT * FROM audit_logs WHERE id_audit = $1 AND table_name = 'patients' AND action = $2",
+        )
+        .bind(id_audit)
+        .bind(ACTION_MERGE)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(merge) = merge else {
+            return Ok(None);
+        };
+        let original: MergeRecord = serde_json::from_value(merge.old_data.unwrap_or_default())?;
+
+        // El índice único sobre la fusión revertida impide revertirla dos veces
+        let audit = sqlx::query_as::<_, AuditLog>(
+            "INSERT INTO audit_logs (table_name, record_id, action, old_data, performed_by)
+             VALUES ('patients', $1, $2, jsonb_build_object('id_audit', $3::int), $4)
+             RETURNING *",
+        )
+        .bind(original.id_duplicate)
+        .bind(ACTION_UNMERGE)
+        .bind(id_audit)
+        .bind(performed_by)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| on_unique_violation(e, "La fusión ya fue revertida"))?;
+
+        // Solo vuelven los registros que siguen en el expediente conservado
+        let mut moved =
+            move_records(&mut tx, original.id_survivor, original.id_duplicate, Some(&original.moved)).await?;
+        unmerge_contacts(&mut tx, original.id_survivor, original.id_duplicate, &original.moved, &mut moved).await?;
+
+        sqlx::query("UPDATE patients SET deleted_at = NULL, updated_at = NOW() WHERE id_patient = $1")
+            .bind(original.id_duplicate)
+            .execute(&mut *tx)
+            .await?;
+
+        let record = MergeRecord {
+            id_survivor: original.id_survivor,
+            id_duplicate: original.id_duplicate,
+            moved,
+        };
+        sqlx::query("UPDATE audit_logs SET new_data = $1 WHERE id_audit = $2")
+            .bind(Json(&record))
+            .bind(audit.id_audit)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(PatientMerge {
+            id_audit: audit.id_audit,
+            action: audit.action,
+            record,
+            performed_at: audit.performed_at,
+        }))
+    }
+}
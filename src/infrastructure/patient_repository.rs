@@ -1,82 +1,209 @@
-use crate::domain::patient::{CreatePatient, Patient, UpdatePatient};
+use crate::domain::audit_log::{ACTION_CONFIRM_IDENTITY, ACTION_STATUS_CHANGE};
+use crate::domain::patient::{
+    ChangePatientStatus, CreatePatient, Patient, PatientFilter, PatientSearchHit, PatientStatusChange, UpdatePatient,
+    IDENTITY_CEDULA, STATUS_ACTIVE, STATUS_DECEASED, STATUS_PENDING_VERIFICATION,
+};
+use crate::domain::pagination::{InvalidListQuery, Page, PageParams};
+use crate::domain::patient_merge::DuplicateCandidate;
+use crate::helpers::crypto::{DataKey, Keyring};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
 use anyhow::Result;
 use async_trait::async_trait;
-use argon2::{Argon2, PasswordHasher};
-use password_hash::{SaltString, rand_core::OsRng};
-use sqlx::{PgPool, Postgres, Transaction};
+use chrono::NaiveDateTime;
+use sqlx::{Acquire, FromRow, PgPool, Postgres, Transaction};
+use std::sync::Arc;
+
+// Columnas cifradas con la DEK del paciente (el nombre va en el AAD del cifrado)
+pub const ALLERGIES: &str = "patients.allergies";
+const CURRENT_MEDICATIONS: &str = "patients.current_medications";
+const MEDICAL_BACKGROUND: &str = "patients.medical_background";
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_patient, id_user, identity_number, identity_type, \
+              first_name, second_name, first_lastname, second_lastname, \
+              gender, birthdate, blood_type, phone, email, address, \
+              emergency_contact_name, emergency_contact_phone, \
+              allergies, current_medications, medical_background, \
+              priority, status, status_date, status_reason, \
+              created_at, updated_at, deleted_at, data_key",
+    from: "patients",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_patient",
+    sort_fields: &[
+        SortField { name: "id_patient", column: "id_patient", sql_type: "int" },
+        SortField { name: "first_name", column: "first_name", sql_type: "text" },
+        SortField { name: "first_lastname", column: "first_lastname", sql_type: "text" },
+        SortField { name: "birthdate", column: "birthdate", sql_type: "date" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "id_patient",
+};
+
+// Términos de búsqueda normalizados igual que las columnas indexadas (ver query.sql)
+const SEARCH_TERMS: &str = "
+    WITH t AS (
+        SELECT immutable_unaccent(lower($1)) AS name,
+               normalize_identity($1) AS identity,
+               phone_digits($1) AS digits
+    )";
+
+// Coincidencia por nombre (trigramas por palabra), fragmento de cédula o de teléfono.
+// Los fragmentos cortos de cédula/teléfono se ignoran para no devolver medio padrón.
+const SEARCH_MATCH: &str = "
+    p.deleted_at IS NULL AND (
+        t.name <% patient_search_name(p.first_name, p.first_lastname, p.second_lastname)
+        OR (length(t.identity) >= 4 AND normalize_identity(p.identity_number) LIKE '%' || t.identity || '%')
+        OR (length(t.digits) >= 4 AND phone_digits(p.phone) LIKE '%' || t.digits || '%')
+    )";
+
+// Puntaje: cédula exacta > prefijo de cédula > teléfono > similitud del nombre
+const SEARCH_SCORE: &str = "
+    GREATEST(
+        word_similarity(t.name, patient_search_name(p.first_name, p.first_lastname, p.second_lastname)),
+        CASE WHEN length(t.identity) >= 4 AND normalize_identity(p.identity_number) = t.identity THEN 1.0
+             WHEN length(t.identity) >= 4 AND normalize_identity(p.identity_number) LIKE t.identity || '%' THEN 0.95
+             WHEN length(t.identity) >= 4 AND normalize_identity(p.identity_number) LIKE '%' || t.identity || '%' THEN 0.85
+             ELSE 0 END,
+        CASE WHEN length(t.digits) >= 4 AND phone_digits(p.phone) LIKE '%' || t.digits || '%' THEN 0.9
+             ELSE 0 END
+    )::real";
+
+// Candidatos a duplicado: nombre, cédula o teléfono parecidos (usan los índices
+// de búsqueda); el puntaje suma además la fecha de nacimiento. Las cédulas
+// comparten muchos trigramas, así que se descartan los puntajes bajos ($3).
+const DUPLICATES: &str = "
+    WITH t AS (
+        SELECT patient_search_name(first_name, first_lastname, second_lastname) AS name,
+               normalize_identity(identity_number) AS identity,
+               phone_digits(phone) AS digits,
+               birthdate
+        FROM patients
+        WHERE id_patient = $1 AND deleted_at IS NULL
+    )
+    SELECT * FROM (
+    SELECT p.*,
+           (0.45 * s.name_similarity
+            + 0.25 * s.same_birthdate::int
+            + 0.15 * s.same_phone::int
+            + 0.15 * s.identity_similarity)::real AS score,
+           s.name_similarity, s.same_birthdate, s.same_phone, s.identity_similarity
+    FROM patients p
+    CROSS JOIN t
+    CROSS JOIN LATERAL (
+        SELECT similarity(t.name, patient_search_name(p.first_name, p.first_lastname, p.second_lastname)) AS name_similarity,
+               p.birthdate = t.birthdate AS same_birthdate,
+               COALESCE(length(t.digits) >= 7 AND phone_digits(p.phone) = t.digits, FALSE) AS same_phone,
+               similarity(t.identity, normalize_identity(p.identity_number)) AS identity_similarity
+    ) s
+    WHERE p.id_patient <> $1
+      AND p.deleted_at IS NULL
+      AND (
+          t.name % patient_search_name(p.first_name, p.first_lastname, p.second_lastname)
+          OR t.identity % normalize_identity(p.identity_number)
+          OR (length(t.digits) >= 7 AND phone_digits(p.phone) = t.digits)
+      )
+    ) c
+    WHERE c.score >= $3
+    ORDER BY c.score DESC, c.id_patient
+    LIMIT $2";
+
+/// Máximo de candidatos a duplicado que se devuelven
+const MAX_DUPLICATES: i64 = 20;
+const MIN_DUPLICATE_SCORE: f32 = 0.3;
 
 #[async_trait]
 pub trait PatientRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<Patient>>;
+    async fn get_all(&self, filter: &PatientFilter, page: &PageParams) -> Result<Page<Patient>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Patient>>;
-    async fn create(&self, data: CreatePatient, raw_password: &str) -> Result<Patient>;
+    /// Expediente del usuario autenticado (portal del paciente)
+    async fn get_by_user(&self, id_user: i32) -> Result<Option<Patient>>;
+    /// Búsqueda por nombre sin acentos, cédula o teléfono, ordenada por relevancia
+    async fn search(&self, term: &str, page: &PageParams) -> Result<Page<PatientSearchHit>>;
+    /// Posibles duplicados del paciente, del más al menos parecido.
+    /// None si el paciente no existe.
+    async fn find_duplicates(&self, id: i32) -> Result<Option<Vec<DuplicateCandidate>>>;
+    /// El usuario es el propio paciente o su tutor legal
+    async fn can_act_for(&self, id: i32, id_user: i32) -> Result<bool>;
+    /// Pacientes de los que el usuario es tutor legal
+    async fn get_dependents(&self, id_user: i32) -> Result<Vec<Patient>>;
+    /// Pasa un paciente autorregistrado a activo y lo registra en audit_logs.
+    /// None si no existe o no está pendiente de verificación.
+    async fn confirm_identity(&self, id: i32, performed_by: i32) -> Result<Option<Patient>>;
+    /// Cambia el estado si sigue siendo `from` y lo registra en audit_logs. Al pasar a
+    /// fallecido cancela sus citas futuras y borra sus dispositivos de notificación.
+    /// None si no existe o el estado ya cambió. Un estado NULL cuenta como `active`;
+    /// `now` es la hora local con la que se decide qué citas son futuras.
+    async fn change_status(
+        &self,
+        id: i32,
+        from: &str,
+        data: &ChangePatientStatus,
+        performed_by: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<PatientStatusChange>>;
+    /// Crea el paciente y su usuario; `password_hash` ya viene hasheada
+    async fn create(&self, data: CreatePatient, password_hash: &str) -> Result<Patient>;
+    /// Crea varios pacientes en una sola transacción; devuelve el resultado de cada fila
+    async fn create_batch(&self, rows: Vec<(CreatePatient, String)>) -> Result<Vec<Result<Patient>>>;
+    /// Números de identidad, emails o usuarios de la lista que ya existen
+    async fn find_taken(&self, values: &[String]) -> Result<Vec<String>>;
     async fn update(&self, id: i32, data: UpdatePatient) -> Result<Option<Patient>>;
     async fn delete(&self, id: i32) -> Result<Option<Patient>>;
+    /// Cifra filas antiguas y re-envuelve DEKs con la llave maestra activa.
+    /// Devuelve cuántas filas se actualizaron.
+    async fn rotate_keys(&self) -> Result<u64>;
+}
+
+#[derive(FromRow)]
+struct PatientSecrets {
+    id_patient: i32,
+    data_key: Option<String>,
+    allergies: Option<String>,
+    current_medications: Option<String>,
+    medical_background: Option<String>,
 }
 
 pub struct PgPatientRepository {
     pool: PgPool,
+    keyring: Arc<Keyring>,
 }
 
 impl PgPatientRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, keyring: Arc<Keyring>) -> Self {
+        Self { pool, keyring }
     }
-}
 
-#[async_trait]
-impl PatientRepository for PgPatientRepository {
-    async fn get_all(&self) -> Result<Vec<Patient>> {
-        let result: Vec<Patient> = sqlx::query_as::<_, Patient>(
-            r#"
-            SELECT id_patient, id_user, identity_number,
-                   first_name, second_name, first_lastname, second_lastname,
-                   gender, birthdate, blood_type,
-                   phone, email, address,
-                   emergency_contact_name, emergency_contact_phone,
-                   allergies, current_medications, medical_background,
-                   priority, status,
-                   created_at, updated_at, deleted_at
-            FROM patients
-            WHERE deleted_at IS NULL
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(result)
+    /// Descifra las columnas sensibles; las filas sin DEK siguen en claro
+    fn decrypt(&self, mut patient: Patient) -> Result<Patient> {
+        if let Some(wrapped) = &patient.data_key {
+            let dek = self.keyring.unwrap_data_key(wrapped)?;
+            patient.allergies = dek.decrypt_opt(ALLERGIES, patient.allergies.as_deref())?;
+            patient.current_medications =
+                dek.decrypt_opt(CURRENT_MEDICATIONS, patient.current_medications.as_deref())?;
+            patient.medical_background =
+                dek.decrypt_opt(MEDICAL_BACKGROUND, patient.medical_background.as_deref())?;
+        }
+        Ok(patient)
     }
 
-    async fn get_by_id(&self, id: i32) -> Result<Option<Patient>> {
-        let result: Option<Patient> = sqlx::query_as::<_, Patient>(
-            r#"
-            SELECT id_patient, id_user, identity_number,
-                   first_name, second_name, first_lastname, second_lastname,
-                   gender, birthdate, blood_type,
-                   phone, email, address,
-                   emergency_contact_name, emergency_contact_phone,
-                   allergies, current_medications, medical_background,
-                   priority, status,
-                   created_at, updated_at, deleted_at
-            FROM patients
-            WHERE id_patient = $1 AND deleted_at IS NULL
-            "#,
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(result)
+    fn decrypt_opt(&self, patient: Option<Patient>) -> Result<Option<Patient>> {
+        patient.map(|p| self.decrypt(p)).transpose()
     }
 
-    async fn create(&self, data: CreatePatient, raw_password: &str) -> Result<Patient> {
-        let mut tx = self.pool.begin().await?;
+    /// Inserta el usuario y el paciente dentro de la transacción recibida
+    async fn insert(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        data: &CreatePatient,
+        password_hash: &str,
+    ) -> Result<Patient> {
+        let (dek, wrapped_key) = self.keyring.new_data_key()?;
 
         // Crear usuario
         let username = data
             .email
             .clone()
             .unwrap_or_else(|| data.identity_number.clone());
-        let password_hash = raw_password;
-
         let user_id: i32 = sqlx::query_scalar!(
             r#"
         INSERT INTO users (username, password_hash, role)
@@ -86,27 +213,28 @@ impl PatientRepository for PgPatientRepository {
             username,
             password_hash
         )
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut **tx)
         .await?;
 
         let patient = sqlx::query_as::<_, Patient>(
             r#"
             INSERT INTO patients (
-                id_user, identity_number,
+                id_user, identity_number, identity_type,
                 first_name, second_name, first_lastname, second_lastname,
                 gender, birthdate, blood_type,
                 phone, email, address,
                 emergency_contact_name, emergency_contact_phone,
                 allergies, current_medications, medical_background,
-                priority, status
+                priority, status, data_key
             )
             VALUES (
-                $1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19
+                $1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20,$21
             )
             RETURNING
                 id_patient,
                 id_user,
                 identity_number,
+                identity_type,
                 first_name,
                 second_name,
                 first_lastname,
@@ -124,13 +252,17 @@ impl PatientRepository for PgPatientRepository {
                 medical_background,
                 priority,
                 status,
+                status_date,
+                status_reason,
                 created_at,
                 updated_at,
-                deleted_at
+                deleted_at,
+                data_key
             "#
         )
         .bind(user_id)
         .bind(&data.identity_number)
+        .bind(data.identity_type.as_deref().unwrap_or(IDENTITY_CEDULA))
         .bind(&data.first_name)
         .bind(&data.second_name)
         .bind(&data.first_lastname)
@@ -143,20 +275,376 @@ impl PatientRepository for PgPatientRepository {
         .bind(&data.address)
         .bind(&data.emergency_contact_name)
         .bind(&data.emergency_contact_phone)
-        .bind(&data.allergies)
-        .bind(&data.current_medications)
-        .bind(&data.medical_background)
+        .bind(dek.encrypt_opt(ALLERGIES, data.allergies.as_deref())?)
+        .bind(dek.encrypt_opt(CURRENT_MEDICATIONS, data.current_medications.as_deref())?)
+        .bind(dek.encrypt_opt(MEDICAL_BACKGROUND, data.medical_background.as_deref())?)
         .bind(data.priority.unwrap_or(0))
-        .bind(data.status.clone().unwrap_or_else(|| "active".to_string()))
-        .fetch_one(&mut *tx)
+        .bind(data.status.as_deref().unwrap_or(STATUS_ACTIVE))
+        .bind(&wrapped_key)
+        .fetch_one(&mut **tx)
         .await?;
 
-        tx.commit().await?;
         Ok(patient)
+    }
+}
+
+fn encrypt_secrets(dek: &DataKey, s: &PatientSecrets) -> Result<(Option<String>, Option<String>, Option<String>)> {
+    Ok((
+        dek.encrypt_opt(ALLERGIES, s.allergies.as_deref())?,
+        dek.encrypt_opt(CURRENT_MEDICATIONS, s.current_medications.as_deref())?,
+        dek.encrypt_opt(MEDICAL_BACKGROUND, s.medical_background.as_deref())?,
+    ))
+}
+
+#[async_trait]
+impl PatientRepository for PgPatientRepository {
+    async fn get_all(&self, filter: &PatientFilter, page: &PageParams) -> Result<Page<Patient>> {
+        let mut result: Page<Patient> = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(status) = &filter.status {
+                q.push(" AND status = ").push_bind(status.clone());
+            }
+            if let Some(gender) = &filter.gender {
+                q.push(" AND gender = ").push_bind(gender.clone());
+            }
+        })
+        .await?;
+        result.data = result.data.into_iter().map(|p| self.decrypt(p)).collect::<Result<_>>()?;
+        Ok(result)
+    }
+
+    async fn search(&self, term: &str, page: &PageParams) -> Result<Page<PatientSearchHit>> {
+        // El orden lo da la relevancia: solo se pagina por offset
+        if page.cursor.is_some() || page.sort.is_some() {
+            return Err(InvalidListQuery("La búsqueda solo admite limit y offset".to_string()).into());
+        }
+        let limit = page.limit();
+        let offset = page.offset.unwrap_or(0);
 
+        let total: i64 = sqlx::query_scalar(&format!(
+            "{} SELECT COUNT(*) FROM patients p, t WHERE {}",
+            SEARCH_TERMS, SEARCH_MATCH
+        ))
+        .bind(term)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let hits: Vec<PatientSearchHit> = sqlx::query_as(&format!(
+            "{} SELECT p.*, {} AS score FROM patients p, t WHERE {}
+             ORDER BY score DESC, p.first_lastname, p.id_patient
+             LIMIT $2 OFFSET $3",
+            SEARCH_TERMS, SEARCH_SCORE, SEARCH_MATCH
+        ))
+        .bind(term)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let data = hits
+            .into_iter()
+            .map(|hit| {
+                Ok(PatientSearchHit {
+                    patient: self.decrypt(hit.patient)?,
+                    score: hit.score,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Page {
+            data,
+            total,
+            limit,
+            offset: Some(offset),
+            next_cursor: None,
+        })
+    }
+
+    async fn find_duplicates(&self, id: i32) -> Result<Option<Vec<DuplicateCandidate>>> {
+        if self.get_by_id(id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let candidates: Vec<DuplicateCandidate> = sqlx::query_as(DUPLICATES)
+            .bind(id)
+            .bind(MAX_DUPLICATES)
+            .bind(MIN_DUPLICATE_SCORE)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let result = candidates
+            .into_iter()
+            .map(|c| {
+                Ok(DuplicateCandidate {
+                    patient: self.decrypt(c.patient)?,
+                    ..c
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(result))
+    }
+
+    async fn can_act_for(&self, id: i32, id_user: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar("SELECT can_act_for_patient($1, $2)")
+            .bind(id_user)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_dependents(&self, id_user: i32) -> Result<Vec<Patient>> {
+        let result = sqlx::query_as::<_, Patient>(
+            r#"
+            SELECT DISTINCT d.*
+            FROM patient_contacts c
+            JOIN patients d ON d.id_patient = c.id_patient
+            JOIN patients g ON g.id_patient = c.id_related_patient
+            WHERE g.id_user = $1
+              AND g.deleted_at IS NULL
+              AND c.is_legal_guardian
+              AND c.deleted_at IS NULL
+              AND d.deleted_at IS NULL
+            ORDER BY d.id_patient
+            "#,
+        )
+        .bind(id_user)
+        .fetch_all(&self.pool)
+        .await?;
+        result.into_iter().map(|p| self.decrypt(p)).collect()
+    }
+
+    async fn confirm_identity(&self, id: i32, performed_by: i32) -> Result<Option<Patient>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result: Option<Patient> = sqlx::query_as::<_, Patient>(
+            "UPDATE patients SET status = $2, updated_at = NOW()
+             WHERE id_patient = $1 AND status = $3 AND deleted_at IS NULL
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(STATUS_ACTIVE)
+        .bind(STATUS_PENDING_VERIFICATION)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if result.is_none() {
+            return Ok(None);
+        }
+
+        sqlx::query(
+            "INSERT INTO audit_logs (table_name, record_id, action, old_data, new_data, performed_by)
+             VALUES ('patients', $1, $2, jsonb_build_object('status', $3::text), jsonb_build_object('status', $4::text), $5)",
+        )
+        .bind(id)
+        .bind(ACTION_CONFIRM_IDENTITY)
+        .bind(STATUS_PENDING_VERIFICATION)
+        .bind(STATUS_ACTIVE)
+        .bind(performed_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        self.decrypt_opt(result)
+    }
+
+    async fn change_status(
+        &self,
+        id: i32,
+        from: &str,
+        data: &ChangePatientStatus,
+        performed_by: i32,
+        now: NaiveDateTime,
+    ) -> Result<Option<PatientStatusChange>> {
+        let mut tx = self.pool.begin().await?;
+
+        let result: Option<Patient> = sqlx::query_as::<_, Patient>(
+            "UPDATE patients SET status = $2, status_date = COALESCE($3, $6), status_reason = $4,
+                                 updated_at = NOW()
+             WHERE id_patient = $1 AND COALESCE(status, 'active') = $5 AND deleted_at IS NULL
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(&data.status)
+        .bind(data.date)
+        .bind(&data.reason)
+        .bind(from)
+        .bind(now.date())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(patient) = result else {
+            return Ok(None);
+        };
+
+        let mut canceled_appointments = Vec::new();
+        if data.status == STATUS_DECEASED {
+            canceled_appointments = sqlx::query_scalar(
+                "UPDATE medical_appointments SET status = 'canceled', updated_at = NOW()
+                 WHERE id_patient = $1 AND appointment_datetime > $2
+                   AND status IN ('pending', 'confirmed') AND deleted_at IS NULL
+                 RETURNING id_appointment",
+            )
+            .bind(id)
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM device_tokens WHERE id_user = $1")
+                .bind(patient.id_user)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO audit_logs (table_name, record_id, action, old_data, new_data, performed_by)
+             VALUES ('patients', $1, $2, jsonb_build_object('status', $3::text),
+                     jsonb_build_object('status', $4::text, 'status_date', $5::date, 'status_reason', $6::text,
+                                        'canceled_appointments', $7::int[]),
+                     $8)",
+        )
+        .bind(id)
+        .bind(ACTION_STATUS_CHANGE)
+        .bind(from)
+        .bind(&data.status)
+        .bind(patient.status_date)
+        .bind(&data.reason)
+        .bind(&canceled_appointments)
+        .bind(performed_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(PatientStatusChange {
+            patient: self.decrypt(patient)?,
+            canceled_appointments,
+        }))
+    }
+
+    async fn get_by_id(&self, id: i32) -> Result<Option<Patient>> {
+        let result: Option<Patient> = sqlx::query_as::<_, Patient>(
+            r#"
+            SELECT id_patient, id_user, identity_number, identity_type,
+                   first_name, second_name, first_lastname, second_lastname,
+                   gender, birthdate, blood_type,
+                   phone, email, address,
+                   emergency_contact_name, emergency_contact_phone,
+                   allergies, current_medications, medical_background,
+                   priority, status, status_date, status_reason,
+                   created_at, updated_at, deleted_at, data_key
+            FROM patients
+            WHERE id_patient = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        self.decrypt_opt(result)
+    }
+
+    async fn get_by_user(&self, id_user: i32) -> Result<Option<Patient>> {
+        let result: Option<Patient> = sqlx::query_as::<_, Patient>(
+            r#"
+            SELECT id_patient, id_user, identity_number, identity_type,
+                   first_name, second_name, first_lastname, second_lastname,
+                   gender, birthdate, blood_type,
+                   phone, email, address,
+                   emergency_contact_name, emergency_contact_phone,
+                   allergies, current_medications, medical_background,
+                   priority, status, status_date, status_reason,
+                   created_at, updated_at, deleted_at, data_key
+            FROM patients
+            WHERE id_user = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id_user)
+        .fetch_optional(&self.pool)
+        .await?;
+        self.decrypt_opt(result)
+    }
+
+    async fn create(&self, data: CreatePatient, password_hash: &str) -> Result<Patient> {
+        let mut tx = self.pool.begin().await?;
+        let patient = self.insert(&mut tx, &data, password_hash).await?;
+        tx.commit().await?;
+        self.decrypt(patient)
+    }
+
+    async fn create_batch(&self, rows: Vec<(CreatePatient, String)>) -> Result<Vec<Result<Patient>>> {
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(rows.len());
+
+        // Un savepoint por fila: una fila con error no descarta el resto del lote
+        for (data, password_hash) in &rows {
+            let mut savepoint = (&mut tx).begin().await?;
+            match self.insert(&mut savepoint, data, password_hash).await {
+                Ok(patient) => {
+                    savepoint.commit().await?;
+                    results.push(self.decrypt(patient));
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    results.push(Err(e));
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    async fn find_taken(&self, values: &[String]) -> Result<Vec<String>> {
+        // El usuario del paciente es su email o su número de identidad
+        let result: Vec<String> = sqlx::query_scalar(
+            "SELECT identity_number FROM patients WHERE identity_number = ANY($1)
+             UNION
+             SELECT email FROM patients WHERE email = ANY($1)
+             UNION
+             SELECT username FROM users WHERE username = ANY($1)",
+        )
+        .bind(values)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
     }
 
     async fn update(&self, id: i32, data: UpdatePatient) -> Result<Option<Patient>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Bloquear la fila para que dos actualizaciones no generen DEKs distintas
+        let current = sqlx::query_as::<_, PatientSecrets>(
+            "SELECT id_patient, data_key, allergies, current_medications, medical_background
+             FROM patients WHERE id_patient = $1 AND deleted_at IS NULL FOR UPDATE",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(current) = current else {
+            return Ok(None);
+        };
+
+        let (allergies, medications, background, wrapped_key) = match &current.data_key {
+            Some(wrapped) => {
+                let dek = self.keyring.unwrap_data_key(wrapped)?;
+                (
+                    dek.encrypt_opt(ALLERGIES, data.allergies.as_deref())?,
+                    dek.encrypt_opt(CURRENT_MEDICATIONS, data.current_medications.as_deref())?,
+                    dek.encrypt_opt(MEDICAL_BACKGROUND, data.medical_background.as_deref())?,
+                    None,
+                )
+            }
+            None => {
+                // Fila anterior al cifrado: con la DEK nueva se cifran también los
+                // valores que no cambian, o quedarían en claro bajo una data_key
+                let (dek, wrapped) = self.keyring.new_data_key()?;
+                let merged = PatientSecrets {
+                    allergies: data.allergies.clone().or(current.allergies),
+                    current_medications: data.current_medications.clone().or(current.current_medications),
+                    medical_background: data.medical_background.clone().or(current.medical_background),
+                    ..current
+                };
+                let (allergies, medications, background) = encrypt_secrets(&dek, &merged)?;
+                (allergies, medications, background, Some(wrapped))
+            }
+        };
+
         let result: Option<Patient> = sqlx::query_as::<_, Patient>(
             r#"
             UPDATE patients SET
@@ -178,9 +666,10 @@ impl PatientRepository for PgPatientRepository {
                 current_medications = COALESCE($16, current_medications),
                 medical_background = COALESCE($17, medical_background),
                 priority = COALESCE($18, priority),
-                status = COALESCE($19, status),
+                data_key = COALESCE($20, data_key),
+                identity_type = COALESCE($21, identity_type),
                 updated_at = NOW()
-            WHERE id_patient = $20
+            WHERE id_patient = $19 AND deleted_at IS NULL
             RETURNING *
             "#,
         )
@@ -198,24 +687,96 @@ impl PatientRepository for PgPatientRepository {
         .bind(data.address)
         .bind(data.emergency_contact_name)
         .bind(data.emergency_contact_phone)
-        .bind(data.allergies)
-        .bind(data.current_medications)
-        .bind(data.medical_background)
+        .bind(allergies)
+        .bind(medications)
+        .bind(background)
         .bind(data.priority)
-        .bind(data.status)
         .bind(id)
-        .fetch_optional(&self.pool)
+        .bind(wrapped_key)
+        .bind(data.identity_type)
+        .fetch_optional(&mut *tx)
         .await?;
-        Ok(result)
+
+        tx.commit().await?;
+        self.decrypt_opt(result)
     }
 
     async fn delete(&self, id: i32) -> Result<Option<Patient>> {
         let result: Option<Patient> = sqlx::query_as::<_, Patient>(
-            "UPDATE patients SET deleted_at = NOW() WHERE id_patient = $1 RETURNING *",
+            "UPDATE patients SET deleted_at = NOW() WHERE id_patient = $1 AND deleted_at IS NULL RETURNING *",
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await?;
-        Ok(result)
+        self.decrypt_opt(result)
+    }
+
+    async fn rotate_keys(&self) -> Result<u64> {
+        let mut rotated = 0;
+
+        loop {
+            // Incluye filas eliminadas: deben seguir siendo legibles
+            let batch = sqlx::query_as::<_, PatientSecrets>(
+                r#"
+                SELECT id_patient, data_key, allergies, current_medications, medical_background
+                FROM patients
+                WHERE data_key IS NULL OR split_part(data_key, ':', 1) <> $1
+                ORDER BY id_patient
+                LIMIT 200
+                "#,
+            )
+            .bind(self.keyring.active_id())
+            .fetch_all(&self.pool)
+            .await?;
+
+            if batch.is_empty() {
+                break;
+            }
+
+            let mut updated_in_batch = 0;
+            for row in &batch {
+                // Solo se actualiza si nadie cambió la DEK mientras tanto
+                let result = match &row.data_key {
+                    Some(wrapped) => {
+                        sqlx::query(
+                            "UPDATE patients SET data_key = $1
+                             WHERE id_patient = $2 AND data_key = $3",
+                        )
+                        .bind(self.keyring.rewrap(wrapped)?)
+                        .bind(row.id_patient)
+                        .bind(wrapped)
+                        .execute(&self.pool)
+                        .await?
+                    }
+                    None => {
+                        let (dek, wrapped) = self.keyring.new_data_key()?;
+                        let (allergies, medications, background) = encrypt_secrets(&dek, row)?;
+                        sqlx::query(
+                            "UPDATE patients SET
+                                data_key = $1,
+                                allergies = $2,
+                                current_medications = $3,
+                                medical_background = $4
+                             WHERE id_patient = $5 AND data_key IS NULL",
+                        )
+                        .bind(wrapped)
+                        .bind(allergies)
+                        .bind(medications)
+                        .bind(background)
+                        .bind(row.id_patient)
+                        .execute(&self.pool)
+                        .await?
+                    }
+                };
+                updated_in_batch += result.rows_affected();
+            }
+
+            rotated += updated_in_batch;
+            if updated_in_batch == 0 {
+                break;
+            }
+        }
+
+        Ok(rotated)
     }
 }
@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use image::{ImageFormat, ImageReader};
+use std::{env, io::Cursor, time::Duration};
+use tokio::process::Command;
+
+// Lado máximo (px) de las vistas previas
+pub const PREVIEW_SIZE: u32 = 256;
+
+// Tiempo máximo para renderizar un PDF; un archivo malicioso no debe colgar la subida
+const PDF_RENDER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Genera una miniatura PNG del documento.
+/// Devuelve None si el tipo de archivo no admite vista previa.
+pub async fn generate_preview(content_type: &str, data: Bytes) -> Result<Option<Bytes>> {
+    match content_type {
+        "image/jpeg" | "image/png" => {
+            let png = tokio::task::spawn_blocking(move || thumbnail(&data)).await??;
+            Ok(Some(png))
+        }
+        "application/pdf" => {
+            let page = render_pdf_first_page(&data).await?;
+            let png = tokio::task::spawn_blocking(move || thumbnail(&page)).await??;
+            Ok(Some(png))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn thumbnail(data: &[u8]) -> Result<Bytes> {
+    let img = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .decode()
+        .context("No se pudo decodificar la imagen")?;
+
+    let mut out = Cursor::new(Vec::new());
+    img.thumbnail(PREVIEW_SIZE, PREVIEW_SIZE)
+        .write_to(&mut out, ImageFormat::Png)?;
+    Ok(Bytes::from(out.into_inner()))
+}
+
+/// Renderiza la primera página del PDF con poppler (pdftoppm).
+/// El binario se puede cambiar con PDF_RENDERER. Si tarda más de
+/// PDF_RENDER_TIMEOUT se mata el proceso y se devuelve error.
+async fn render_pdf_first_page(data: &[u8]) -> Result<Bytes> {
+    let renderer = env::var("PDF_RENDERER").unwrap_or_else(|_| "pdftoppm".to_string());
+
+    let dir = env::temp_dir().join(format!("nexo-preview-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&dir).await?;
+    let input = dir.join("input.pdf");
+    let output = dir.join("page");
+
+    let result = async {
+        tokio::fs::write(&input, data).await?;
+
+        // kill_on_drop: al vencer el timeout se descarta el futuro y se mata el proceso
+        let run = Command::new(&renderer)
+            .args(["-png", "-f", "1", "-l", "1", "-singlefile", "-scale-to"])
+            .arg((PREVIEW_SIZE * 2).to_string())
+            .arg(&input)
+            .arg(&output)
+            .kill_on_drop(true)
+            .status();
+        let status = tokio::time::timeout(PDF_RENDER_TIMEOUT, run)
+            .await
+            .map_err(|_| anyhow::anyhow!("{} no terminó en {:?}", renderer, PDF_RENDER_TIMEOUT))?
+            .with_context(|| format!("No se pudo ejecutar {}", renderer))?;
+
+        if !status.success() {
+            anyhow::bail!("{} terminó con {}", renderer, status);
+        }
+
+        let page = tokio::fs::read(output.with_extension("png")).await?;
+        Ok(Bytes::from(page))
+    }
+    .await;
+
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    result
+}
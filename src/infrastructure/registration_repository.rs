@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use sqlx::{types::Json, PgPool};
+use anyhow::Result;
+use chrono::{Duration, Local};
+use uuid::Uuid;
+
+use crate::domain::registration::{CreatePatientRegistration, PatientRegistration, CODE_TTL_MINUTES};
+
+#[async_trait]
+pub trait RegistrationRepository: Send + Sync + 'static {
+    async fn create(&self, data: CreatePatientRegistration) -> Result<PatientRegistration>;
+    /// Suma un intento y devuelve el registro; None si no existe o ya se verificó
+    async fn register_attempt(&self, id: Uuid) -> Result<Option<PatientRegistration>>;
+    /// Marca el registro como verificado si nadie lo hizo antes; false si otra
+    /// petición se adelantó
+    async fn claim(&self, id: Uuid) -> Result<bool>;
+    /// Deshace `claim` cuando no se pudo crear el paciente
+    async fn release(&self, id: Uuid) -> Result<()>;
+    /// Asocia el paciente creado al registro ya reclamado
+    async fn mark_verified(&self, id: Uuid, id_patient: i32) -> Result<()>;
+}
+
+pub struct PgRegistrationRepository {
+    pool: PgPool,
+}
+
+impl PgRegistrationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RegistrationRepository for PgRegistrationRepository {
+    async fn create(&self, data: CreatePatientRegistration) -> Result<PatientRegistration> {
+        let expires_at = Local::now().naive_local() + Duration::minutes(CODE_TTL_MINUTES);
+
+        let result = sqlx::query_as::<_, PatientRegistration>(
+            "INSERT INTO patient_registrations
+            (id_registration, payload, password_hash, channel, destination, code_hash, expires_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7)
+            RETURNING *"
+        )
+        .bind(Uuid::new_v4())
+        .bind(Json(&data.payload))
+        .bind(data.password_hash)
+        .bind(data.channel)
+        .bind(data.destination)
+        .bind(data.code_hash)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn register_attempt(&self, id: Uuid) -> Result<Option<PatientRegistration>> {
+        let result = sqlx::query_as::<_, PatientRegistration>(
+            "UPDATE patient_registrations SET attempts = attempts + 1
+             WHERE id_registration = $1 AND verified_at IS NULL
+             RETURNING *"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn claim(&self, id: Uuid) -> Result<bool> {
+        let claimed = sqlx::query(
+            "UPDATE patient_registrations SET verified_at = NOW()
+             WHERE id_registration = $1 AND verified_at IS NULL"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    async fn release(&self, id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE patient_registrations SET verified_at = NULL WHERE id_registration = $1 AND id_patient IS NULL"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_verified(&self, id: Uuid, id_patient: i32) -> Result<()> {
+        sqlx::query(
+            "UPDATE patient_registrations SET id_patient = $2 WHERE id_registration = $1"
+        )
+        .bind(id)
+        .bind(id_patient)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::domain::appointment_reminder::{AppointmentReminder, DueReminder};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ReminderRepository: Send + Sync + 'static {
+    /// Citas pendientes o confirmadas que entraron en la ventana de `offset_minutes`
+    /// (faltan `offset_minutes` o menos y más de `until_minutes`) y no tienen ese
+    /// recordatorio para su horario actual. Las agendadas después de ese momento
+    /// se saltan esta anticipación. `now` es la hora local de la aplicación, la
+    /// misma con que se guardan la cita y su `created_at`.
+    async fn find_due(&self, offset_minutes: i32, until_minutes: i32, now: NaiveDateTime) -> Result<Vec<DueReminder>>;
+    /// Guarda el recordatorio y, si el paciente tiene usuario, su notificación en
+    /// la aplicación. None si ya existía: otro proceso lo envió o lo está enviando.
+    async fn claim(
+        &self,
+        due: &DueReminder,
+        offset_minutes: i32,
+        token: Uuid,
+        title: &str,
+        body: &str,
+    ) -> Result<Option<AppointmentReminder>>;
+    /// Registra el envío; con `error` queda como fallido y no se reintenta
+    async fn mark_sent(&self, id: i32, error: Option<&str>) -> Result<()>;
+}
+
+pub struct PgReminderRepository {
+    pool: PgPool,
+}
+
+impl PgReminderRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ReminderRepository for PgReminderRepository {
+    async fn find_due(&self, offset_minutes: i32, until_minutes: i32, now: NaiveDateTime) -> Result<Vec<DueReminder>> {
+        let result = sqlx::query_as::<_, DueReminder>(
+            "SELECT a.id_appointment, p.id_user, a.appointment_datetime, a.status, a.building, a.room,
+                    CONCAT_WS(' ', d.first_name, d.first_lastname) AS doctor_name,
+                    CASE WHEN p.email IS NOT NULL THEN 'email' WHEN p.phone IS NOT NULL THEN 'phone' END AS channel,
+                    COALESCE(p.email, p.phone) AS destination
+             FROM medical_appointments a
+             JOIN patients p ON p.id_patient = a.id_patient AND p.deleted_at IS NULL
+             JOIN doctors d ON d.id_doctor = a.id_doctor
+             WHERE a.deleted_at IS NULL
+               AND a.status IN ('pending', 'confirmed')
+               AND p.status IS DISTINCT FROM 'deceased'
+               AND a.appointment_datetime - $1 * INTERVAL '1 minute' <= $3
+               AND a.appointment_datetime - $2 * INTERVAL '1 minute' > $3
+               AND a.created_at <= a.appointment_datetime - $1 * INTERVAL '1 minute'
+               AND NOT EXISTS (
+                   SELECT 1 FROM appointment_reminders r
+                   WHERE r.id_appointment = a.id_appointment
+                     AND r.offset_minutes = $1
+                     AND r.appointment_datetime = a.appointment_datetime
+               )
+             ORDER BY a.appointment_datetime, a.id_appointment",
+        )
+        .bind(offset_minutes)
+        .bind(until_minutes)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn claim(
+        &self,
+        due: &DueReminder,
+        offset_minutes: i32,
+        token: Uuid,
+        title: &str,
+        body: &str,
+    ) -> Result<Option<AppointmentReminder>> {
+        let mut tx = self.pool.begin().await?;
+
+        let reminder = sqlx::query_as::<_, AppointmentReminder>(
+            "INSERT INTO appointment_reminders
+            (id_appointment, offset_minutes, appointment_datetime, token, channel, destination)
+            VALUES ($1,$2,$3,$4,$5,$6)
+            ON CONFLICT (id_appointment, offset_minutes, appointment_datetime) DO NOTHING
+            RETURNING *",
+        )
+        .bind(due.id_appointment)
+        .bind(offset_minutes)
+        .bind(due.appointment_datetime)
+        .bind(token)
+        .bind(due.channel.as_deref())
+        .bind(due.destination.as_deref())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(reminder) = reminder else {
+            return Ok(None);
+        };
+
+        if let Some(id_user) = due.id_user {
+            sqlx::query("INSERT INTO notifications (id_user, title, message, type) VALUES ($1, $2, $3, 'cita')")
+                .bind(id_user)
+                .bind(title)
+                .bind(body)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(reminder))
+    }
+
+    async fn mark_sent(&self, id: i32, error: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "UPDATE appointment_reminders
+             SET sent_at = CASE WHEN $2::text IS NULL THEN NOW() END, error = $2
+             WHERE id_reminder = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
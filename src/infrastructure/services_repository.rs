@@ -1,13 +1,27 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::PgPool;
-use sqlx::Error;
+use crate::helpers::errors::on_unique_violation;
 
 use crate::domain::services::{Service, CreateService, UpdateService};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_service, service_name, created_at, updated_at, deleted_at",
+    from: "services",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_service",
+    sort_fields: &[
+        SortField { name: "id_service", column: "id_service", sql_type: "int" },
+        SortField { name: "service_name", column: "service_name", sql_type: "text" },
+    ],
+    default_sort: "service_name",
+};
 
 #[async_trait]
 pub trait ServiceRepository: Send + Sync + 'static {   
-    async fn get_all(&self) -> Result<Vec<Service>>;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Service>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Service>>;
     async fn create(&self, data: CreateService) -> Result<Service>;
     async fn update(&self, id: i32, data: UpdateService) -> Result<Option<Service>>;
@@ -26,12 +40,8 @@ impl PgServiceRepository {
 
 #[async_trait]
 impl ServiceRepository for PgServiceRepository { 
-    async fn get_all(&self) -> Result<Vec<Service>> {
-        let result: Vec<Service> = sqlx::query_as::<_, Service>(
-            "SELECT id_service, service_name, created_at, updated_at, deleted_at FROM services WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Service>> {
+        let result = fetch_page(&self.pool, &LIST, page, |_| {}).await?;
         Ok(result)
     }
 
@@ -54,19 +64,10 @@ impl ServiceRepository for PgServiceRepository {
         )
         .bind(data.service_name);
 
-        let result = match query.fetch_one(&self.pool).await {
-            Ok(service) => service,
-            Err(Error::Database(db_err)) => {
-                if db_err.code().as_deref() == Some("23505") {
-                    anyhow::bail!("El servicio ya existe")
-                } else {
-                    anyhow::bail!("Error en la base de datos: {}", db_err.message())
-                }
-            }
-            Err(e) => {
-                anyhow::bail!("Error inesperado: {}", e)
-            }
-        };
+        let result = query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| on_unique_violation(e, "El servicio ya existe"))?;
 
         Ok(result)
     }
@@ -76,7 +77,7 @@ impl ServiceRepository for PgServiceRepository {
             "UPDATE services SET
  service_name = COALESCE($1, service_name),
  updated_at = NOW()
- WHERE id_service = $2 RETURNING *",
+ WHERE id_service = $2 AND deleted_at IS NULL RETURNING *",
         )
         .bind(data.service_name.as_deref())
         .bind(id)
@@ -87,7 +88,7 @@ impl ServiceRepository for PgServiceRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<Service>> {
         let result: Option<Service> = sqlx::query_as::<_, Service>(
-            "UPDATE services SET deleted_at = NOW() WHERE id_service = $1 RETURNING *",
+            "UPDATE services SET deleted_at = NOW() WHERE id_service = $1 AND deleted_at IS NULL RETURNING *",
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -1,13 +1,27 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::PgPool;
-use sqlx::Error;
+use crate::helpers::errors::on_unique_violation;
 
 use crate::domain::speciality::{Speciality, CreateSpeciality, UpdateSpeciality};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_speciality, speciality_name, created_at, updated_at, deleted_at",
+    from: "specialities",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_speciality",
+    sort_fields: &[
+        SortField { name: "id_speciality", column: "id_speciality", sql_type: "int" },
+        SortField { name: "speciality_name", column: "speciality_name", sql_type: "text" },
+    ],
+    default_sort: "speciality_name",
+};
 
 #[async_trait]
 pub trait SpecialityRepository: Send + Sync+ 'static {
-    async fn get_all(&self) -> Result<Vec<Speciality>>;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Speciality>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<Speciality>>;
     async fn create(&self, data: CreateSpeciality) -> Result<Speciality>;
     async fn update(&self, id: i32, data: UpdateSpeciality) -> Result<Option<Speciality>>;
@@ -26,12 +40,8 @@ impl PgSpecialityRepository {
 
 #[async_trait]
 impl SpecialityRepository for PgSpecialityRepository {
-    async fn get_all(&self) -> Result<Vec<Speciality>> {
-        let result: Vec<Speciality> = sqlx::query_as::<_, Speciality>(
-            "SELECT id_speciality, speciality_name, created_at, updated_at, deleted_at FROM specialities WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_all(&self, page: &PageParams) -> Result<Page<Speciality>> {
+        let result = fetch_page(&self.pool, &LIST, page, |_| {}).await?;
         Ok(result)
     }
 
@@ -51,19 +61,10 @@ impl SpecialityRepository for PgSpecialityRepository {
         ) 
         .bind(data.speciality_name);
 
-        let result = match query.fetch_one(&self.pool).await{
-            Ok(speciality) => speciality,
-            Err(Error::Database(db_err)) => {
-                if db_err.code().as_deref() == Some("23505") {
-                    anyhow::bail!("La especialidad ya existe")
-                } else {
-                    anyhow::bail!("Error en la base de datos: {}", db_err.message())
-                }
-            }
-            Err(e) => {
-                anyhow::bail!("Error inesperado: {}", e)
-            }
-        };
+        let result = query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| on_unique_violation(e, "La especialidad ya existe"))?;
 
         Ok(result)
     }
@@ -71,9 +72,9 @@ impl SpecialityRepository for PgSpecialityRepository {
     async fn update(&self, id: i32, data: UpdateSpeciality) -> Result<Option<Speciality>>{
         let result: Option<Speciality> = sqlx::query_as::<_, Speciality>(
             "UPDATE specialities SET
-            speciality_name = COALESCE($1, speciality_name)
+            speciality_name = COALESCE($1, speciality_name),
             updated_at = NOW()
-            WHERE id_speciality = $2 RETURNING *"
+            WHERE id_speciality = $2 AND deleted_at IS NULL RETURNING *"
         )
         .bind(data.speciality_name)
         .bind(id)
@@ -84,7 +85,7 @@ impl SpecialityRepository for PgSpecialityRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<Speciality>> {
         let result: Option<Speciality>  = sqlx::query_as::<_, Speciality>(
-            "UPDATE users SET deleted_at = NOW() WHERE id_speciality = $1 RETURNING *"
+            "UPDATE specialities SET deleted_at = NOW() WHERE id_speciality = $1 AND deleted_at IS NULL RETURNING *"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use calamine::{Data, DataType, Reader, Xlsx};
+use csv::{ReaderBuilder, StringRecord, Trim};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io::Cursor;
+
+use crate::domain::patient_import::{ImportFormat, InvalidImportFile};
+
+/// Fila de datos leída de la hoja. `row` cuenta el encabezado como fila 1.
+pub struct SheetRow<T> {
+    pub row: i32,
+    pub value: Result<T, CellError>,
+}
+
+/// Celda que no se pudo convertir al tipo esperado (ej. fecha mal escrita)
+pub struct CellError {
+    pub column: Option<String>,
+    pub message: String,
+}
+
+fn invalid(msg: impl Into<String>) -> anyhow::Error {
+    InvalidImportFile(msg.into()).into()
+}
+
+/// Lee la primera hoja (XLSX) o el CSV completo y convierte cada fila al tipo `T`
+/// usando el encabezado como nombres de campo. Las filas vacías se omiten.
+pub fn read_rows<T: DeserializeOwned>(
+    format: ImportFormat,
+    data: &[u8],
+    required: &[&str],
+) -> Result<Vec<SheetRow<T>>> {
+    let mut records = match format {
+        ImportFormat::Csv => read_csv(data)?,
+        ImportFormat::Xlsx => read_xlsx(data)?,
+    }
+    .into_iter();
+
+    // Encabezados sin distinguir mayúsculas ni espacios alrededor
+    let headers: StringRecord = records
+        .next()
+        .ok_or_else(|| invalid("El archivo no tiene encabezado"))?
+        .iter()
+        .map(|h| h.trim_start_matches('\u{feff}').trim().to_lowercase())
+        .collect();
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|c| !headers.iter().any(|h| h == *c))
+        .collect();
+    if !missing.is_empty() {
+        return Err(invalid(format!("Faltan columnas obligatorias: {}", missing.join(", "))));
+    }
+
+    let rows = records
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|f| !f.is_empty()))
+        .map(|(i, record)| SheetRow {
+            row: i as i32 + 2,
+            value: record.deserialize(Some(&headers)).map_err(|e| cell_error(&headers, e)),
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn cell_error(headers: &StringRecord, err: csv::Error) -> CellError {
+    match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => CellError {
+            column: err.field().and_then(|i| headers.get(i as usize)).map(String::from),
+            message: format!("Valor inválido: {}", err.kind()),
+        },
+        _ => CellError { column: None, message: err.to_string() },
+    }
+}
+
+fn read_csv(data: &[u8]) -> Result<Vec<StringRecord>> {
+    // Excel en español exporta CSV separado por ';'
+    let first_line = data.split(|b| *b == b'\n').next().unwrap_or_default();
+    let semicolons = first_line.iter().filter(|b| **b == b';').count();
+    let commas = first_line.iter().filter(|b| **b == b',').count();
+    let delimiter = if semicolons > commas { b';' } else { b',' };
+
+    ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(Trim::All)
+        .delimiter(delimiter)
+        .from_reader(data)
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(format!("CSV inválido: {}", e)))
+}
+
+fn read_xlsx(data: &[u8]) -> Result<Vec<StringRecord>> {
+    let mut workbook = Xlsx::new(Cursor::new(data)).map_err(|e| invalid(format!("XLSX inválido: {}", e)))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| invalid("El archivo no tiene hojas"))?
+        .map_err(|e| invalid(format!("XLSX inválido: {}", e)))?;
+
+    Ok(range
+        .rows()
+        .map(|cells| cells.iter().map(cell_to_string).collect())
+        .collect())
+}
+
+/// Texto de la celda tal como lo leería el deserializador del CSV
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) => s.trim().to_string(),
+        Data::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Data::DateTime(_) => cell.as_date().map(|d| d.to_string()).unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+/// Serializa una lista como CSV (ej. reporte de errores). El encabezado se
+/// escribe aunque la lista esté vacía.
+pub fn to_csv<T: Serialize>(headers: &[&str], rows: &[T]) -> Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    writer.write_record(headers)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    Ok(writer.into_inner()?)
+}
@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use sqlx::{Acquire, PgPool};
+
+use crate::domain::audit_log::{ACTION_MERGE, ACTION_PURGE, ACTION_RESTORE, ACTION_UNMERGE};
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::trash::{PurgeResult, TrashItem};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+/// Referencia a otro registro con papelera: si el padre está eliminado,
+/// el hijo no se puede restaurar
+struct Parent {
+    column: &'static str,
+    table: &'static str,
+    id_column: &'static str,
+}
+
+struct TrashSpec {
+    /// Listado de la papelera; `from` es la tabla e `id_column` su clave
+    list: ListQuery,
+    parents: &'static [Parent],
+}
+
+// El orden desempata por la clave de cada tabla
+const SORT_FIELDS: &[SortField] = &[
+    SortField { name: "deleted_at", column: "deleted_at", sql_type: "timestamp" },
+];
+
+const PATIENT: Parent = Parent { column: "id_patient", table: "patients", id_column: "id_patient" };
+const USER: Parent = Parent { column: "id_user", table: "users", id_column: "id_user" };
+const DOCTOR: Parent = Parent { column: "id_doctor", table: "doctors", id_column: "id_doctor" };
+
+macro_rules! trash_spec {
+    ($table:literal, $id:literal, $label:literal, $parents:expr) => {
+        TrashSpec {
+            list: ListQuery {
+                columns: concat!($id, " AS id, ", $label, " AS label, deleted_at"),
+                from: $table,
+                base_filter: "deleted_at IS NOT NULL",
+                id_column: $id,
+                sort_fields: SORT_FIELDS,
+                default_sort: "-deleted_at",
+            },
+            parents: $parents,
+        }
+    };
+}
+
+static PATIENT_CONTACTS: TrashSpec = trash_spec!(
+    "patient_contacts", "id_contact",
+    "COALESCE(contact_name, 'Paciente ' || id_related_patient) || ' (de ' || id_patient || ')'",
+    &[PATIENT, Parent { column: "id_related_patient", table: "patients", id_column: "id_patient" }]
+);
+static APPOINTMENTS: TrashSpec = trash_spec!(
+    "medical_appointments", "id_appointment",
+    "'Paciente ' || id_patient || ', ' || to_char(appointment_datetime, 'YYYY-MM-DD HH24:MI')",
+    &[PATIENT, DOCTOR]
+);
+static MEDICAL_HISTORY: TrashSpec = trash_spec!(
+    "medical_history", "id_history",
+    "'Paciente ' || id_patient || ', ' || to_char(record_date, 'YYYY-MM-DD')",
+    &[PATIENT, DOCTOR]
+);
+static LAB_RESULTS: TrashSpec = trash_spec!(
+    "lab_results", "id_result",
+    "lab_name || ', paciente ' || id_patient",
+    &[PATIENT, DOCTOR]
+);
+static DOCTORS: TrashSpec = trash_spec!(
+    "doctors", "id_doctor",
+    "concat_ws(' ', first_name, first_lastname)",
+    &[
+        USER,
+        Parent { column: "id_service", table: "services", id_column: "id_service" },
+        Parent { column: "id_speciality", table: "specialities", id_column: "id_speciality" },
+    ]
+);
+static PATIENTS: TrashSpec = trash_spec!(
+    "patients", "id_patient",
+    "concat_ws(' ', first_name, first_lastname) || ' (' || identity_number || ')'",
+    &[USER]
+);
+static SERVICES: TrashSpec = trash_spec!("services", "id_service", "service_name", &[]);
+static SPECIALITIES: TrashSpec = trash_spec!("specialities", "id_speciality", "speciality_name", &[]);
+static USERS: TrashSpec = trash_spec!("users", "id_user", "username", &[]);
+
+/// Mismos nombres que `TRASH_ENTITIES`
+fn spec(entity: &str) -> Option<&'static TrashSpec> {
+    match entity {
+        "patient_contacts" => Some(&PATIENT_CONTACTS),
+        "appointments" => Some(&APPOINTMENTS),
+        "medical_history" => Some(&MEDICAL_HISTORY),
+        "lab_results" => Some(&LAB_RESULTS),
+        "doctors" => Some(&DOCTORS),
+        "patients" => Some(&PATIENTS),
+        "services" => Some(&SERVICES),
+        "specialities" => Some(&SPECIALITIES),
+        "users" => Some(&USERS),
+        _ => None,
+    }
+}
+
+#[async_trait]
+pub trait TrashRepository: Send + Sync + 'static {
+    /// Registros eliminados de la entidad; None si la entidad no tiene papelera
+    async fn list(&self, entity: &str, page: &PageParams) -> Result<Option<Page<TrashItem>>>;
+    /// Motivos por los que el registro no se puede restaurar (padre eliminado,
+    /// paciente fusionado). None si no está en la papelera.
+    async fn restore_blockers(&self, entity: &str, id: i32) -> Result<Option<Vec<String>>>;
+    /// Quita `deleted_at` y lo registra en audit_logs. None si no está en la papelera.
+    async fn restore(&self, entity: &str, id: i32, performed_by: i32) -> Result<Option<TrashItem>>;
+    /// Borra definitivamente lo eliminado hace más de `years` años. Los registros que
+    /// otros todavía referencian se omiten. Con `dry_run` se revierte todo al final.
+    async fn purge(&self, entity: &str, years: i32, dry_run: bool, performed_by: Option<i32>) -> Result<PurgeResult>;
+}
+
+pub struct PgTrashRepository {
+    pool: PgPool,
+}
+
+impl PgTrashRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TrashRepository for PgTrashRepository {
+    async fn list(&self, entity: &str, page: &PageParams) -> Result<Option<Page<TrashItem>>> {
+        let Some(spec) = spec(entity) else {
+            return Ok(None);
+        };
+        let result = fetch_page(&self.pool, &spec.list, page, |_| {}).await?;
+        Ok(Some(result))
+    }
+
+    async fn restore_blockers(&self, entity: &str, id: i32) -> Result<Option<Vec<String>>> {
+        let Some(spec) = spec(entity) else {
+            return Ok(None);
+        };
+        let (table, id_column) = (spec.list.from, spec.list.id_column);
+
+        let exists: Option<i32> = sqlx::query_scalar(&format!(
+            "SELECT {id_column} FROM {table} WHERE {id_column} = $1 AND deleted_at IS NOT NULL"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        let mut blockers = Vec::new();
+        for parent in spec.parents {
+            let deleted_parent: Option<i32> = sqlx::query_scalar(&format!(
+                "SELECT p.{pid} FROM {table} c JOIN {ptable} p ON p.{pid} = c.{col}
+                 WHERE c.{id_column} = $1 AND p.deleted_at IS NOT NULL",
+                pid = parent.id_column,
+                ptable = parent.table,
+                col = parent.column,
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(parent_id) = deleted_parent {
+                blockers.push(format!("{} {} está eliminado; restáurelo primero", parent.table, parent_id));
+            }
+        }
+
+        // Un duplicado fusionado se recupera revirtiendo la fusión, no desde la papelera
+        if table == "patients" {
+            let merge: Option<i32> = sqlx::query_scalar(
+                "SELECT m.id_audit FROM audit_logs m
+                 WHERE m.table_name = 'patients' AND m.record_id = $1 AND m.action = $2
+                   AND NOT EXISTS (
+                       SELECT 1 FROM audit_logs u
+                       WHERE u.action = $3 AND u.old_data->>'id_audit' = m.id_audit::text
+                   )",
+            )
+            .bind(id)
+            .bind(ACTION_MERGE)
+            .bind(ACTION_UNMERGE)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(id_audit) = merge {
+                blockers.push(format!(
+                    "El paciente fue fusionado; use POST /patients/merges/{}/undo",
+                    id_audit
+                ));
+            }
+        }
+
+        Ok(Some(blockers))
+    }
+
+    async fn restore(&self, entity: &str, id: i32, performed_by: i32) -> Result<Option<TrashItem>> {
+        let Some(spec) = spec(entity) else {
+            return Ok(None);
+        };
+        let (table, id_column) = (spec.list.from, spec.list.id_column);
+        let mut tx = self.pool.begin().await?;
+
+        let item: Option<TrashItem> = sqlx::query_as::<_, TrashItem>(&format!(
+            "SELECT {} FROM {table} WHERE {id_column} = $1 AND deleted_at IS NOT NULL FOR UPDATE",
+            spec.list.columns
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(item) = item else {
+            return Ok(None);
+        };
+
+        sqlx::query(&format!(
+            "UPDATE {table} SET deleted_at = NULL, updated_at = NOW() WHERE {id_column} = $1"
+        ))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO audit_logs (table_name, record_id, action, old_data, performed_by)
+             VALUES ($1, $2, $3, jsonb_build_object('deleted_at', $4::timestamp), $5)",
+        )
+        .bind(table)
+        .bind(id)
+        .bind(ACTION_RESTORE)
+        .bind(item.deleted_at)
+        .bind(performed_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(item))
+    }
+
+    async fn purge(&self, entity: &str, years: i32, dry_run: bool, performed_by: Option<i32>) -> Result<PurgeResult> {
+        let spec = spec(entity).ok_or_else(|| anyhow::anyhow!("La entidad {} no tiene papelera", entity))?;
+        let (table, id_column) = (spec.list.from, spec.list.id_column);
+        let mut tx = self.pool.begin().await?;
+
+        let expired: Vec<i32> = sqlx::query_scalar(&format!(
+            "SELECT {id_column} FROM {table}
+             WHERE deleted_at < NOW() - make_interval(years => $1)
+             ORDER BY {id_column}
+             FOR UPDATE"
+        ))
+        .bind(years)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut purged = Vec::new();
+        let mut skipped = Vec::new();
+        for id in expired {
+            // Un savepoint por registro: una referencia viva no aborta el resto
+            let mut savepoint = (&mut tx).begin().await?;
+            let deleted = sqlx::query(&format!("DELETE FROM {table} WHERE {id_column} = $1"))
+                .bind(id)
+                .execute(&mut *savepoint)
+                .await;
+            match deleted {
+                Ok(_) => {
+                    savepoint.commit().await?;
+                    purged.push(id);
+                }
+                Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("23503") => {
+                    savepoint.rollback().await?;
+                    skipped.push(id);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if !purged.is_empty() {
+            sqlx::query(
+                "INSERT INTO audit_logs (table_name, record_id, action, performed_by)
+                 SELECT $1, id, $2, $3 FROM unnest($4::int[]) AS id",
+            )
+            .bind(table)
+            .bind(ACTION_PURGE)
+            .bind(performed_by)
+            .bind(&purged)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(PurgeResult {
+            entity: entity.to_string(),
+            retention_years: Some(years),
+            purged,
+            skipped,
+        })
+    }
+}
+
@@ -1,13 +1,28 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use sqlx::PgPool;
-use sqlx::Error;
+use crate::helpers::errors::on_unique_violation;
 
-use crate::domain::user::{User, CreateUser, UpdateUser};
+use crate::domain::user::{User, CreateUser, UpdateUser, UserFilter};
+use crate::domain::pagination::{Page, PageParams};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_user, username, password_hash, role, created_at, updated_at, deleted_at",
+    from: "users",
+    base_filter: "deleted_at IS NULL",
+    id_column: "id_user",
+    sort_fields: &[
+        SortField { name: "id_user", column: "id_user", sql_type: "int" },
+        SortField { name: "username", column: "username", sql_type: "text" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "id_user",
+};
 
 #[async_trait]
 pub trait UserRepository: Send + Sync + 'static {
-    async fn get_all(&self) -> Result<Vec<User>>;
+    async fn get_all(&self, filter: &UserFilter, page: &PageParams) -> Result<Page<User>>;
     async fn get_by_id(&self, id: i32) -> Result<Option<User>>;
     async fn create(&self, data: CreateUser) -> Result<User>;
     async fn update(&self, id: i32, data: UpdateUser) -> Result<Option<User>>;
@@ -26,11 +41,12 @@ impl PgUserRepository {
 
 #[async_trait]
 impl UserRepository for PgUserRepository {
-    async fn get_all(&self) -> Result<Vec<User>>{
-        let result: Vec<User> = sqlx::query_as::<_, User>(
-            "SELECT id_user, username, password_hash, role, created_at, updated_at, deleted_at FROM users WHERE deleted_at IS NULL"
-        )
-        .fetch_all(&self.pool)
+    async fn get_all(&self, filter: &UserFilter, page: &PageParams) -> Result<Page<User>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(role) = &filter.role {
+                q.push(" AND role = ").push_bind(role.clone());
+            }
+        })
         .await?;
         Ok(result)
     }
@@ -56,19 +72,10 @@ impl UserRepository for PgUserRepository {
         .bind(data.password_hash)
         .bind(data.role);
 
-        let result = match query.fetch_one(&self.pool).await {
-            Ok(user) => user,
-            Err(Error::Database(db_err)) => {
-                if db_err.code().as_deref() == Some("23505") {
-                    anyhow::bail!("El usuario ya existe")
-                } else {
-                    anyhow::bail!("Error en la base de datos: {}", db_err.message())
-                }
-            }
-            Err(e) => {
-                anyhow::bail!("Error inesperado: {}", e)
-            }
-        };
+        let result = query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| on_unique_violation(e, "El usuario ya existe"))?;
 
         Ok(result)
     }
@@ -80,7 +87,7 @@ impl UserRepository for PgUserRepository {
  password_hash = COALESCE($2, password_hash),
  role = COALESCE($3, role),
  updated_at = NOW()
- WHERE id_user = $4 RETURNING *",
+ WHERE id_user = $4 AND deleted_at IS NULL RETURNING *",
         )
         .bind(data.username.as_deref())
         .bind(data.password_hash.as_deref())
@@ -93,7 +100,7 @@ impl UserRepository for PgUserRepository {
 
     async fn delete(&self, id: i32) -> Result<Option<User>> {
         let result: Option<User> = sqlx::query_as::<_, User>(
-            "UPDATE users SET deleted_at = NOW() WHERE id_user = $1 RETURNING *",
+            "UPDATE users SET deleted_at = NOW() WHERE id_user = $1 AND deleted_at IS NULL RETURNING *",
         )
         .bind(id)
         .fetch_optional(&self.pool)
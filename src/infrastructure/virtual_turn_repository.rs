@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::virtual_turn::VirtualTurn;
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+
+const LIST: ListQuery = ListQuery {
+    columns: "id_turn, id_patient, id_service, id_area, turn_number, priority, status,
+              created_at, called_at, started_at, completed_at, expires_at, turn_date",
+    from: "virtual_turns",
+    base_filter: "TRUE",
+    id_column: "id_turn",
+    sort_fields: &[
+        SortField { name: "id_turn", column: "id_turn", sql_type: "int" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+    ],
+    default_sort: "-created_at",
+};
+
+#[async_trait]
+pub trait VirtualTurnRepository: Send + Sync + 'static {
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<VirtualTurn>>;
+}
+
+pub struct PgVirtualTurnRepository {
+    pool: PgPool,
+}
+
+impl PgVirtualTurnRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl VirtualTurnRepository for PgVirtualTurnRepository {
+    async fn get_by_patient(&self, id_patient: i32, page: &PageParams) -> Result<Page<VirtualTurn>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            q.push(" AND id_patient = ").push_bind(id_patient);
+        })
+        .await?;
+        Ok(result)
+    }
+}
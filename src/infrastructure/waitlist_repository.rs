@@ -0,0 +1,430 @@
+use crate::domain::appointment::Appointment;
+use crate::domain::pagination::{Page, PageParams};
+use crate::domain::waitlist::{
+    CreateWaitlistEntry, WaitlistEntry, WaitlistFilter, WaitlistOffer, WaitlistSlot, ENTRY_BOOKED, ENTRY_CANCELED,
+    ENTRY_OFFERED, ENTRY_WAITING, OFFER_ACCEPTED, OFFER_DECLINED, OFFER_EXPIRED, OFFER_PENDING,
+};
+use crate::infrastructure::pagination::{fetch_page, ListQuery, SortField};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+
+const LIST: ListQuery = ListQuery {
+    columns: "*",
+    from: "waitlist_entries",
+    base_filter: "TRUE",
+    id_column: "id_entry",
+    sort_fields: &[
+        SortField { name: "id_entry", column: "id_entry", sql_type: "int" },
+        SortField { name: "created_at", column: "created_at", sql_type: "timestamp" },
+        SortField { name: "date_from", column: "date_from", sql_type: "date" },
+    ],
+    default_sort: "created_at",
+};
+
+#[async_trait]
+pub trait WaitlistRepository: Send + Sync + 'static {
+    async fn get_entries(&self, filter: &WaitlistFilter, page: &PageParams) -> Result<Page<WaitlistEntry>>;
+    async fn get_entry(&self, id: i32) -> Result<Option<WaitlistEntry>>;
+    async fn create_entry(&self, data: CreateWaitlistEntry, created_by: i32) -> Result<WaitlistEntry>;
+    /// Saca la entrada de la lista si sigue esperando o con una oferta pendiente
+    async fn cancel_entry(&self, id: i32) -> Result<Option<WaitlistEntry>>;
+    /// Ofertas de la entrada, de la más reciente a la más antigua
+    async fn get_offers(&self, id_entry: i32) -> Result<Vec<WaitlistOffer>>;
+    async fn get_offer(&self, id: i32) -> Result<Option<WaitlistOffer>>;
+    /// Oferta pendiente de la entrada, si tiene
+    async fn get_pending_offer(&self, id_entry: i32) -> Result<Option<WaitlistOffer>>;
+    /// Ofrece el turno a la primera entrada que lo acepta (la más antigua): el
+    /// doctor, su especialidad o su servicio coinciden, el turno cae en su rango
+    /// de fechas y horas, no se le ofreció antes y el paciente no tiene otra cita
+    /// a esa hora. None si nadie lo acepta o si el turno ya no está libre.
+    async fn offer_next(&self, slot: &WaitlistSlot, expires_at: NaiveDateTime) -> Result<Option<WaitlistOffer>>;
+    /// Crea la cita y cierra la oferta y la entrada en una transacción.
+    /// None si la oferta ya no está pendiente o venció a `now` (hora local).
+    async fn accept_offer(&self, id: i32, now: NaiveDateTime) -> Result<Option<(WaitlistOffer, Appointment)>>;
+    /// La entrada vuelve a esperar otros turnos. None si la oferta ya no está pendiente.
+    async fn decline_offer(&self, id: i32) -> Result<Option<WaitlistOffer>>;
+    /// Marca vencidas las ofertas pendientes con `expires_at` anterior a `now` y
+    /// devuelve sus entradas a la espera
+    async fn expire_offers(&self, now: NaiveDateTime) -> Result<Vec<WaitlistOffer>>;
+    /// Oferta vigente a `now` que reserva un turno del doctor que se cruza con el rango
+    async fn find_hold(
+        &self,
+        id_doctor: i32,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        now: NaiveDateTime,
+    ) -> Result<Option<WaitlistOffer>>;
+    /// El usuario es el propio paciente o su tutor legal
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool>;
+    /// Canal y destino para avisar al paciente (correo si tiene, si no teléfono)
+    async fn get_patient_contact(&self, id_patient: i32) -> Result<Option<(String, String)>>;
+}
+
+pub struct PgWaitlistRepository {
+    pool: PgPool,
+}
+
+impl PgWaitlistRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WaitlistRepository for PgWaitlistRepository {
+    async fn get_entries(&self, filter: &WaitlistFilter, page: &PageParams) -> Result<Page<WaitlistEntry>> {
+        let result = fetch_page(&self.pool, &LIST, page, |q| {
+            if let Some(id_patient) = filter.id_patient {
+                q.push(" AND id_patient = ").push_bind(id_patient);
+            }
+            if let Some(id_doctor) = filter.id_doctor {
+                q.push(" AND id_doctor = ").push_bind(id_doctor);
+            }
+            if let Some(id_speciality) = filter.id_speciality {
+                q.push(" AND id_speciality = ").push_bind(id_speciality);
+            }
+            if let Some(id_service) = filter.id_service {
+                q.push(" AND id_service = ").push_bind(id_service);
+            }
+            if let Some(status) = &filter.status {
+                q.push(" AND status = ").push_bind(status.clone());
+            }
+        })
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_entry(&self, id: i32) -> Result<Option<WaitlistEntry>> {
+        let result = sqlx::query_as::<_, WaitlistEntry>("SELECT * FROM waitlist_entries WHERE id_entry = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn create_entry(&self, data: CreateWaitlistEntry, created_by: i32) -> Result<WaitlistEntry> {
+        let result = sqlx::query_as::<_, WaitlistEntry>(
+            "INSERT INTO waitlist_entries
+            (id_patient, id_doctor, id_speciality, id_service, date_from, date_to, time_from, time_to, notes, created_by)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+            RETURNING *",
+        )
+        .bind(data.id_patient)
+        .bind(data.id_doctor)
+        .bind(data.id_speciality)
+        .bind(data.id_service)
+        .bind(data.date_from)
+        .bind(data.date_to)
+        .bind(data.time_from)
+        .bind(data.time_to)
+        .bind(data.notes)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn cancel_entry(&self, id: i32) -> Result<Option<WaitlistEntry>> {
+        let result = sqlx::query_as::<_, WaitlistEntry>(
+            "UPDATE waitlist_entries SET status = $2, updated_at = NOW()
+             WHERE id_entry = $1 AND status IN ($3, $4)
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(ENTRY_CANCELED)
+        .bind(ENTRY_WAITING)
+        .bind(ENTRY_OFFERED)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_offers(&self, id_entry: i32) -> Result<Vec<WaitlistOffer>> {
+        let result = sqlx::query_as::<_, WaitlistOffer>(
+            "SELECT * FROM waitlist_offers WHERE id_entry = $1 ORDER BY created_at DESC, id_offer DESC",
+        )
+        .bind(id_entry)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn get_offer(&self, id: i32) -> Result<Option<WaitlistOffer>> {
+        let result = sqlx::query_as::<_, WaitlistOffer>("SELECT * FROM waitlist_offers WHERE id_offer = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_pending_offer(&self, id_entry: i32) -> Result<Option<WaitlistOffer>> {
+        let result = sqlx::query_as::<_, WaitlistOffer>(
+            "SELECT * FROM waitlist_offers WHERE id_entry = $1 AND status = $2",
+        )
+        .bind(id_entry)
+        .bind(OFFER_PENDING)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn offer_next(&self, slot: &WaitlistSlot, expires_at: NaiveDateTime) -> Result<Option<WaitlistOffer>> {
+        let mut tx = self.pool.begin().await?;
+
+        // El turno pudo ocuparse desde que se liberó
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (
+                 SELECT 1 FROM medical_appointments a
+                 WHERE a.id_doctor = $1 AND a.deleted_at IS NULL AND a.status NOT IN ('canceled', 'no_show')
+                   AND tsrange(a.appointment_datetime, a.appointment_datetime + a.duration_minutes * INTERVAL '1 minute')
+                       && tsrange($2, $2 + $3 * INTERVAL '1 minute')
+             )",
+        )
+        .bind(slot.id_doctor)
+        .bind(slot.appointment_datetime)
+        .bind(slot.duration_minutes)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Ok(None);
+        }
+
+        // SKIP LOCKED: dos turnos liberados a la vez no se ofrecen a la misma entrada
+        let entry = sqlx::query_as::<_, WaitlistEntry>(
+            "SELECT e.* FROM waitlist_entries e
+             JOIN patients p ON p.id_patient = e.id_patient AND p.deleted_at IS NULL
+             JOIN doctors d ON d.id_doctor = $1
+             WHERE e.status = $6
+               AND p.status IS DISTINCT FROM 'deceased'
+               AND (e.id_doctor IS NULL OR e.id_doctor = d.id_doctor)
+               AND (e.id_speciality IS NULL OR e.id_speciality = d.id_speciality)
+               AND (e.id_service IS NULL OR e.id_service = d.id_service OR e.id_service = $2)
+               AND $3::date BETWEEN e.date_from AND e.date_to
+               AND (e.time_from IS NULL OR ($3::time >= e.time_from AND $3::time < e.time_to))
+               AND e.id_patient IS DISTINCT FROM (
+                   SELECT c.id_patient FROM medical_appointments c WHERE c.id_appointment = $5
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM waitlist_offers o
+                   WHERE o.id_entry = e.id_entry AND o.id_doctor = $1 AND o.appointment_datetime = $3
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM medical_appointments a
+                   WHERE a.id_patient = e.id_patient AND a.deleted_at IS NULL
+                     AND a.status NOT IN ('canceled', 'no_show')
+                     AND tsrange(a.appointment_datetime, a.appointment_datetime + a.duration_minutes * INTERVAL '1 minute')
+                         && tsrange($3, $3 + $4 * INTERVAL '1 minute')
+               )
+             ORDER BY e.created_at, e.id_entry
+             LIMIT 1
+             FOR UPDATE OF e SKIP LOCKED",
+        )
+        .bind(slot.id_doctor)
+        .bind(slot.id_service)
+        .bind(slot.appointment_datetime)
+        .bind(slot.duration_minutes)
+        .bind(slot.id_canceled_appointment)
+        .bind(ENTRY_WAITING)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+
+        // Si otra oferta ya reserva el turno, el índice único lo impide y no se ofrece
+        let offer = sqlx::query_as::<_, WaitlistOffer>(
+            "INSERT INTO waitlist_offers
+            (id_entry, id_patient, id_doctor, id_area, id_service, appointment_datetime, duration_minutes,
+             building, room, id_canceled_appointment, status, expires_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+            ON CONFLICT (id_doctor, appointment_datetime) WHERE status = 'pending' DO NOTHING
+            RETURNING *",
+        )
+        .bind(entry.id_entry)
+        .bind(entry.id_patient)
+        .bind(slot.id_doctor)
+        .bind(slot.id_area)
+        .bind(entry.id_service.unwrap_or(slot.id_service))
+        .bind(slot.appointment_datetime)
+        .bind(slot.duration_minutes)
+        .bind(slot.building.as_deref())
+        .bind(slot.room.as_deref())
+        .bind(slot.id_canceled_appointment)
+        .bind(OFFER_PENDING)
+        .bind(expires_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(offer) = offer else {
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE waitlist_entries SET status = $2, updated_at = NOW() WHERE id_entry = $1")
+            .bind(entry.id_entry)
+            .bind(ENTRY_OFFERED)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(offer))
+    }
+
+    async fn accept_offer(&self, id: i32, now: NaiveDateTime) -> Result<Option<(WaitlistOffer, Appointment)>> {
+        let mut tx = self.pool.begin().await?;
+
+        let offer = sqlx::query_as::<_, WaitlistOffer>(
+            "UPDATE waitlist_offers SET status = $2, responded_at = NOW()
+             WHERE id_offer = $1 AND status = $3 AND expires_at > $4
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(OFFER_ACCEPTED)
+        .bind(OFFER_PENDING)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(offer) = offer else {
+            return Ok(None);
+        };
+
+        // Un cruce (23P01) revierte la aceptación y llega como 409
+        let appointment = sqlx::query_as::<_, Appointment>(
+            "INSERT INTO medical_appointments
+            (id_patient, id_doctor, id_area, id_service, appointment_datetime, duration_minutes, building, room, notes, created_at)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,'Turno de la lista de espera',$9)
+            RETURNING *",
+        )
+        .bind(offer.id_patient)
+        .bind(offer.id_doctor)
+        .bind(offer.id_area)
+        .bind(offer.id_service)
+        .bind(offer.appointment_datetime)
+        .bind(offer.duration_minutes)
+        .bind(offer.building.as_deref())
+        .bind(offer.room.as_deref())
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let offer = sqlx::query_as::<_, WaitlistOffer>(
+            "UPDATE waitlist_offers SET id_appointment = $2 WHERE id_offer = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(appointment.id_appointment)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE waitlist_entries SET status = $2, updated_at = NOW() WHERE id_entry = $1")
+            .bind(offer.id_entry)
+            .bind(ENTRY_BOOKED)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some((offer, appointment)))
+    }
+
+    async fn decline_offer(&self, id: i32) -> Result<Option<WaitlistOffer>> {
+        let mut tx = self.pool.begin().await?;
+
+        let offer = sqlx::query_as::<_, WaitlistOffer>(
+            "UPDATE waitlist_offers SET status = $2, responded_at = NOW()
+             WHERE id_offer = $1 AND status = $3
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(OFFER_DECLINED)
+        .bind(OFFER_PENDING)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(offer) = offer else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "UPDATE waitlist_entries SET status = $2, updated_at = NOW() WHERE id_entry = $1 AND status = $3",
+        )
+        .bind(offer.id_entry)
+        .bind(ENTRY_WAITING)
+        .bind(ENTRY_OFFERED)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(offer))
+    }
+
+    async fn expire_offers(&self, now: NaiveDateTime) -> Result<Vec<WaitlistOffer>> {
+        let mut tx = self.pool.begin().await?;
+
+        let offers = sqlx::query_as::<_, WaitlistOffer>(
+            "UPDATE waitlist_offers SET status = $1
+             WHERE status = $2 AND expires_at <= $3
+             RETURNING *",
+        )
+        .bind(OFFER_EXPIRED)
+        .bind(OFFER_PENDING)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let entries: Vec<i32> = offers.iter().map(|o| o.id_entry).collect();
+        sqlx::query(
+            "UPDATE waitlist_entries SET status = $2, updated_at = NOW() WHERE id_entry = ANY($1) AND status = $3",
+        )
+        .bind(&entries)
+        .bind(ENTRY_WAITING)
+        .bind(ENTRY_OFFERED)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(offers)
+    }
+
+    async fn find_hold(
+        &self,
+        id_doctor: i32,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        now: NaiveDateTime,
+    ) -> Result<Option<WaitlistOffer>> {
+        let result = sqlx::query_as::<_, WaitlistOffer>(
+            "SELECT * FROM waitlist_offers
+             WHERE id_doctor = $1 AND status = $4 AND expires_at > $5
+               AND tsrange(appointment_datetime, appointment_datetime + duration_minutes * INTERVAL '1 minute')
+                   && tsrange($2, $3)
+             LIMIT 1",
+        )
+        .bind(id_doctor)
+        .bind(start)
+        .bind(end)
+        .bind(OFFER_PENDING)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+
+    async fn can_act_for(&self, id_patient: i32, id_user: i32) -> Result<bool> {
+        let result: bool = sqlx::query_scalar("SELECT can_act_for_patient($1, $2)")
+            .bind(id_user)
+            .bind(id_patient)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    async fn get_patient_contact(&self, id_patient: i32) -> Result<Option<(String, String)>> {
+        let result = sqlx::query_as::<_, (String, String)>(
+            "SELECT CASE WHEN email IS NOT NULL THEN 'email' ELSE 'phone' END, COALESCE(email, phone)
+             FROM patients
+             WHERE id_patient = $1 AND COALESCE(email, phone) IS NOT NULL",
+        )
+        .bind(id_patient)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(result)
+    }
+}
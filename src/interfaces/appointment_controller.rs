@@ -1,102 +1,330 @@
 use crate::{
     application::appointment_service::AppointmentService,
-    domain::appointment::{CreateAppointment, UpdateAppointment},
+    domain::{
+        appointment::{
+            AppointmentFilter, AppointmentTransition, BookAppointment, CreateAppointment, RescheduleAppointment,
+            TransitionAppointment, TransitionResult, UpdateAppointment,
+        },
+        appointment_series::{CreateAppointmentSeries, ScopeParams, SeriesChange, SeriesParams, UpdateSeriesOccurrences},
+        doctor_schedule::SlotFilter,
+        pagination::PageParams,
+    },
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppJson, AppPath, AppQuery, AppResult},
+    },
 };
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use std::sync::Arc;
+use uuid::Uuid;
 use validator::Validate;
 
-pub type SharedAppointmentService =
-    Arc<AppointmentService<crate::infrastructure::appointment_repository::PgAppointmentRepository>>;
-
-pub async fn get_all(State(service): State<SharedAppointmentService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(appointments) => (StatusCode::OK, Json(appointments)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al obtener citas médicas",
-        )
-            .into_response(),
-    }
+pub type SharedAppointmentService = Arc<
+    AppointmentService<
+        crate::infrastructure::appointment_repository::PgAppointmentRepository,
+        crate::infrastructure::doctor_schedule_repository::PgDoctorScheduleRepository,
+        crate::infrastructure::waitlist_repository::PgWaitlistRepository,
+        crate::infrastructure::notifier::LogNotifier,
+    >,
+>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Cita médica no encontrada".to_string())
+}
+
+pub async fn get_all(
+    State(service): State<SharedAppointmentService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<AppointmentFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let appointments = service.get_all(&filter, &page).await?;
+    Ok((StatusCode::OK, Json(appointments)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
-    State(service): State<SharedAppointmentService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(a)) => (StatusCode::OK, Json(a)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Cita médica no encontrada").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al buscar cita médica",
-        )
-            .into_response(),
-    }
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+) -> AppResult<impl IntoResponse> {
+    let a = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(a)))
 }
 
 pub async fn create(
+    auth: AuthUser,
+    State(service): State<SharedAppointmentService>,
+    AppJson(payload): AppJson<CreateAppointment>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let a = service.create(payload, &auth).await?.ok_or_else(|| {
+        AppError::Forbidden("No puede agendar citas para este paciente".to_string())
+    })?;
+    Ok((StatusCode::CREATED, Json(a)))
+}
+
+/// Próximos turnos libres de una especialidad, servicio, área o doctor
+pub async fn get_free_slots(
+    State(service): State<SharedAppointmentService>,
+    AppQuery(filter): AppQuery<SlotFilter>,
+) -> AppResult<impl IntoResponse> {
+    filter.validate()?;
+
+    let slots = service.find_free_slots(&filter).await?;
+    Ok((StatusCode::OK, Json(slots)))
+}
+
+/// Reserva un turno libre; si otro lo tomó primero responde 409
+pub async fn book(
+    auth: AuthUser,
+    State(service): State<SharedAppointmentService>,
+    AppJson(payload): AppJson<BookAppointment>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let a = service.book(payload, &auth).await?.ok_or_else(|| {
+        AppError::Forbidden("No puede agendar citas para este paciente".to_string())
+    })?;
+    Ok((StatusCode::CREATED, Json(a)))
+}
+
+pub async fn update(
     State(service): State<SharedAppointmentService>,
-    Json(payload): Json<CreateAppointment>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
+    AppPath(id): AppPath<i32>,
+    AppJson(payload): AppJson<UpdateAppointment>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let a = service.update(id, payload).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(a)))
+}
+
+pub async fn delete(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo un administrador puede eliminar citas".to_string()));
     }
+    let a = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(a)))
+}
+
+async fn transition(
+    auth: AuthUser,
+    id: i32,
+    service: SharedAppointmentService,
+    transition: AppointmentTransition,
+    payload: TransitionAppointment,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.create(payload).await {
-        Ok(a) => (StatusCode::CREATED, Json(a)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al crear cita médica",
-        )
-            .into_response(),
+    match service.transition(id, transition, payload, &auth).await?.ok_or_else(not_found)? {
+        TransitionResult::Done(a) => Ok((StatusCode::OK, Json(a))),
+        TransitionResult::Forbidden(message) => Err(AppError::Forbidden(message.to_string())),
     }
 }
 
-pub async fn update(
+pub async fn confirm(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    transition(auth, id, service, AppointmentTransition::Confirm, payload).await
+}
+
+pub async fn check_in(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    transition(auth, id, service, AppointmentTransition::CheckIn, payload).await
+}
+
+pub async fn start(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    transition(auth, id, service, AppointmentTransition::Start, payload).await
+}
+
+pub async fn complete(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    transition(auth, id, service, AppointmentTransition::Complete, payload).await
+}
+
+pub async fn cancel(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedAppointmentService>,
-    Path(id): Path<i32>,
-    Json(payload): Json<UpdateAppointment>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
+    AppJson(payload): AppJson<TransitionAppointment>,
+) -> AppResult<impl IntoResponse> {
+    transition(auth, id, service, AppointmentTransition::Cancel, payload).await
+}
+
+pub async fn no_show(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    transition(auth, id, service, AppointmentTransition::NoShow, payload).await
+}
+
+async fn respond_to_reminder(
+    token: Uuid,
+    service: SharedAppointmentService,
+    transition: AppointmentTransition,
+    payload: TransitionAppointment,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let a = service
+        .respond_to_reminder(token, transition, payload)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Recordatorio no encontrado".to_string()))?;
+    Ok((StatusCode::OK, Json(a)))
+}
+
+/// Enlace de confirmación del recordatorio; público, el token autoriza
+pub async fn confirm_by_reminder(
+    AppPath(token): AppPath<Uuid>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    respond_to_reminder(token, service, AppointmentTransition::Confirm, payload).await
+}
+
+/// Enlace de cancelación del recordatorio; público, el token autoriza
+pub async fn cancel_by_reminder(
+    AppPath(token): AppPath<Uuid>,
+    State(service): State<SharedAppointmentService>,
+    payload: Option<AppJson<TransitionAppointment>>,
+) -> AppResult<impl IntoResponse> {
+    let payload = payload.map(|AppJson(p)| p).unwrap_or_default();
+    respond_to_reminder(token, service, AppointmentTransition::Cancel, payload).await
+}
+
+/// Cambios de estado con su motivo y quién los hizo
+pub async fn get_status_history(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede ver el historial de la cita".to_string()));
     }
 
-    match service.update(id, payload).await {
-        Ok(Some(a)) => (StatusCode::OK, Json(a)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Cita médica no encontrada").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al actualizar cita médica",
-        )
-            .into_response(),
+    let history = service.get_status_history(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(history)))
+}
+
+/// Mueve la cita a otro horario con motivo; el paciente recibe un aviso
+pub async fn reschedule(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    AppJson(payload): AppJson<RescheduleAppointment>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    match service.reschedule(id, payload, &auth).await?.ok_or_else(not_found)? {
+        TransitionResult::Done(a) => Ok((StatusCode::OK, Json(*a))),
+        TransitionResult::Forbidden(message) => Err(AppError::Forbidden(message.to_string())),
     }
 }
 
-pub async fn delete(
-    Path(id): Path<i32>,
-    State(service): State<SharedAppointmentService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(a)) => (StatusCode::OK, Json(a)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Cita médica no encontrada").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar cita médica",
-        )
-            .into_response(),
+/// Horarios anteriores de la cita, con quién la reprogramó y por qué
+pub async fn get_reschedules(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede ver el historial de la cita".to_string()));
+    }
+
+    let history = service.get_reschedules(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(history)))
+}
+
+/// Crea una serie de citas recurrentes; con `dry_run=true` solo muestra qué citas
+/// se crearían y cuáles tienen conflicto
+pub async fn create_series(
+    auth: AuthUser,
+    State(service): State<SharedAppointmentService>,
+    AppQuery(params): AppQuery<SeriesParams>,
+    AppJson(payload): AppJson<CreateAppointmentSeries>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let result = service.create_series(payload, &auth, params.dry_run).await?.ok_or_else(|| {
+        AppError::Forbidden("No puede agendar citas para este paciente".to_string())
+    })?;
+    let status = if params.dry_run { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(result)))
+}
+
+pub async fn get_series(
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+) -> AppResult<impl IntoResponse> {
+    let series = service
+        .get_series(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Serie de citas no encontrada".to_string()))?;
+    Ok((StatusCode::OK, Json(series)))
+}
+
+/// Cambia esta cita, esta y las siguientes o toda la serie (`scope`)
+pub async fn update_series(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    AppQuery(params): AppQuery<ScopeParams>,
+    AppJson(payload): AppJson<UpdateSeriesOccurrences>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    match service.update_series(id, params.scope, payload, &auth).await?.ok_or_else(not_found)? {
+        SeriesChange::Done(appointments) => Ok((StatusCode::OK, Json(appointments))),
+        SeriesChange::Forbidden(message) => Err(AppError::Forbidden(message.to_string())),
+    }
+}
+
+/// Cancela esta cita, esta y las siguientes o toda la serie (`scope`)
+pub async fn cancel_series(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedAppointmentService>,
+    AppQuery(params): AppQuery<ScopeParams>,
+    AppJson(payload): AppJson<TransitionAppointment>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    match service.cancel_series(id, params.scope, payload, &auth).await?.ok_or_else(not_found)? {
+        SeriesChange::Done(appointments) => Ok((StatusCode::OK, Json(appointments))),
+        SeriesChange::Forbidden(message) => Err(AppError::Forbidden(message.to_string())),
     }
-}   
\ No newline at end of file
+}
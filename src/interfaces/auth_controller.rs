@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use crate::application::auth_service::AuthService;
 use sqlx::PgPool;
 use crate::domain::user::UserInfo;
+use crate::helpers::errors::{AppError, AppJson, AppResult};
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
@@ -18,57 +19,38 @@ pub struct LoginRequest {
 struct LoginResponse {
     message: String,
     success: bool,
-    token: Option<String>,
-    user: Option<UserInfo>,
+    token: String,
+    user: UserInfo,
 }
 
 
 pub async fn login_handler(
     State(pool): State<PgPool>,
-    Json(payload): Json<LoginRequest>,
-) -> Response {
+    AppJson(payload): AppJson<LoginRequest>,
+) -> AppResult<Response> {
     let service = AuthService::new(&pool);
 
-    match service.login(&payload.username, &payload.password).await {
-        Ok(Some((token, user_info))) => {
-            let cookie = format!(
-                "auth_token={}; HttpOnly; Secure; SameSite=Strict; Path=/; Max-Age=3600",
-                token
-            );
+    let (token, user_info) = service
+        .login(&payload.username, &payload.password)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Credenciales incorrectas".to_string()))?;
 
-            (
-                StatusCode::OK,
-                [(header::SET_COOKIE, cookie)],
-                Json(LoginResponse {
-                    message: "Login exitoso".to_string(),
-                    success: true,
-                    token: Some(token),
-                    user: Some(user_info),
-                }),
-            )
-                .into_response()
-        }
-        Ok(None) => (
-            StatusCode::UNAUTHORIZED,
-            Json(LoginResponse {
-                message: "Credenciales incorrectas".to_string(),
-                success: false,
-                token: None,
-                user: None,
-            }),
-        )
-            .into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(LoginResponse {
-                message: "Error interno".to_string(),
-                success: false,
-                token: None,
-                user: None,
-            }),
-        )
-            .into_response(),
-    }
+    let cookie = format!(
+        "auth_token={}; HttpOnly; Secure; SameSite=Strict; Path=/; Max-Age=3600",
+        token
+    );
+
+    Ok((
+        StatusCode::OK,
+        [(header::SET_COOKIE, cookie)],
+        Json(LoginResponse {
+            message: "Login exitoso".to_string(),
+            success: true,
+            token,
+            user: user_info,
+        }),
+    )
+        .into_response())
 }
 
 
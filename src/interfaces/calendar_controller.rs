@@ -0,0 +1,76 @@
+use crate::application::calendar_service::{feed_owner, CalendarService};
+use crate::domain::calendar::CalendarFeedLink;
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppPath, AppResult};
+use crate::infrastructure::calendar_repository::PgCalendarRepository;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub type SharedCalendarService = Arc<CalendarService<PgCalendarRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Calendario no encontrado".to_string())
+}
+
+fn require_agenda(auth: &AuthUser) -> AppResult<()> {
+    if feed_owner(&auth.role).is_none() {
+        return Err(AppError::Forbidden("Solo doctores y pacientes tienen calendario de citas".to_string()));
+    }
+    Ok(())
+}
+
+/// Ruta del calendario del usuario, si ya generó una
+pub async fn get_feed(
+    auth: AuthUser,
+    State(service): State<SharedCalendarService>,
+) -> AppResult<impl IntoResponse> {
+    require_agenda(&auth)?;
+
+    let feed = service.get_feed(auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(CalendarFeedLink::from(feed))))
+}
+
+/// Genera la URL secreta del calendario, o una nueva si la anterior se filtró
+pub async fn rotate_feed(
+    auth: AuthUser,
+    State(service): State<SharedCalendarService>,
+) -> AppResult<impl IntoResponse> {
+    require_agenda(&auth)?;
+
+    let feed = service.rotate_feed(auth.id_user).await?;
+    Ok((StatusCode::CREATED, Json(CalendarFeedLink::from(feed))))
+}
+
+pub async fn delete_feed(
+    auth: AuthUser,
+    State(service): State<SharedCalendarService>,
+) -> AppResult<impl IntoResponse> {
+    let feed = service.delete_feed(auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(CalendarFeedLink::from(feed))))
+}
+
+/// Calendario público para las aplicaciones de calendario: el token autoriza.
+/// Acepta el token con o sin `.ics`.
+pub async fn get_calendar(
+    AppPath(token): AppPath<String>,
+    State(service): State<SharedCalendarService>,
+) -> AppResult<impl IntoResponse> {
+    let token = Uuid::parse_str(token.strip_suffix(".ics").unwrap_or(&token)).map_err(|_| not_found())?;
+    let calendar = service.render(token).await?.ok_or_else(not_found)?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "inline; filename=\"citas.ics\""),
+            (header::CACHE_CONTROL, "private, no-store"),
+        ],
+        calendar,
+    ))
+}
@@ -0,0 +1,36 @@
+use crate::application::doctor_agenda_service::DoctorAgendaService;
+use crate::domain::doctor_agenda::AgendaParams;
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppPath, AppQuery, AppResult};
+use crate::infrastructure::access_log_repository::PgAccessLogRepository;
+use crate::infrastructure::doctor_agenda_repository::PgDoctorAgendaRepository;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+
+pub type SharedDoctorAgendaService = Arc<DoctorAgendaService<PgDoctorAgendaRepository, PgAccessLogRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Doctor no encontrado".to_string())
+}
+
+/// Citas del día del doctor con alergias y último diagnóstico de cada paciente.
+/// La ve el propio doctor, admisión o un administrador.
+pub async fn get_agenda(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedDoctorAgendaService>,
+    AppQuery(params): AppQuery<AgendaParams>,
+) -> AppResult<impl IntoResponse> {
+    let own = service.is_doctor_user(id, auth.id_user).await?.ok_or_else(not_found)?;
+    if !(auth.is_admin() || auth.is_admisionist() || (auth.is_doctor() && own)) {
+        return Err(AppError::Forbidden("No puede ver la agenda de este doctor".to_string()));
+    }
+
+    let agenda = service.get_agenda(id, &params, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(agenda)))
+}
@@ -1,9 +1,13 @@
 use crate::{
     application::doctor_service::DoctorService,
-    domain::doctor::{CreateDoctor, UpdateDoctor},
+    domain::{
+        doctor::{CreateDoctor, UpdateDoctor, DoctorFilter},
+        pagination::PageParams,
+    },
+    helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult},
 };
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -14,87 +18,54 @@ use validator::Validate;
 pub type SharedDoctorService =
     Arc<DoctorService<crate::infrastructure::doctor_repository::PgDoctorRepository>>;
 
-pub async fn get_all(State(service): State<SharedDoctorService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(doctores) => (StatusCode::OK, Json(doctores)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al obtener doctores",
-        )
-            .into_response(),
-    }
+fn not_found() -> AppError {
+    AppError::NotFound("Doctor no encontrado".to_string())
+}
+
+pub async fn get_all(
+    State(service): State<SharedDoctorService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<DoctorFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let doctores = service.get_all(&filter, &page).await?;
+    Ok((StatusCode::OK, Json(doctores)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedDoctorService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(d)) => (StatusCode::OK, Json(d)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Doctor no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al buscar doctor").into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let doctor = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(doctor)))
 }
 
 pub async fn create(
     State(service): State<SharedDoctorService>,
-    Json(payload): Json<CreateDoctor>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppJson(payload): AppJson<CreateDoctor>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.create(payload).await {
-        Ok(doc) => (StatusCode::CREATED, Json(doc)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al crear doctor",
-        )
-        .into_response(),
-    }
+    let doc = service.create(payload).await?;
+    Ok((StatusCode::CREATED, Json(doc)))
 }
 
-
 pub async fn update(
     State(service): State<SharedDoctorService>,
-    Path(id): Path<i32>,
-    Json(payload): Json<UpdateDoctor>, 
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {  
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppPath(id): AppPath<i32>,
+    AppJson(payload): AppJson<UpdateDoctor>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.update(id, payload).await { 
-        Ok(Some(doc)) => (StatusCode::OK, Json(doc)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Doctor no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al actualizar doctor",
-        )
-        .into_response(),
-    }
+    let doc = service.update(id, payload).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(doc)))
 }
 
-
 pub async fn delete(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedDoctorService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(d)) => (StatusCode::OK, Json(d)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Doctor no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar doctor",
-        )
-            .into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let doc = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(doc)))
 }
@@ -0,0 +1,118 @@
+use crate::{
+    application::doctor_schedule_service::DoctorScheduleService,
+    domain::doctor_schedule::{CreateDoctorSchedule, CreateDoctorScheduleException, ExceptionFilter},
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppJson, AppPath, AppQuery, AppResult},
+    },
+    infrastructure::doctor_schedule_repository::PgDoctorScheduleRepository,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedDoctorScheduleService = Arc<DoctorScheduleService<PgDoctorScheduleRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Doctor no encontrado".to_string())
+}
+
+fn require_scheduler(auth: &AuthUser) -> AppResult<()> {
+    if !auth.is_admin() && !auth.is_admisionist() {
+        return Err(AppError::Forbidden("Solo admisión o administradores pueden gestionar horarios".to_string()));
+    }
+    Ok(())
+}
+
+/// Admisión y administradores gestionan las ausencias de cualquier doctor;
+/// un doctor, solo las suyas
+async fn require_exception_manager(
+    service: &SharedDoctorScheduleService,
+    id_doctor: i32,
+    auth: &AuthUser,
+) -> AppResult<()> {
+    let own = service.is_doctor_user(id_doctor, auth.id_user).await?.ok_or_else(not_found)?;
+    let allowed = auth.is_admin() || auth.is_admisionist() || (auth.is_doctor() && own);
+    if !allowed {
+        return Err(AppError::Forbidden("No puede gestionar las ausencias de este doctor".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn get_schedules(
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedDoctorScheduleService>,
+) -> AppResult<impl IntoResponse> {
+    let schedules = service.get_schedules(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(schedules)))
+}
+
+pub async fn create_schedule(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedDoctorScheduleService>,
+    AppJson(payload): AppJson<CreateDoctorSchedule>,
+) -> AppResult<impl IntoResponse> {
+    require_scheduler(&auth)?;
+    payload.validate()?;
+
+    let schedule = service.create_schedule(id, payload).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+pub async fn delete_schedule(
+    auth: AuthUser,
+    AppPath((id, id_schedule)): AppPath<(i32, i32)>,
+    State(service): State<SharedDoctorScheduleService>,
+) -> AppResult<impl IntoResponse> {
+    require_scheduler(&auth)?;
+
+    let schedule = service
+        .delete_schedule(id, id_schedule)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Bloque de horario no encontrado".to_string()))?;
+    Ok((StatusCode::OK, Json(schedule)))
+}
+
+pub async fn get_exceptions(
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedDoctorScheduleService>,
+    AppQuery(filter): AppQuery<ExceptionFilter>,
+) -> AppResult<impl IntoResponse> {
+    let exceptions = service.get_exceptions(id, &filter).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(exceptions)))
+}
+
+/// Registra una ausencia. Las citas activas que quedan dentro no se cancelan;
+/// se devuelven en `affected_appointments` para reprogramarlas.
+pub async fn create_exception(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedDoctorScheduleService>,
+    AppJson(payload): AppJson<CreateDoctorScheduleException>,
+) -> AppResult<impl IntoResponse> {
+    require_exception_manager(&service, id, &auth).await?;
+    payload.validate()?;
+
+    let exception = service.create_exception(id, payload, auth.id_user).await?;
+    Ok((StatusCode::CREATED, Json(exception)))
+}
+
+pub async fn delete_exception(
+    auth: AuthUser,
+    AppPath((id, id_exception)): AppPath<(i32, i32)>,
+    State(service): State<SharedDoctorScheduleService>,
+) -> AppResult<impl IntoResponse> {
+    require_exception_manager(&service, id, &auth).await?;
+
+    let exception = service
+        .delete_exception(id, id_exception)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Ausencia no encontrada".to_string()))?;
+    Ok((StatusCode::OK, Json(exception)))
+}
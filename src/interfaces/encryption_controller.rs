@@ -0,0 +1,34 @@
+use crate::{
+    application::encryption_service::EncryptionService,
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppResult},
+    },
+    infrastructure::{
+        blob_store::LocalBlobStore,
+        medical_document_repository::PgMedicalDocumentRepository,
+        patient_repository::PgPatientRepository,
+    },
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+
+pub type SharedEncryptionService =
+    Arc<EncryptionService<PgPatientRepository, PgMedicalDocumentRepository, LocalBlobStore>>;
+
+pub async fn rotate(
+    auth: AuthUser,
+    State(service): State<SharedEncryptionService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo un administrador puede rotar llaves".to_string()));
+    }
+
+    let report = service.rotate().await?;
+    Ok((StatusCode::OK, Json(report)))
+}
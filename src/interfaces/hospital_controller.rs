@@ -1,43 +1,41 @@
-use axum::{extract::{State, Path}, response::IntoResponse, Json, http::StatusCode};
-use crate::{application::hospital_service::HospitalService, domain::hospital::CreateHospital};
+use axum::{extract::State, response::IntoResponse, Json, http::StatusCode};
+use crate::{application::hospital_service::HospitalService, domain::hospital::CreateHospital, domain::pagination::PageParams};
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
 use std::sync::Arc;
+use validator::Validate;
 
 pub type SharedHospitalService = Arc<HospitalService<crate::infrastructure::hospital_repository::PgHospitalRepository>>;
 
-pub async fn get_all(State(service): State<SharedHospitalService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(hospitales) => (StatusCode::OK, Json(hospitales)).into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al obtener hospitales").into_response(),
-    }
+fn not_found() -> AppError {
+    AppError::NotFound("Hospital no encontrado".to_string())
 }
 
-pub async fn get_by_id(Path(id): Path<i32>, State(service): State<SharedHospitalService>) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(h)) => (StatusCode::OK, Json(h)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Hospital no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al buscar hospital").into_response(),
-    }
+pub async fn get_all(
+    State(service): State<SharedHospitalService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let hospitales = service.get_all(&page).await?;
+    Ok((StatusCode::OK, Json(hospitales)))
+}
+
+pub async fn get_by_id(AppPath(id): AppPath<i32>, State(service): State<SharedHospitalService>) -> AppResult<impl IntoResponse> {
+    let h = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(h)))
 }
 
-pub async fn create(State(service): State<SharedHospitalService>, Json(data): Json<CreateHospital>) -> impl IntoResponse {
-    match service.create(data).await {
-        Ok(h) => (StatusCode::CREATED, Json(h)).into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al crear hospital").into_response(),
-    }
+pub async fn create(State(service): State<SharedHospitalService>, AppJson(data): AppJson<CreateHospital>) -> AppResult<impl IntoResponse> {
+    let h = service.create(data).await?;
+    Ok((StatusCode::CREATED, Json(h)))
 }
 
-pub async fn update(Path(id): Path<i32>, State(service): State<SharedHospitalService>, Json(data): Json<CreateHospital>) -> impl IntoResponse {
-    match service.update(id, data).await {
-        Ok(Some(h)) => (StatusCode::OK, Json(h)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Hospital no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al actualizar hospital").into_response(),
-    }
+pub async fn update(AppPath(id): AppPath<i32>, State(service): State<SharedHospitalService>, AppJson(data): AppJson<CreateHospital>) -> AppResult<impl IntoResponse> {
+    let h = service.update(id, data).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(h)))
 }
 
-pub async fn delete(Path(id): Path<i32>, State(service): State<SharedHospitalService>) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(h)) => (StatusCode::OK, Json(h)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Hospital no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al eliminar hospital").into_response(),
-    }
+pub async fn delete(AppPath(id): AppPath<i32>, State(service): State<SharedHospitalService>) -> AppResult<impl IntoResponse> {
+    let h = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(h)))
 }
@@ -1,9 +1,17 @@
 use crate::{
     application::lab_result_service::LabResultService,
-    domain::lab_result::{CreateLabResult, UpdateLabResult},
+    domain::{
+        lab_result::{CreateLabResult, UpdateLabResult, LabResultFilter},
+        pagination::PageParams,
+    },
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppJson, AppPath, AppQuery, AppResult},
+    },
+    infrastructure::{access_log_repository::PgAccessLogRepository, lab_result::PgLabResultRepository},
 };
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -12,92 +20,61 @@ use std::sync::Arc;
 use validator::Validate;
 
 pub type SharedLabResultService =
-    Arc<LabResultService<crate::infrastructure::lab_result::PgLabResultRepository>>;
+    Arc<LabResultService<PgLabResultRepository, PgAccessLogRepository>>;
 
-pub async fn get_all(State(service): State<SharedLabResultService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(lab_results) => (StatusCode::OK, Json(lab_results)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al obtener lab_results",
-        )
-            .into_response(),
+fn not_found() -> AppError {
+    AppError::NotFound("Lab result no encontrado".to_string())
+}
+
+pub async fn get_all(
+    auth: AuthUser,
+    State(service): State<SharedLabResultService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<LabResultFilter>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede listar los resultados de laboratorio".to_string()));
     }
+    page.validate()?;
+
+    let lab_results = service.get_all(&filter, &page, auth.id_user).await?;
+    Ok((StatusCode::OK, Json(lab_results)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedLabResultService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(l)) => (StatusCode::OK, Json(l)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Lab result no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al buscar lab result",
-        )
-            .into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let l = service.get_by_id(id, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(l)))
 }
 
 pub async fn create(
     State(service): State<SharedLabResultService>,
-    Json(payload): Json<CreateLabResult>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppJson(payload): AppJson<CreateLabResult>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.create(payload).await {
-        Ok(l) => (StatusCode::CREATED, Json(l)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al crear lab result",
-        )
-            .into_response(),
-    }
+    let l = service.create(payload).await?;
+    Ok((StatusCode::CREATED, Json(l)))
 }
 
-
 pub async fn update(
     State(service): State<SharedLabResultService>,
-    Path(id): Path<i32>,
-    Json(payload): Json<UpdateLabResult>, 
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {  
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppPath(id): AppPath<i32>,
+    AppJson(payload): AppJson<UpdateLabResult>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.update(id, payload).await { 
-        Ok(Some(l)) => (StatusCode::OK, Json(l)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Lab result no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al actualizar lab result",
-        )
-            .into_response(),
-    }
+    let l = service.update(id, payload).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(l)))
 }
 
 pub async fn delete(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedLabResultService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(l)) => (StatusCode::OK, Json(l)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Lab result no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar lab result",
-        )
-            .into_response(),
-    }
-}   
\ No newline at end of file
+) -> AppResult<impl IntoResponse> {
+    let l = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(l)))
+}
@@ -0,0 +1,192 @@
+use crate::{
+    application::medical_document_service::MedicalDocumentService,
+    domain::{
+        medical_document::{extension_for, UploadMedicalDocument},
+        pagination::PageParams,
+    },
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppPath, AppQuery, AppResult},
+    },
+    infrastructure::{
+        access_log_repository::PgAccessLogRepository,
+        blob_store::LocalBlobStore,
+        encrypted_blob_store::EncryptedBlobStore,
+        medical_document_repository::PgMedicalDocumentRepository,
+    },
+};
+use axum::{
+    body::Body,
+    extract::{Multipart, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedMedicalDocumentService = Arc<
+    MedicalDocumentService<
+        PgMedicalDocumentRepository,
+        EncryptedBlobStore<LocalBlobStore>,
+        PgAccessLogRepository,
+    >,
+>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Documento no encontrado".to_string())
+}
+
+/// Campo numérico del formulario: vacío cuenta como ausente, un valor que no es
+/// un entero es un error en vez de descartarse
+fn parse_id_field(name: &str, value: &str) -> AppResult<Option<i32>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| AppError::BadRequest(format!("{} debe ser un número entero", name)))
+}
+
+pub async fn upload(
+    auth: AuthUser,
+    State(service): State<SharedMedicalDocumentService>,
+    mut multipart: Multipart,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("No tiene permiso para subir documentos".to_string()));
+    }
+
+    let mut id_patient: Option<i32> = None;
+    let mut id_doctor: Option<i32> = None;
+    let mut document_type: Option<String> = None;
+    let mut description: Option<String> = None;
+    let mut file: Option<(Option<String>, String, bytes::Bytes)> = None;
+
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        match name.as_str() {
+            "file" => {
+                let file_name = field.file_name().map(|f| f.to_string());
+                let content_type = field.content_type().unwrap_or_default().to_string();
+                if extension_for(&content_type).is_none() {
+                    return Err(AppError::UnsupportedMediaType(format!(
+                        "Tipo de archivo no permitido: {}",
+                        content_type
+                    )));
+                }
+                // Falla si se excedió el límite del body o se cortó la conexión
+                let data = field.bytes().await?;
+                if data.is_empty() {
+                    return Err(AppError::BadRequest("El archivo está vacío".to_string()));
+                }
+                file = Some((file_name, content_type, data));
+            }
+            _ => {
+                let value = field.text().await?;
+                match name.as_str() {
+                    "id_patient" => id_patient = parse_id_field("id_patient", &value)?,
+                    "id_doctor" => id_doctor = parse_id_field("id_doctor", &value)?,
+                    "document_type" => document_type = Some(value),
+                    "description" => description = Some(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let id_patient =
+        id_patient.ok_or_else(|| AppError::BadRequest("id_patient es requerido".to_string()))?;
+    let (file_name, content_type, data) =
+        file.ok_or_else(|| AppError::BadRequest("El archivo es requerido".to_string()))?;
+
+    let upload = UploadMedicalDocument {
+        id_patient,
+        id_doctor,
+        document_type,
+        description,
+        file_name,
+        content_type,
+        data,
+    };
+
+    let document = service
+        .upload(upload)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Paciente no encontrado".to_string()))?;
+    Ok((StatusCode::CREATED, Json(document)))
+}
+
+pub async fn get_by_id(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedMedicalDocumentService>,
+) -> AppResult<impl IntoResponse> {
+    let document = service.get_by_id(id, &auth).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(document)))
+}
+
+pub async fn get_by_patient(
+    auth: AuthUser,
+    AppPath(id_patient): AppPath<i32>,
+    State(service): State<SharedMedicalDocumentService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let documents = service
+        .get_by_patient(id_patient, &auth, &page)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Paciente no encontrado".to_string()))?;
+    Ok((StatusCode::OK, Json(documents)))
+}
+
+pub async fn download(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedMedicalDocumentService>,
+) -> AppResult<impl IntoResponse> {
+    let (document, stream) = service.download(id, &auth).await?.ok_or_else(not_found)?;
+
+    let content_type = document
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let file_name = document
+        .file_name
+        .unwrap_or_else(|| format!("documento-{}", document.id_document))
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+        .collect::<String>();
+    let disposition = format!("attachment; filename=\"{}\"", file_name);
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    ))
+}
+
+pub async fn preview(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedMedicalDocumentService>,
+) -> AppResult<impl IntoResponse> {
+    let stream = service
+        .preview(id, &auth)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Vista previa no disponible".to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "private, max-age=3600"),
+        ],
+        Body::from_stream(stream),
+    ))
+}
@@ -1,9 +1,20 @@
 use crate::{
     application::medical_history_service::MedicalHistoryService,
-    domain::medical_history::{CreateMedicalHistory, UpdateMedicalHistory},
+    domain::{
+        medical_history::{CreateMedicalHistory, UpdateMedicalHistory, MedicalHistoryFilter},
+        pagination::PageParams,
+    },
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppJson, AppPath, AppQuery, AppResult},
+    },
+    infrastructure::{
+        access_log_repository::PgAccessLogRepository,
+        medical_history_repository::PgMedicalHistoryRepository,
+    },
 };
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -12,92 +23,61 @@ use std::sync::Arc;
 use validator::Validate;
 
 pub type SharedMedicalHistoryService =
-    Arc<MedicalHistoryService<crate::infrastructure::medical_history_repository::PgMedicalHistoryRepository>>;
+    Arc<MedicalHistoryService<PgMedicalHistoryRepository, PgAccessLogRepository>>;
 
-pub async fn get_all(State(service): State<SharedMedicalHistoryService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(medical_histories) => (StatusCode::OK, Json(medical_histories)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al obtener medical histories",
-        )
-            .into_response(),
+fn not_found() -> AppError {
+    AppError::NotFound("Medical history no encontrado".to_string())
+}
+
+pub async fn get_all(
+    auth: AuthUser,
+    State(service): State<SharedMedicalHistoryService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<MedicalHistoryFilter>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede listar el historial médico".to_string()));
     }
+    page.validate()?;
+
+    let medical_histories = service.get_all(&filter, &page, auth.id_user).await?;
+    Ok((StatusCode::OK, Json(medical_histories)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedMedicalHistoryService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(m)) => (StatusCode::OK, Json(m)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Medical history no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al buscar medical history",
-        )
-            .into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let m = service.get_by_id(id, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(m)))
 }
 
 pub async fn create(
     State(service): State<SharedMedicalHistoryService>,
-    Json(payload): Json<CreateMedicalHistory>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppJson(payload): AppJson<CreateMedicalHistory>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.create(payload).await {
-        Ok(m) => (StatusCode::CREATED, Json(m)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al crear medical history",
-        )
-            .into_response(),
-    }
+    let m = service.create(payload).await?;
+    Ok((StatusCode::CREATED, Json(m)))
 }
 
-
 pub async fn update(
     State(service): State<SharedMedicalHistoryService>,
-    Path(id): Path<i32>,
-    Json(payload): Json<UpdateMedicalHistory>, 
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {  
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-        .into_response();
-    }
+    AppPath(id): AppPath<i32>,
+    AppJson(payload): AppJson<UpdateMedicalHistory>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
 
-    match service.update(id, payload).await { 
-        Ok(Some(m)) => (StatusCode::OK, Json(m)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Medical history no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al actualizar medical history",
-        )
-            .into_response(),
-    }
+    let m = service.update(id, payload).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(m)))
 }
 
 pub async fn delete(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedMedicalHistoryService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(m)) => (StatusCode::OK, Json(m)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Medical history no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar medical history",
-        )
-            .into_response(),
-    }
-}   
\ No newline at end of file
+) -> AppResult<impl IntoResponse> {
+    let m = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(m)))
+}
@@ -7,4 +7,19 @@ pub mod speciality_controller;
 pub mod appointment_controller;
 pub mod medical_history_controller;
 pub mod lab_result_controller;
-pub mod auth_controller;
\ No newline at end of file
+pub mod auth_controller;
+pub mod medical_document_controller;
+pub mod encryption_controller;
+pub mod patient_contact_controller;
+pub mod registration_controller;
+pub mod portal_controller;
+pub mod patient_import_controller;
+pub mod patient_import_cli;
+pub mod trash_controller;
+pub mod trash_cli;
+pub mod doctor_schedule_controller;
+pub mod waitlist_controller;
+pub mod waitlist_worker;
+pub mod reminder_worker;
+pub mod calendar_controller;
+pub mod doctor_agenda_controller;
\ No newline at end of file
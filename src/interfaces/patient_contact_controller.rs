@@ -0,0 +1,92 @@
+use crate::application::patient_contact_service::PatientContactService;
+use crate::domain::patient_contact::{CreatePatientContact, UpdatePatientContact};
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppResult};
+use crate::infrastructure::patient_contact_repository::PgPatientContactRepository;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedPatientContactService = Arc<PatientContactService<PgPatientContactRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Contacto no encontrado".to_string())
+}
+
+fn patient_not_found() -> AppError {
+    AppError::NotFound("Paciente no encontrado".to_string())
+}
+
+/// Registrar o cambiar contactos y tutores es tarea del personal
+fn require_staff(auth: &AuthUser) -> AppResult<()> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede modificar los contactos del paciente".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn get_by_patient(
+    auth: AuthUser,
+    AppPath(id_patient): AppPath<i32>,
+    State(service): State<SharedPatientContactService>,
+) -> AppResult<impl IntoResponse> {
+    let contacts = service
+        .get_by_patient(id_patient, &auth)
+        .await?
+        .ok_or_else(patient_not_found)?;
+    Ok((StatusCode::OK, Json(contacts)))
+}
+
+pub async fn create(
+    auth: AuthUser,
+    AppPath(id_patient): AppPath<i32>,
+    State(service): State<SharedPatientContactService>,
+    AppJson(payload): AppJson<CreatePatientContact>,
+) -> AppResult<impl IntoResponse> {
+    require_staff(&auth)?;
+    payload.validate()?;
+    if payload.id_related_patient.is_none() && payload.contact_name.is_none() {
+        return Err(AppError::BadRequest("Indique id_related_patient o contact_name".to_string()));
+    }
+    if payload.id_related_patient == Some(id_patient) {
+        return Err(AppError::BadRequest("Un paciente no puede ser su propio contacto".to_string()));
+    }
+
+    let contact = service
+        .create(id_patient, payload)
+        .await?
+        .ok_or_else(patient_not_found)?;
+    Ok((StatusCode::CREATED, Json(contact)))
+}
+
+pub async fn update(
+    auth: AuthUser,
+    AppPath((id_patient, id_contact)): AppPath<(i32, i32)>,
+    State(service): State<SharedPatientContactService>,
+    AppJson(payload): AppJson<UpdatePatientContact>,
+) -> AppResult<impl IntoResponse> {
+    require_staff(&auth)?;
+    payload.validate()?;
+
+    let contact = service
+        .update(id_patient, id_contact, payload)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(contact)))
+}
+
+pub async fn delete(
+    auth: AuthUser,
+    AppPath((id_patient, id_contact)): AppPath<(i32, i32)>,
+    State(service): State<SharedPatientContactService>,
+) -> AppResult<impl IntoResponse> {
+    require_staff(&auth)?;
+
+    let contact = service.delete(id_patient, id_contact).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(contact)))
+}
@@ -1,99 +1,219 @@
 use crate::application::patient_service::PatientService;
-use crate::domain::patient::{CreatePatient, UpdatePatient};
+use crate::domain::pagination::PageParams;
+use crate::domain::patient::{ChangePatientStatus, CreatePatient, PatientFilter, PatientSearchParams, UpdatePatient};
+use crate::domain::patient_merge::MergePatients;
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
 use crate::helpers::utils::generate_patient_password;
+use crate::infrastructure::access_log_repository::PgAccessLogRepository;
+use crate::infrastructure::patient_merge_repository::PgPatientMergeRepository;
 use crate::infrastructure::patient_repository::PgPatientRepository;
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
+use bcrypt::{hash, DEFAULT_COST};
 use chrono::Datelike; // para el método `.year()`
 
 use std::sync::Arc;
 use validator::Validate;
 
-pub type SharedPatientService = Arc<PatientService<PgPatientRepository>>;
+pub type SharedPatientService =
+    Arc<PatientService<PgPatientRepository, PgAccessLogRepository, PgPatientMergeRepository>>;
 
-pub async fn get_all(State(service): State<SharedPatientService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(patients) => (StatusCode::OK, Json(patients)).into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al obtener pacientes",
-        )
-            .into_response(),
+fn not_found() -> AppError {
+    AppError::NotFound("Paciente no encontrado".to_string())
+}
+
+pub async fn get_all(
+    auth: AuthUser,
+    State(service): State<SharedPatientService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<PatientFilter>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede listar pacientes".to_string()));
     }
+    page.validate()?;
+
+    let patients = service.get_all(&filter, &page, auth.id_user).await?;
+    Ok((StatusCode::OK, Json(patients)))
 }
 
-pub async fn get_by_id(
-    Path(id): Path<i32>,
+pub async fn search(
+    auth: AuthUser,
     State(service): State<SharedPatientService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(p)) => (StatusCode::OK, Json(p)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Patiente no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al buscar paciente",
-        )
-            .into_response(),
+    AppQuery(params): AppQuery<PatientSearchParams>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede buscar pacientes".to_string()));
     }
+    params.validate()?;
+    page.validate()?;
+
+    let hits = service.search(&params.q, &page, auth.id_user).await?;
+    Ok((StatusCode::OK, Json(hits)))
+}
+
+pub async fn get_by_id(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+) -> AppResult<impl IntoResponse> {
+    let p = service.get_by_id(id, &auth).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(p)))
 }
 
 pub async fn create(
+    auth: AuthUser,
     State(service): State<SharedPatientService>,
-    Json(payload): Json<CreatePatient>,
-) -> impl IntoResponse {
-    if let Err(errors) = payload.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-            .into_response();
+    AppJson(payload): AppJson<CreatePatient>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede registrar pacientes".to_string()));
     }
+    payload.validate()?;
 
     let birth_year = payload.birthdate.year(); 
     let raw_password =
         generate_patient_password(&payload.first_name, &payload.first_lastname, birth_year);
+    let password_hash = hash(&raw_password, DEFAULT_COST).map_err(anyhow::Error::from)?;
 
     // Pasamos al servicio
-    match service.create(payload, &raw_password).await {
-        Ok(patient) => (StatusCode::CREATED, Json(patient)).into_response(),
-        Err(e) => {
-            eprintln!("Error al crear paciente: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Error al crear paciente").into_response()
-        }
-    }
+    let patient = service.create(payload, &password_hash).await?;
+    Ok((StatusCode::CREATED, Json(patient)))
 }
 
 pub async fn update(
-    Path(id): Path<i32>,
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedPatientService>,
-    Json(data): Json<UpdatePatient>,
-) -> impl IntoResponse {
-    match service.update(id, data).await {
-        Ok(Some(p)) => (StatusCode::OK, Json(p)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Patiente no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al actualizar paciente",
-        )
-            .into_response(),
+    AppJson(data): AppJson<UpdatePatient>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede modificar pacientes".to_string()));
     }
+    data.validate()?;
+
+    let p = service.update(id, data).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(p)))
 }
 
 pub async fn delete(
-    Path(id): Path<i32>,
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedPatientService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(p)) => (StatusCode::OK, Json(p)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Patiente no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar paciente",
-        )
-            .into_response(),
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admisionist() && !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo admisión puede eliminar pacientes".to_string()));
     }
+    let p = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(p)))
+}
+
+pub async fn get_access_report(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let report = service
+        .get_access_report(id, auth.id_user, auth.is_admin(), &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Admisión confirma la identidad de un paciente autorregistrado contra la cédula física
+pub async fn confirm_identity(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admisionist() && !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo admisión puede confirmar la identidad".to_string()));
+    }
+
+    let p = service.confirm_identity(id, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(p)))
+}
+
+/// Marca al paciente como inactivo, trasladado o fallecido (o lo reactiva)
+pub async fn change_status(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+    AppJson(payload): AppJson<ChangePatientStatus>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede cambiar el estado del paciente".to_string()));
+    }
+    payload.validate()?;
+
+    let change = service.change_status(id, payload, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(change)))
+}
+
+/// Pacientes de los que el usuario autenticado es tutor legal
+pub async fn get_dependents(
+    auth: AuthUser,
+    State(service): State<SharedPatientService>,
+) -> AppResult<impl IntoResponse> {
+    let dependents = service.get_dependents(auth.id_user).await?;
+    Ok((StatusCode::OK, Json(dependents)))
+}
+
+pub async fn find_duplicates(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_staff() {
+        return Err(AppError::Forbidden("Solo el personal puede buscar duplicados".to_string()));
+    }
+
+    let candidates = service.find_duplicates(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(candidates)))
+}
+
+pub async fn merge(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+    AppJson(payload): AppJson<MergePatients>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo un administrador puede fusionar pacientes".to_string()));
+    }
+    payload.validate()?;
+    if payload.duplicate_id == id {
+        return Err(AppError::BadRequest("Un paciente no se puede fusionar consigo mismo".to_string()));
+    }
+
+    let merge = service
+        .merge(id, payload.duplicate_id, auth.id_user)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(merge)))
+}
+
+pub async fn unmerge(
+    auth: AuthUser,
+    AppPath(id_audit): AppPath<i32>,
+    State(service): State<SharedPatientService>,
+) -> AppResult<impl IntoResponse> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo un administrador puede revertir fusiones".to_string()));
+    }
+
+    let merge = service
+        .unmerge(id_audit, auth.id_user)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Fusión no encontrada".to_string()))?;
+    Ok((StatusCode::OK, Json(merge)))
 }
@@ -0,0 +1,61 @@
+use crate::{
+    application::patient_import_service::PatientImportService,
+    domain::patient_import::ImportFormat,
+    helpers::crypto::Keyring,
+    infrastructure::{
+        patient_import_repository::PgPatientImportRepository,
+        patient_repository::PgPatientRepository,
+    },
+};
+use anyhow::{anyhow, Result};
+use sqlx::PgPool;
+use std::{path::Path, sync::Arc};
+
+pub const USAGE: &str = "Uso: paciente-app-backend import-patients <archivo.csv|archivo.xlsx> [--dry-run] [--report errores.csv]";
+
+/// `import-patients`: la misma importación de POST /patients/import, para
+/// archivos grandes que no conviene subir por HTTP
+pub async fn run(pool: PgPool, keyring: Arc<Keyring>, args: &[String]) -> Result<()> {
+    let mut path: Option<&str> = None;
+    let mut report_path: Option<&str> = None;
+    let mut dry_run = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--report" => report_path = Some(iter.next().ok_or_else(|| anyhow!(USAGE))?),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(anyhow!(USAGE)),
+        }
+    }
+    let path = path.ok_or_else(|| anyhow!(USAGE))?;
+
+    let file_name = Path::new(path).file_name().map(|f| f.to_string_lossy().to_string());
+    let format = ImportFormat::detect(file_name.as_deref(), "")
+        .ok_or_else(|| anyhow!("Formato no soportado: use un archivo .csv o .xlsx"))?;
+    let data = tokio::fs::read(path).await?;
+
+    let service = PatientImportService::new(
+        PgPatientRepository::new(pool.clone(), keyring),
+        PgPatientImportRepository::new(pool),
+    );
+    let report = service.import(format, file_name, &data, dry_run, None).await?;
+
+    println!(
+        "Importación {}{}: {} filas, {} válidas, {} importadas, {} con errores",
+        report.id_import,
+        if dry_run { " (dry-run)" } else { "" },
+        report.total_rows,
+        report.valid_rows,
+        report.imported_rows,
+        report.failed_rows
+    );
+
+    if let Some(report_path) = report_path {
+        let csv = service.error_report(report.id_import).await?.unwrap_or_default();
+        tokio::fs::write(report_path, csv).await?;
+        println!("Reporte de errores guardado en {}", report_path);
+    }
+    Ok(())
+}
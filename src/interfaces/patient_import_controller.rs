@@ -0,0 +1,101 @@
+use crate::{
+    application::patient_import_service::PatientImportService,
+    domain::patient_import::{ImportFormat, ImportParams},
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppPath, AppQuery, AppResult},
+    },
+    infrastructure::{
+        patient_import_repository::PgPatientImportRepository,
+        patient_repository::PgPatientRepository,
+    },
+};
+use axum::{
+    extract::{Multipart, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+
+pub type SharedPatientImportService = Arc<PatientImportService<PgPatientRepository, PgPatientImportRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Importación no encontrada".to_string())
+}
+
+fn require_admin(auth: &AuthUser) -> AppResult<()> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo administradores pueden importar pacientes".to_string()));
+    }
+    Ok(())
+}
+
+/// Importa pacientes desde un CSV o XLSX (campo `file`). Con `?dry_run=true`
+/// solo valida y devuelve el reporte.
+pub async fn import(
+    auth: AuthUser,
+    State(service): State<SharedPatientImportService>,
+    AppQuery(params): AppQuery<ImportParams>,
+    mut multipart: Multipart,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+
+    let mut file: Option<(Option<String>, ImportFormat, bytes::Bytes)> = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().map(|f| f.to_string());
+        let content_type = field.content_type().unwrap_or_default().to_string();
+        let format = ImportFormat::detect(file_name.as_deref(), &content_type).ok_or_else(|| {
+            AppError::UnsupportedMediaType("Formato no soportado: use un archivo .csv o .xlsx".to_string())
+        })?;
+        // Falla si se excedió el límite del body o se cortó la conexión
+        let data = field.bytes().await?;
+        if data.is_empty() {
+            return Err(AppError::BadRequest("El archivo está vacío".to_string()));
+        }
+        file = Some((file_name, format, data));
+    }
+
+    let (file_name, format, data) =
+        file.ok_or_else(|| AppError::BadRequest("El archivo es requerido".to_string()))?;
+
+    let report = service
+        .import(format, file_name, &data, params.dry_run, Some(auth.id_user))
+        .await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+pub async fn get_by_id(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientImportService>,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+
+    let report = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Errores por fila en CSV para corregir la hoja y volver a importarla
+pub async fn error_report(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedPatientImportService>,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+
+    let csv = service.error_report(id).await?.ok_or_else(not_found)?;
+    let disposition = format!("attachment; filename=\"importacion-{}-errores.csv\"", id);
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        csv,
+    ))
+}
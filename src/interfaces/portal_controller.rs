@@ -0,0 +1,140 @@
+use crate::{
+    application::portal_service::PortalService,
+    domain::{
+        appointment::AppointmentFilter,
+        lab_result::LabResultFilter,
+        medical_history::MedicalHistoryFilter,
+        pagination::PageParams,
+        patient::UpdateOwnProfile,
+    },
+    helpers::{
+        auth::AuthUser,
+        errors::{AppError, AppJson, AppQuery, AppResult},
+    },
+    infrastructure::{
+        appointment_repository::PgAppointmentRepository,
+        lab_result::PgLabResultRepository,
+        medical_document_repository::PgMedicalDocumentRepository,
+        medical_history_repository::PgMedicalHistoryRepository,
+        patient_repository::PgPatientRepository,
+        virtual_turn_repository::PgVirtualTurnRepository,
+    },
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedPortalService = Arc<
+    PortalService<
+        PgPatientRepository,
+        PgAppointmentRepository,
+        PgMedicalHistoryRepository,
+        PgLabResultRepository,
+        PgMedicalDocumentRepository,
+        PgVirtualTurnRepository,
+    >,
+>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("El usuario no tiene un expediente de paciente".to_string())
+}
+
+pub async fn get_profile(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+) -> AppResult<impl IntoResponse> {
+    let patient = service.get_profile(auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(patient)))
+}
+
+pub async fn update_profile(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppJson(payload): AppJson<UpdateOwnProfile>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let patient = service
+        .update_profile(auth.id_user, payload)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(patient)))
+}
+
+pub async fn get_appointments(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<AppointmentFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let appointments = service
+        .get_appointments(auth.id_user, filter, &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(appointments)))
+}
+
+pub async fn get_history(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<MedicalHistoryFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let history = service
+        .get_history(auth.id_user, filter, &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(history)))
+}
+
+pub async fn get_lab_results(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<LabResultFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let results = service
+        .get_lab_results(auth.id_user, filter, &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(results)))
+}
+
+pub async fn get_documents(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let documents = service
+        .get_documents(auth.id_user, &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(documents)))
+}
+
+pub async fn get_turns(
+    auth: AuthUser,
+    State(service): State<SharedPortalService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let turns = service
+        .get_turns(auth.id_user, &page)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(turns)))
+}
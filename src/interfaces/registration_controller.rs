@@ -0,0 +1,59 @@
+use crate::application::registration_service::RegistrationService;
+use crate::domain::registration::{SelfRegistration, Verification, VerifyRegistration};
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppResult};
+use crate::infrastructure::notifier::LogNotifier;
+use crate::infrastructure::patient_repository::PgPatientRepository;
+use crate::infrastructure::registration_repository::PgRegistrationRepository;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+pub type SharedRegistrationService =
+    Arc<RegistrationService<PgRegistrationRepository, PgPatientRepository, LogNotifier>>;
+
+/// Autorregistro público: guarda los datos y envía un código al teléfono o correo
+pub async fn register(
+    State(service): State<SharedRegistrationService>,
+    AppJson(payload): AppJson<SelfRegistration>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+    if payload.has_staff_fields() {
+        return Err(AppError::BadRequest(
+            "La información clínica, la prioridad y el estado los registra el personal".to_string(),
+        ));
+    }
+
+    let pending = service.register(payload).await?;
+    Ok((StatusCode::ACCEPTED, Json(pending)))
+}
+
+/// Verifica el código y crea la cuenta en estado pending_verification
+pub async fn verify(
+    AppPath(id): AppPath<Uuid>,
+    State(service): State<SharedRegistrationService>,
+    AppJson(payload): AppJson<VerifyRegistration>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let verification = service
+        .verify(id, &payload.code)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Registro no encontrado o ya verificado".to_string()))?;
+
+    match verification {
+        Verification::Verified(patient) => Ok((StatusCode::CREATED, Json(patient))),
+        Verification::InvalidCode { attempts_left } => Err(AppError::BadRequest(format!(
+            "Código incorrecto. Intentos restantes: {}",
+            attempts_left
+        ))),
+        Verification::Expired => Err(AppError::BadRequest(
+            "El código venció o se agotaron los intentos; registre sus datos de nuevo".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,26 @@
+use crate::application::reminder_service::ReminderService;
+use crate::domain::appointment_reminder::ReminderPolicy;
+use crate::infrastructure::notifier::LogNotifier;
+use crate::infrastructure::reminder_repository::PgReminderRepository;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Cada cuánto se buscan citas a las que les toca recordatorio
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tarea de fondo iniciada desde `main`: envía los recordatorios de citas con
+/// las anticipaciones de REMINDER_OFFSETS
+pub fn spawn(pool: PgPool) {
+    let service = ReminderService::new(PgReminderRepository::new(pool), LogNotifier, ReminderPolicy::from_env());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match service.send_due().await {
+                Ok(0) => {}
+                Ok(sent) => println!("Recordatorios: {} citas avisadas", sent),
+                Err(e) => eprintln!("Error al enviar recordatorios de citas: {:?}", e),
+            }
+        }
+    });
+}
@@ -1,81 +1,63 @@
 use crate::application::services_service::ServicesService;
 use crate::domain::services::{CreateService, UpdateService};
+use crate::domain::pagination::PageParams;
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
 use crate::infrastructure::services_repository::PgServiceRepository;
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use std::sync::Arc;
+use validator::Validate;
 
 pub type SharedServicesService = Arc<ServicesService<PgServiceRepository>>;
 
-pub async fn get_all(State(service): State<SharedServicesService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(services) => (StatusCode::OK, Json(services)).into_response(),
-        Err(e) => {
-            eprintln!("Error al obtener servicios: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response()
-        }
-    }
+fn not_found() -> AppError {
+    AppError::NotFound("Servicio no encontrado".to_string())
+}
+
+pub async fn get_all(
+    State(service): State<SharedServicesService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let services = service.get_all(&page).await?;
+    Ok((StatusCode::OK, Json(services)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedServicesService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(s)) => (StatusCode::OK, Json(s)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Servicio no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al buscar servicio").into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let s = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(s)))
 }
 
 pub async fn create(
     State(service): State<SharedServicesService>,
-    Json(data): Json<CreateService>,
-) -> impl IntoResponse {
-    match service.create(data).await {
-        Ok(s) => (StatusCode::CREATED, Json(s)).into_response(),
-        Err(e) => {
-            eprintln!("Error al crear servicio: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Error al crear servicio").into_response()
-        }
-    }
+    AppJson(data): AppJson<CreateService>,
+) -> AppResult<impl IntoResponse> {
+    let s = service.create(data).await?;
+    Ok((StatusCode::CREATED, Json(s)))
 }
 
 pub async fn update(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedServicesService>,
-    Json(data): Json<UpdateService>,
-) -> impl IntoResponse {
+    AppJson(data): AppJson<UpdateService>,
+) -> AppResult<impl IntoResponse> {
     // Actualizar servicio 
-    match service.update(id, data).await {
-        Ok(Some(s)) => (StatusCode::OK, Json(s)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Servicio no encontrado").into_response(),
-        Err(e) => {
-            eprintln!("Error al actualizar servicio: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Error al actualizar servicio",
-            )
-                .into_response()
-        }
-    }
+    let s = service.update(id, data).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(s)))
 }
 
 pub async fn delete(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedServicesService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(s)) => (StatusCode::OK, Json(s)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Servicio no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar servicio",
-        )
-            .into_response(),
-    }
-}       
\ No newline at end of file
+) -> AppResult<impl IntoResponse> {
+    let s = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(s)))
+}
@@ -1,58 +1,53 @@
 use crate::domain::speciality::{CreateSpeciality, UpdateSpeciality};
+use crate::domain::pagination::PageParams;
 use crate::application::speciality_service::SpecialityService;
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
 use crate::infrastructure::speciality_repository::PgSpecialityRepository;
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
-use std::i32;
 use std::sync::Arc;
+use validator::Validate;
 
 pub type SharedSpecialityService = Arc<SpecialityService<PgSpecialityRepository>>;
 
-pub async fn get_all(State(service): State<SharedSpecialityService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(speciality) => (StatusCode::OK, Json(speciality)).into_response(),
-        Err(e) => {
-            eprintln!("Error al obtener especialidad: {:?}", e);    
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response()
-        }
-    }
+fn not_found() -> AppError {
+    AppError::NotFound("Especialidad no encontrada".to_string())
 }
 
-pub async fn get_by_id(Path(id): Path<i32>, State(service): State<SharedSpecialityService>) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(speciality)) => (StatusCode::OK, Json(speciality)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Especialidad no encontrada").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al buscar especialidad").into_response() 
-    }
+pub async fn get_all(
+    State(service): State<SharedSpecialityService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let speciality = service.get_all(&page).await?;
+    Ok((StatusCode::OK, Json(speciality)))
+}
+
+pub async fn get_by_id(AppPath(id): AppPath<i32>, State(service): State<SharedSpecialityService>) -> AppResult<impl IntoResponse> {
+    let speciality = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(speciality)))
 }
 
-pub async fn create(State(service): State<SharedSpecialityService>, Json(data): Json<CreateSpeciality>) -> impl IntoResponse {
-    match service.create(data).await {
-        Ok(speciality) => (StatusCode::CREATED, Json(speciality)).into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al crear especialidad").into_response()
-    }
+pub async fn create(State(service): State<SharedSpecialityService>, AppJson(data): AppJson<CreateSpeciality>) -> AppResult<impl IntoResponse> {
+    let speciality = service.create(data).await?;
+    Ok((StatusCode::CREATED, Json(speciality)))
 }
 
 pub async fn update(
-    Path(id): Path<i32>, 
+    AppPath(id): AppPath<i32>, 
     State(service): State<SharedSpecialityService>, 
-    Json(data): Json<UpdateSpeciality>
-) -> impl IntoResponse {
-    match service.update(id, data).await {
-        Ok(Some(speciality)) => (StatusCode::OK, Json(speciality)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Especialidad no encontrada").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al actualizar datos").into_response(),
-    } 
+    AppJson(data): AppJson<UpdateSpeciality>
+) -> AppResult<impl IntoResponse> {
+    let speciality = service.update(id, data).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(speciality)))
 }
 
-pub async fn delete(Path(id): Path<i32>, State(service): State<SharedSpecialityService>) -> impl IntoResponse {
-    match service.delete(id).await{
-        Ok(Some(speciality)) => (StatusCode::OK, Json(speciality)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "No se encontro la especialidad").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al eliminar especialidad").into_response(),
-    }
-} 
\ No newline at end of file
+pub async fn delete(AppPath(id): AppPath<i32>, State(service): State<SharedSpecialityService>) -> AppResult<impl IntoResponse> {
+    let speciality = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(speciality)))
+}
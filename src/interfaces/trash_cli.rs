@@ -0,0 +1,36 @@
+use crate::{
+    application::trash_service::TrashService,
+    domain::trash::RetentionPolicy,
+    infrastructure::trash_repository::PgTrashRepository,
+};
+use anyhow::{anyhow, Result};
+use sqlx::PgPool;
+
+pub const USAGE: &str = "Uso: paciente-app-backend purge-trash [--dry-run]";
+
+/// `purge-trash`: la misma purga de POST /trash/purge, para programarla con cron
+pub async fn run(pool: PgPool, args: &[String]) -> Result<()> {
+    let dry_run = match args {
+        [] => false,
+        [flag] if flag == "--dry-run" => true,
+        _ => return Err(anyhow!(USAGE)),
+    };
+
+    let service = TrashService::new(PgTrashRepository::new(pool), RetentionPolicy::from_env());
+    let results = service.purge(dry_run, None).await?;
+
+    for r in results {
+        match r.retention_years {
+            Some(years) => println!(
+                "{}{}: {} purgados, {} omitidos por referencias (más de {} años)",
+                r.entity,
+                if dry_run { " (dry-run)" } else { "" },
+                r.purged.len(),
+                r.skipped.len(),
+                years
+            ),
+            None => println!("{}: no se purga (sin plazo de retención configurado)", r.entity),
+        }
+    }
+    Ok(())
+}
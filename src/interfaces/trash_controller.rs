@@ -0,0 +1,67 @@
+use crate::application::trash_service::TrashService;
+use crate::domain::pagination::PageParams;
+use crate::domain::trash::PurgeParams;
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppPath, AppQuery, AppResult};
+use crate::infrastructure::trash_repository::PgTrashRepository;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedTrashService = Arc<TrashService<PgTrashRepository>>;
+
+fn not_found() -> AppError {
+    AppError::NotFound("Registro no encontrado en la papelera".to_string())
+}
+
+fn require_admin(auth: &AuthUser) -> AppResult<()> {
+    if !auth.is_admin() {
+        return Err(AppError::Forbidden("Solo administradores pueden gestionar la papelera".to_string()));
+    }
+    Ok(())
+}
+
+/// Registros eliminados de una entidad, del más reciente al más antiguo
+pub async fn list(
+    auth: AuthUser,
+    AppPath(entity): AppPath<String>,
+    State(service): State<SharedTrashService>,
+    AppQuery(page): AppQuery<PageParams>,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+    page.validate()?;
+
+    let items = service.list(&entity, &page).await?.ok_or_else(|| {
+        AppError::NotFound(format!("La entidad '{}' no tiene papelera", entity))
+    })?;
+    Ok((StatusCode::OK, Json(items)))
+}
+
+pub async fn restore(
+    auth: AuthUser,
+    AppPath((entity, id)): AppPath<(String, i32)>,
+    State(service): State<SharedTrashService>,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+
+    let item = service.restore(&entity, id, auth.id_user).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(item)))
+}
+
+/// Purga lo que venció según la política de retención. Con `?dry_run=true`
+/// solo informa qué se borraría.
+pub async fn purge(
+    auth: AuthUser,
+    State(service): State<SharedTrashService>,
+    AppQuery(params): AppQuery<PurgeParams>,
+) -> AppResult<impl IntoResponse> {
+    require_admin(&auth)?;
+
+    let results = service.purge(params.dry_run, Some(auth.id_user)).await?;
+    Ok((StatusCode::OK, Json(results)))
+}
@@ -1,8 +1,10 @@
 use crate::application::user_service::UserService;
-use crate::domain::user::{CreateUser, UpdateUser};
+use crate::domain::pagination::PageParams;
+use crate::domain::user::{CreateUser, UpdateUser, UserFilter};
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
 use crate::infrastructure::user_repository::PgUserRepository;
 use axum::{
-    extract::{Path, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -13,107 +15,65 @@ use::validator::Validate;
 
 pub type SharedUserService = Arc<UserService<PgUserRepository>>;
 
-pub async fn get_all(State(service): State<SharedUserService>) -> impl IntoResponse {
-    match service.get_all().await {
-        Ok(users) => (StatusCode::OK, Json(users)).into_response(),
-        Err(e) => {
-            eprintln!("Error al obtener usuarios: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response()
-        }
-    }
+fn not_found() -> AppError {
+    AppError::NotFound("Usuario no encontrado".to_string())
+}
+
+pub async fn get_all(
+    State(service): State<SharedUserService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<UserFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let users = service.get_all(&filter, &page).await?;
+    Ok((StatusCode::OK, Json(users)))
 }
 
 pub async fn get_by_id(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedUserService>,
-) -> impl IntoResponse {
-    match service.get_by_id(id).await {
-        Ok(Some(u)) => (StatusCode::OK, Json(u)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Usuario no encontrado").into_response(),
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Error al buscar usuario").into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let u = service.get_by_id(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(u)))
 }
 
 
 pub async fn create(
     State(service): State<SharedUserService>,
-    Json(mut data): Json<CreateUser>,
-) -> impl IntoResponse {
+    AppJson(mut data): AppJson<CreateUser>,
+) -> AppResult<impl IntoResponse> {
     // Validación
-    if let Err(errors) = data.validate() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(format!("Errores de validación: {:?}", errors)),
-        )
-            .into_response();
-    }
+    data.validate()?;
 
     // Hashear la contraseña
-    match hash(&data.password_hash, DEFAULT_COST) {
-        Ok(hashed) => data.password_hash = hashed,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Error al hashear la contraseña",
-            )
-                .into_response();
-        }
-    }
+    data.password_hash = hash(&data.password_hash, DEFAULT_COST).map_err(anyhow::Error::from)?;
 
     // Crear usuario con la contraseña hasheada
-    match service.create(data).await {
-        Ok(u) => (StatusCode::CREATED, Json(u)).into_response(),
-        Err(e) => {
-            eprintln!("Error al crear usuario: {:?}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Error al crear usuario").into_response()
-        }
-    }
+    let u = service.create(data).await?;
+    Ok((StatusCode::CREATED, Json(u)))
 }
 
 pub async fn update(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedUserService>,
-    Json(mut data): Json<UpdateUser>,
-) -> impl IntoResponse {
+    AppJson(mut data): AppJson<UpdateUser>,
+) -> AppResult<impl IntoResponse> {
+    data.validate()?;
+
      // Si viene una contraseña, la hasheamos
     if let Some(ref password) = data.password_hash {
-        match hash(password, DEFAULT_COST) {
-            Ok(hashed) => data.password_hash = Some(hashed),
-            Err(_) => {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Error al hashear la contraseña",
-                )
-                    .into_response()
-            }
-        }
+        data.password_hash = Some(hash(password, DEFAULT_COST).map_err(anyhow::Error::from)?);
     }
     // Actualizar usuario 
-    match service.update(id, data).await {
-        Ok(Some(u)) => (StatusCode::OK, Json(u)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Usuario no encontrado").into_response(),
-        Err(e) => {
-            eprintln!("Error al actualizar usuario: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Error al actualizar usuario",
-            )
-                .into_response()
-        }
-    }
+    let u = service.update(id, data).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(u)))
 }
 
 pub async fn delete(
-    Path(id): Path<i32>,
+    AppPath(id): AppPath<i32>,
     State(service): State<SharedUserService>,
-) -> impl IntoResponse {
-    match service.delete(id).await {
-        Ok(Some(u)) => (StatusCode::OK, Json(u)).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Usuario no encontrado").into_response(),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Error al eliminar usuario",
-        )
-            .into_response(),
-    }
+) -> AppResult<impl IntoResponse> {
+    let u = service.delete(id).await?.ok_or_else(not_found)?;
+    Ok((StatusCode::OK, Json(u)))
 }
@@ -0,0 +1,99 @@
+use crate::application::waitlist_service::WaitlistService;
+use crate::domain::pagination::PageParams;
+use crate::domain::waitlist::{CreateWaitlistEntry, WaitlistFilter, WaitlistResult};
+use crate::helpers::auth::AuthUser;
+use crate::helpers::errors::{AppError, AppJson, AppPath, AppQuery, AppResult};
+use crate::infrastructure::notifier::LogNotifier;
+use crate::infrastructure::waitlist_repository::PgWaitlistRepository;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+pub type SharedWaitlistService = Arc<WaitlistService<PgWaitlistRepository, LogNotifier>>;
+
+fn entry_not_found() -> AppError {
+    AppError::NotFound("Entrada de la lista de espera no encontrada".to_string())
+}
+
+fn offer_not_found() -> AppError {
+    AppError::NotFound("Oferta no encontrada".to_string())
+}
+
+fn done<T>(result: WaitlistResult<T>) -> AppResult<T> {
+    match result {
+        WaitlistResult::Done(value) => Ok(value),
+        WaitlistResult::Forbidden(message) => Err(AppError::Forbidden(message.to_string())),
+    }
+}
+
+/// El personal ve toda la lista; un paciente debe indicar `id_patient`
+pub async fn get_all(
+    auth: AuthUser,
+    State(service): State<SharedWaitlistService>,
+    AppQuery(page): AppQuery<PageParams>,
+    AppQuery(filter): AppQuery<WaitlistFilter>,
+) -> AppResult<impl IntoResponse> {
+    page.validate()?;
+
+    let entries = service.get_entries(&filter, &page, &auth).await?.ok_or_else(|| {
+        AppError::Forbidden("Indique un paciente por el que pueda actuar en id_patient".to_string())
+    })?;
+    Ok((StatusCode::OK, Json(entries)))
+}
+
+pub async fn create(
+    auth: AuthUser,
+    State(service): State<SharedWaitlistService>,
+    AppJson(payload): AppJson<CreateWaitlistEntry>,
+) -> AppResult<impl IntoResponse> {
+    payload.validate()?;
+
+    let entry = service.create_entry(payload, &auth).await?.ok_or_else(|| {
+        AppError::Forbidden("No puede anotar a este paciente en la lista de espera".to_string())
+    })?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// Saca al paciente de la lista de espera
+pub async fn cancel(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedWaitlistService>,
+) -> AppResult<impl IntoResponse> {
+    let entry = done(service.cancel_entry(id, &auth).await?.ok_or_else(entry_not_found)?)?;
+    Ok((StatusCode::OK, Json(entry)))
+}
+
+pub async fn get_offers(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedWaitlistService>,
+) -> AppResult<impl IntoResponse> {
+    let offers = done(service.get_offers(id, &auth).await?.ok_or_else(entry_not_found)?)?;
+    Ok((StatusCode::OK, Json(offers)))
+}
+
+/// Acepta el turno ofrecido; crea la cita
+pub async fn accept_offer(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedWaitlistService>,
+) -> AppResult<impl IntoResponse> {
+    let accepted = done(service.accept(id, &auth).await?.ok_or_else(offer_not_found)?)?;
+    Ok((StatusCode::CREATED, Json(accepted)))
+}
+
+/// Rechaza el turno ofrecido; pasa a la siguiente persona de la lista
+pub async fn decline_offer(
+    auth: AuthUser,
+    AppPath(id): AppPath<i32>,
+    State(service): State<SharedWaitlistService>,
+) -> AppResult<impl IntoResponse> {
+    let offer = done(service.decline(id, &auth).await?.ok_or_else(offer_not_found)?)?;
+    Ok((StatusCode::OK, Json(offer)))
+}
@@ -0,0 +1,26 @@
+use crate::application::waitlist_service::WaitlistService;
+use crate::domain::waitlist::WaitlistPolicy;
+use crate::infrastructure::notifier::LogNotifier;
+use crate::infrastructure::waitlist_repository::PgWaitlistRepository;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Cada cuánto se revisan las ofertas vencidas
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tarea de fondo iniciada desde `main`: vence las ofertas de la lista de espera
+/// sin respuesta y pasa cada turno a la siguiente persona
+pub fn spawn(pool: PgPool) {
+    let service = WaitlistService::new(PgWaitlistRepository::new(pool), LogNotifier, WaitlistPolicy::from_env());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match service.expire_offers().await {
+                Ok(0) => {}
+                Ok(expired) => println!("Lista de espera: {} ofertas vencidas pasaron a la siguiente persona", expired),
+                Err(e) => eprintln!("Error al vencer ofertas de la lista de espera: {:?}", e),
+            }
+        }
+    });
+}
@@ -10,11 +10,40 @@ use sqlx::postgres::PgPoolOptions;
 use axum::{Router, http};
 use tower_http::cors::{CorsLayer};
 use std::env;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
 
+    let database_url = env::var("DATABASE_URL")
+        .expect("DATABASE_URL no está definida en .env");
+    let app_port = env::var("APP_PORT").unwrap_or_else(|_| "3000".to_string());
+
+    let keyring = Arc::new(
+        helpers::crypto::Keyring::from_env().expect("ENCRYPTION_KEYS inválida o no definida en .env"),
+    );
+
+    let pool = PgPoolOptions::new()
+        .connect(&database_url)
+        .await
+        .expect("Error conectando a la BD");
+
+    // Comandos de consola: `paciente-app-backend import-patients <archivo>`, `purge-trash`
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match args.first().map(String::as_str) {
+        Some("import-patients") => Some(interfaces::patient_import_cli::run(pool.clone(), keyring.clone(), &args[1..]).await),
+        Some("purge-trash") => Some(interfaces::trash_cli::run(pool.clone(), &args[1..]).await),
+        _ => None,
+    };
+    if let Some(result) = command {
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let frontend_url = env::var("FRONTEND_URL")
         .expect("FRONTEND_URL no está definida en .env");
 
@@ -24,30 +53,37 @@ async fn main() {
         .allow_headers([http::header::CONTENT_TYPE, http::header::AUTHORIZATION])
         .allow_credentials(true); 
 
-    let database_url = env::var("DATABASE_URL")
-        .expect("DATABASE_URL no está definida en .env");
-    let app_port = env::var("APP_PORT").unwrap_or_else(|_| "3000".to_string());
-
-    let pool = PgPoolOptions::new()
-        .connect(&database_url)
-        .await
-        .expect("Error conectando a la BD");
 
     let app = Router::new()
         .merge(routes::hospital::routes_hospital(pool.clone()))
-        .merge(routes::patient::routes_patient(pool.clone()))
+        .merge(routes::patient::routes_patient(pool.clone(), keyring.clone()))
+        .merge(routes::patient_import::routes_patient_import(pool.clone(), keyring.clone()))
+        .merge(routes::patient_contact::routes_patient_contact(pool.clone()))
+        .merge(routes::portal::routes_portal(pool.clone(), keyring.clone()))
         .merge(routes::user::routes_user(pool.clone()))
         .merge(routes::doctor::routes_doctor(pool.clone()))
+        .merge(routes::doctor_schedule::routes_doctor_schedule(pool.clone()))
+        .merge(routes::doctor_agenda::routes_doctor_agenda(pool.clone(), keyring.clone()))
         .merge(routes::services::routes_services(pool.clone()))
         .merge(routes::speciality::routes_speciality(pool.clone()))
         .merge(routes::appointment::routes_appointment(pool.clone()))
         .merge(routes::medical_history::routes_medical_history(pool.clone()))
         .merge(routes::lab_result::routes_lab_result(pool.clone()))
+        .merge(routes::medical_document::routes_medical_document(pool.clone(), keyring.clone()))
         .merge(routes::auth::routes_auth(pool.clone()))
+        .merge(routes::registration::routes_registration(pool.clone(), keyring.clone()))
+        .merge(routes::encryption::routes_encryption(pool.clone(), keyring.clone()))
+        .merge(routes::trash::routes_trash(pool.clone()))
+        .merge(routes::waitlist::routes_waitlist(pool.clone()))
+        .merge(routes::calendar::routes_calendar(pool.clone()))
         .layer(cors)
         ;
 
 
+    // Tareas de fondo
+    interfaces::waitlist_worker::spawn(pool.clone());
+    interfaces::reminder_worker::spawn(pool.clone());
+
     let addr = format!("0.0.0.0:{}", app_port);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     println!("Servidor iniciado en http://{}", addr);
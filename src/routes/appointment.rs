@@ -1,5 +1,5 @@
 use axum::{
-    routing::{get},
+    routing::{get, patch, post},
     Router,
 };
 use std::sync::Arc;
@@ -7,16 +7,28 @@ use std::sync::Arc;
 use crate::{
     application::appointment_service::AppointmentService,
     infrastructure::appointment_repository::PgAppointmentRepository,
+    domain::waitlist::WaitlistPolicy,
+    infrastructure::doctor_schedule_repository::PgDoctorScheduleRepository,
+    infrastructure::notifier::LogNotifier,
+    infrastructure::waitlist_repository::PgWaitlistRepository,
     interfaces::appointment_controller,
 };
 use sqlx::PgPool;
 
 pub fn routes_appointment(pool: PgPool) -> Router {
     // 1. Crear el repositorio
-    let repo = PgAppointmentRepository::new(pool);
+    let repo = PgAppointmentRepository::new(pool.clone());
+    let schedules = PgDoctorScheduleRepository::new(pool.clone());
+    let waitlist = PgWaitlistRepository::new(pool);
 
     // 2. Crear el servicio
-    let service = Arc::new(AppointmentService::new(repo));
+    let service = Arc::new(AppointmentService::new(
+        repo,
+        schedules,
+        waitlist,
+        LogNotifier,
+        WaitlistPolicy::from_env(),
+    ));
 
     // 3. Construir el router con endpoints
     Router::new()
@@ -25,12 +37,29 @@ pub fn routes_appointment(pool: PgPool) -> Router {
             get(appointment_controller::get_all)
                 .post(appointment_controller::create),
         )
+        .route("/appointments/slots", get(appointment_controller::get_free_slots))
+        .route("/appointments/book", post(appointment_controller::book))
+        .route("/appointments/series", post(appointment_controller::create_series))
+        .route("/appointments/series/{id}", get(appointment_controller::get_series))
+        .route("/appointments/reminders/{token}/confirm", post(appointment_controller::confirm_by_reminder))
+        .route("/appointments/reminders/{token}/cancel", post(appointment_controller::cancel_by_reminder))
         .route(
             "/appointments/{id}",
             get(appointment_controller::get_by_id)
                 .patch(appointment_controller::update)
                 .delete(appointment_controller::delete),
         )
+        .route("/appointments/{id}/confirm", post(appointment_controller::confirm))
+        .route("/appointments/{id}/check_in", post(appointment_controller::check_in))
+        .route("/appointments/{id}/start", post(appointment_controller::start))
+        .route("/appointments/{id}/complete", post(appointment_controller::complete))
+        .route("/appointments/{id}/cancel", post(appointment_controller::cancel))
+        .route("/appointments/{id}/no_show", post(appointment_controller::no_show))
+        .route("/appointments/{id}/status_history", get(appointment_controller::get_status_history))
+        .route("/appointments/{id}/reschedule", post(appointment_controller::reschedule))
+        .route("/appointments/{id}/reschedules", get(appointment_controller::get_reschedules))
+        .route("/appointments/{id}/series", patch(appointment_controller::update_series))
+        .route("/appointments/{id}/series/cancel", post(appointment_controller::cancel_series))
         .with_state(service)    
 
 }
\ No newline at end of file
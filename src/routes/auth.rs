@@ -3,8 +3,8 @@ use sqlx::PgPool;
 use crate::interfaces::auth_controller::{login_handler, logout_handler};
 
 pub fn routes_auth(pool: PgPool) -> Router {
-    return Router::new()
+    Router::new()
         .route("/auth/login", post(login_handler))
         .route("/auth/logout", post(logout_handler))
-        .with_state(pool);
+        .with_state(pool)
 }
\ No newline at end of file
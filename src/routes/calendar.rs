@@ -0,0 +1,32 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::calendar_service::CalendarService,
+    domain::calendar::CalendarPolicy,
+    infrastructure::calendar_repository::PgCalendarRepository,
+    interfaces::calendar_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_calendar(pool: PgPool) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgCalendarRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(CalendarService::new(repo, CalendarPolicy::from_env()));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/calendar/feed",
+            get(calendar_controller::get_feed)
+                .post(calendar_controller::rotate_feed)
+                .delete(calendar_controller::delete_feed),
+        )
+        .route("/calendar/{token}", get(calendar_controller::get_calendar))
+        .with_state(service)
+}
@@ -0,0 +1,28 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::doctor_agenda_service::DoctorAgendaService,
+    helpers::crypto::Keyring,
+    infrastructure::access_log_repository::PgAccessLogRepository,
+    infrastructure::doctor_agenda_repository::PgDoctorAgendaRepository,
+    interfaces::doctor_agenda_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_doctor_agenda(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgDoctorAgendaRepository::new(pool.clone(), keyring);
+    let access_log = PgAccessLogRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(DoctorAgendaService::new(repo, access_log));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route("/doctors/{id}/agenda", get(doctor_agenda_controller::get_agenda))
+        .with_state(service)
+}
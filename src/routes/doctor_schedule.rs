@@ -0,0 +1,42 @@
+use axum::{
+    routing::{delete, get},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::doctor_schedule_service::DoctorScheduleService,
+    infrastructure::doctor_schedule_repository::PgDoctorScheduleRepository,
+    interfaces::doctor_schedule_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_doctor_schedule(pool: PgPool) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgDoctorScheduleRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(DoctorScheduleService::new(repo));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/doctors/{id}/schedules",
+            get(doctor_schedule_controller::get_schedules)
+                .post(doctor_schedule_controller::create_schedule),
+        )
+        .route(
+            "/doctors/{id}/schedules/{id_schedule}",
+            delete(doctor_schedule_controller::delete_schedule),
+        )
+        .route(
+            "/doctors/{id}/exceptions",
+            get(doctor_schedule_controller::get_exceptions)
+                .post(doctor_schedule_controller::create_exception),
+        )
+        .route(
+            "/doctors/{id}/exceptions/{id_exception}",
+            delete(doctor_schedule_controller::delete_exception),
+        )
+        .with_state(service)
+}
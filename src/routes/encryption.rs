@@ -0,0 +1,29 @@
+use axum::{routing::post, Router};
+use std::sync::Arc;
+
+use crate::{
+    application::encryption_service::EncryptionService,
+    helpers::crypto::Keyring,
+    infrastructure::blob_store::LocalBlobStore,
+    infrastructure::encrypted_blob_store::EncryptedBlobStore,
+    infrastructure::medical_document_repository::PgMedicalDocumentRepository,
+    infrastructure::patient_repository::PgPatientRepository,
+    interfaces::encryption_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_encryption(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear los repositorios
+    let patients = PgPatientRepository::new(pool.clone(), keyring.clone());
+    let documents = PgMedicalDocumentRepository::new(pool);
+    let blobs = EncryptedBlobStore::new(LocalBlobStore::from_env(), keyring.clone());
+
+    // 2. Crear el servicio
+    let active_key = keyring.active_id().to_string();
+    let service = Arc::new(EncryptionService::new(patients, documents, blobs, active_key));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route("/admin/encryption/rotate", post(encryption_controller::rotate))
+        .with_state(service)
+}
@@ -7,16 +7,18 @@ use std::sync::Arc;
 use crate::{
     application::lab_result_service::LabResultService,
     infrastructure::lab_result::PgLabResultRepository,
+    infrastructure::access_log_repository::PgAccessLogRepository,
     interfaces::lab_result_controller,
 };
 use sqlx::PgPool;
 
 pub fn routes_lab_result(pool: PgPool) -> Router {
     // 1. Crear el repositorio
-    let repo = PgLabResultRepository::new(pool);
+    let repo = PgLabResultRepository::new(pool.clone());
+    let access_log = PgAccessLogRepository::new(pool);
 
     // 2. Crear el servicio
-    let service = Arc::new(LabResultService::new(repo));
+    let service = Arc::new(LabResultService::new(repo, access_log));
 
     // 3. Construir el router con endpoints
     Router::new()
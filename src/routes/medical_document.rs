@@ -0,0 +1,59 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+use std::{env, sync::Arc};
+
+use crate::{
+    application::medical_document_service::MedicalDocumentService,
+    domain::medical_document::DEFAULT_MAX_DOCUMENT_SIZE,
+    helpers::crypto::Keyring,
+    infrastructure::access_log_repository::PgAccessLogRepository,
+    infrastructure::blob_store::LocalBlobStore,
+    infrastructure::encrypted_blob_store::EncryptedBlobStore,
+    infrastructure::medical_document_repository::PgMedicalDocumentRepository,
+    interfaces::medical_document_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_medical_document(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear el repositorio y el almacenamiento de archivos (cifrado)
+    let repo = PgMedicalDocumentRepository::new(pool.clone());
+    let blobs = EncryptedBlobStore::new(LocalBlobStore::from_env(), keyring);
+    let access_log = PgAccessLogRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(MedicalDocumentService::new(repo, blobs, access_log));
+
+    // Tamaño máximo de subida (bytes), configurable con MAX_DOCUMENT_SIZE
+    let max_size = env::var("MAX_DOCUMENT_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_DOCUMENT_SIZE);
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/medical_documents",
+            post(medical_document_controller::upload),
+        )
+        .route(
+            "/medical_documents/{id}",
+            get(medical_document_controller::get_by_id),
+        )
+        .route(
+            "/medical_documents/{id}/download",
+            get(medical_document_controller::download),
+        )
+        .route(
+            "/medical_documents/{id}/preview",
+            get(medical_document_controller::preview),
+        )
+        .route(
+            "/patients/{id}/documents",
+            get(medical_document_controller::get_by_patient),
+        )
+        .layer(DefaultBodyLimit::max(max_size))
+        .with_state(service)
+}
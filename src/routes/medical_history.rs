@@ -7,16 +7,18 @@ use std::sync::Arc;
 use crate::{
     application::medical_history_service::MedicalHistoryService,
     infrastructure::medical_history_repository::PgMedicalHistoryRepository,
+    infrastructure::access_log_repository::PgAccessLogRepository,
     interfaces::medical_history_controller,
 };
 use sqlx::PgPool;
 
 pub fn routes_medical_history(pool: PgPool) -> Router {
     // 1. Crear el repositorio
-    let repo = PgMedicalHistoryRepository::new(pool);
+    let repo = PgMedicalHistoryRepository::new(pool.clone());
+    let access_log = PgAccessLogRepository::new(pool);
 
     // 2. Crear el servicio
-    let service = Arc::new(MedicalHistoryService::new(repo));
+    let service = Arc::new(MedicalHistoryService::new(repo, access_log));
 
     // 3. Construir el router con endpoints
     Router::new()
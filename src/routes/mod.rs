@@ -7,4 +7,15 @@ pub mod speciality;
 pub mod appointment;
 pub mod medical_history;
 pub mod lab_result;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod medical_document;
+pub mod encryption;
+pub mod patient_contact;
+pub mod registration;
+pub mod portal;
+pub mod patient_import;
+pub mod trash;
+pub mod doctor_schedule;
+pub mod waitlist;
+pub mod calendar;
+pub mod doctor_agenda;
\ No newline at end of file
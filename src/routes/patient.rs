@@ -1,22 +1,27 @@
 use axum::{
-    routing::{get},
+    routing::{get, post},
     Router,
 };
 use std::sync::Arc;
 
 use crate::{
     application::patient_service::PatientService,
+    helpers::crypto::Keyring,
     infrastructure::patient_repository::PgPatientRepository,
+    infrastructure::access_log_repository::PgAccessLogRepository,
+    infrastructure::patient_merge_repository::PgPatientMergeRepository,
     interfaces::patient_controller,
 };
 use sqlx::PgPool;
 
-pub fn routes_patient(pool: PgPool) -> Router {
+pub fn routes_patient(pool: PgPool, keyring: Arc<Keyring>) -> Router {
     // 1. Crear el repositorio
-    let repo = PgPatientRepository::new(pool);
+    let repo = PgPatientRepository::new(pool.clone(), keyring);
+    let access_log = PgAccessLogRepository::new(pool.clone());
+    let merges = PgPatientMergeRepository::new(pool);
 
     // 2. Crear el servicio
-    let service = Arc::new(PatientService::new(repo));
+    let service = Arc::new(PatientService::new(repo, access_log, merges));
 
     // 3. Construir el router con endpoints
     Router::new()
@@ -25,12 +30,23 @@ pub fn routes_patient(pool: PgPool) -> Router {
             get(patient_controller::get_all)
                 .post(patient_controller::create),
         )
+        .route("/patients/search", get(patient_controller::search))
         .route(
             "/patients/{id}",
             get(patient_controller::get_by_id)
                 .patch(patient_controller::update)
                 .delete(patient_controller::delete),
         )
+        .route(
+            "/patients/{id}/access_logs",
+            get(patient_controller::get_access_report),
+        )
+        .route("/patients/{id}/confirm_identity", post(patient_controller::confirm_identity))
+        .route("/patients/{id}/status", post(patient_controller::change_status))
+        .route("/me/dependents", get(patient_controller::get_dependents))
+        .route("/patients/{id}/duplicates", get(patient_controller::find_duplicates))
+        .route("/patients/{id}/merge", post(patient_controller::merge))
+        .route("/patients/merges/{id}/undo", post(patient_controller::unmerge))
         .with_state(service)    
 
 }
\ No newline at end of file
@@ -0,0 +1,34 @@
+use axum::{
+    routing::{get, patch},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::patient_contact_service::PatientContactService,
+    infrastructure::patient_contact_repository::PgPatientContactRepository,
+    interfaces::patient_contact_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_patient_contact(pool: PgPool) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgPatientContactRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(PatientContactService::new(repo));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/patients/{id}/contacts",
+            get(patient_contact_controller::get_by_patient)
+                .post(patient_contact_controller::create),
+        )
+        .route(
+            "/patients/{id}/contacts/{id_contact}",
+            patch(patient_contact_controller::update)
+                .delete(patient_contact_controller::delete),
+        )
+        .with_state(service)
+}
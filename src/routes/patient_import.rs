@@ -0,0 +1,39 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+use std::{env, sync::Arc};
+
+use crate::{
+    application::patient_import_service::PatientImportService,
+    domain::patient_import::DEFAULT_MAX_IMPORT_SIZE,
+    helpers::crypto::Keyring,
+    infrastructure::patient_import_repository::PgPatientImportRepository,
+    infrastructure::patient_repository::PgPatientRepository,
+    interfaces::patient_import_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_patient_import(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear el repositorio
+    let patients = PgPatientRepository::new(pool.clone(), keyring);
+    let imports = PgPatientImportRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(PatientImportService::new(patients, imports));
+
+    // Tamaño máximo del archivo (bytes), configurable con MAX_IMPORT_SIZE
+    let max_size = env::var("MAX_IMPORT_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_IMPORT_SIZE);
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route("/patients/import", post(patient_import_controller::import))
+        .route("/patients/imports/{id}", get(patient_import_controller::get_by_id))
+        .route("/patients/imports/{id}/errors", get(patient_import_controller::error_report))
+        .layer(DefaultBodyLimit::max(max_size))
+        .with_state(service)
+}
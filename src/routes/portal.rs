@@ -0,0 +1,45 @@
+use axum::{
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::portal_service::PortalService,
+    helpers::crypto::Keyring,
+    infrastructure::appointment_repository::PgAppointmentRepository,
+    infrastructure::lab_result::PgLabResultRepository,
+    infrastructure::medical_document_repository::PgMedicalDocumentRepository,
+    infrastructure::medical_history_repository::PgMedicalHistoryRepository,
+    infrastructure::patient_repository::PgPatientRepository,
+    infrastructure::virtual_turn_repository::PgVirtualTurnRepository,
+    interfaces::portal_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_portal(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear los repositorios
+    let patients = PgPatientRepository::new(pool.clone(), keyring);
+    let appointments = PgAppointmentRepository::new(pool.clone());
+    let history = PgMedicalHistoryRepository::new(pool.clone());
+    let lab_results = PgLabResultRepository::new(pool.clone());
+    let documents = PgMedicalDocumentRepository::new(pool.clone());
+    let turns = PgVirtualTurnRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(PortalService::new(patients, appointments, history, lab_results, documents, turns));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/me/profile",
+            get(portal_controller::get_profile)
+                .patch(portal_controller::update_profile),
+        )
+        .route("/me/appointments", get(portal_controller::get_appointments))
+        .route("/me/history", get(portal_controller::get_history))
+        .route("/me/lab-results", get(portal_controller::get_lab_results))
+        .route("/me/documents", get(portal_controller::get_documents))
+        .route("/me/turns", get(portal_controller::get_turns))
+        .with_state(service)
+}
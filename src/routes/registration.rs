@@ -0,0 +1,30 @@
+use axum::{
+    routing::post,
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::registration_service::RegistrationService,
+    helpers::crypto::Keyring,
+    infrastructure::notifier::LogNotifier,
+    infrastructure::patient_repository::PgPatientRepository,
+    infrastructure::registration_repository::PgRegistrationRepository,
+    interfaces::registration_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_registration(pool: PgPool, keyring: Arc<Keyring>) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgRegistrationRepository::new(pool.clone());
+    let patients = PgPatientRepository::new(pool, keyring);
+
+    // 2. Crear el servicio
+    let service = Arc::new(RegistrationService::new(repo, patients, LogNotifier));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route("/auth/register", post(registration_controller::register))
+        .route("/auth/register/{id}/verify", post(registration_controller::verify))
+        .with_state(service)
+}
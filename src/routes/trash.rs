@@ -0,0 +1,28 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::trash_service::TrashService,
+    domain::trash::RetentionPolicy,
+    infrastructure::trash_repository::PgTrashRepository,
+    interfaces::trash_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_trash(pool: PgPool) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgTrashRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(TrashService::new(repo, RetentionPolicy::from_env()));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route("/trash/purge", post(trash_controller::purge))
+        .route("/trash/{entity}", get(trash_controller::list))
+        .route("/trash/{entity}/{id}/restore", post(trash_controller::restore))
+        .with_state(service)
+}
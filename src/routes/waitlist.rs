@@ -0,0 +1,35 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    application::waitlist_service::WaitlistService,
+    domain::waitlist::WaitlistPolicy,
+    infrastructure::notifier::LogNotifier,
+    infrastructure::waitlist_repository::PgWaitlistRepository,
+    interfaces::waitlist_controller,
+};
+use sqlx::PgPool;
+
+pub fn routes_waitlist(pool: PgPool) -> Router {
+    // 1. Crear el repositorio
+    let repo = PgWaitlistRepository::new(pool);
+
+    // 2. Crear el servicio
+    let service = Arc::new(WaitlistService::new(repo, LogNotifier, WaitlistPolicy::from_env()));
+
+    // 3. Construir el router con endpoints
+    Router::new()
+        .route(
+            "/waitlist",
+            get(waitlist_controller::get_all)
+                .post(waitlist_controller::create),
+        )
+        .route("/waitlist/{id}/cancel", post(waitlist_controller::cancel))
+        .route("/waitlist/{id}/offers", get(waitlist_controller::get_offers))
+        .route("/waitlist/offers/{id}/accept", post(waitlist_controller::accept_offer))
+        .route("/waitlist/offers/{id}/decline", post(waitlist_controller::decline_offer))
+        .with_state(service)
+}